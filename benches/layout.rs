@@ -0,0 +1,40 @@
+// Runs of a thousand ticks with the dots kept as Dots and as one array per
+// coordinate, run with `cargo bench --bench layout`.
+extern crate chord_sim;
+extern crate criterion;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use chord_sim::{Chord, Integrator, Layout};
+
+const K: f64 = 1./4096.;
+const TICKS: u64 = 1_000;
+
+// A plucked string of n dots. Chord::new counts segments in a u16, which
+// stops short of the biggest case, so the shape goes in as samples.
+fn string(n: usize) -> Chord {
+    let samples: Vec<f64> = (0..n).map(|i| {
+        let u = i as f64/(n-1) as f64;
+        n as f64*0.375*u.min(1. - u)
+    }).collect();
+    Chord::from_samples(&samples, None, K)
+}
+
+fn split_vs_interleaved(c: &mut Criterion) {
+    for &(integrator, name) in &[(Integrator::Euler, "euler"), (Integrator::Verlet, "verlet")] {
+        let mut group = c.benchmark_group(format!("run_{}/{}", TICKS, name));
+        group.sample_size(10);
+        for &n in &[1_000, 10_000] {
+            for &(layout, label) in &[(Layout::Interleaved, "interleaved"), (Layout::Split, "split")] {
+                let mut chord = string(n);
+                chord.set_integrator(integrator);
+                chord.set_layout(layout);
+                group.bench_with_input(BenchmarkId::new(label, n), &n, |b, _| b.iter(|| chord.run(TICKS)));
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, split_vs_interleaved);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+// Ticks of the same string on the calling thread and split across rayon's
+// pool, run with `cargo bench --bench parallel`, to show where the split
+// starts paying for itself and so where PARALLEL_THRESHOLD belongs.
+extern crate chord_sim;
+extern crate criterion;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use chord_sim::Chord;
+
+const K: f64 = 1./4096.;
+
+// A plucked string of n dots. Chord::new counts segments in a u16, which
+// stops short of the biggest case, so the shape goes in as samples.
+fn string(n: usize) -> Chord {
+    let samples: Vec<f64> = (0..n).map(|i| {
+        let u = i as f64/(n-1) as f64;
+        n as f64*0.375*u.min(1. - u)
+    }).collect();
+    Chord::from_samples(&samples, None, K)
+}
+
+fn sequential_vs_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for &n in &[1_000, 10_000, 100_000] {
+        for &(name, threshold) in &[("sequential", usize::MAX), ("parallel", 0)] {
+            let mut chord = string(n);
+            chord.set_parallel_threshold(threshold);
+            group.bench_with_input(BenchmarkId::new(name, n), &n, |b, _| b.iter(|| chord.tick()));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sequential_vs_parallel);
+criterion_main!(benches);
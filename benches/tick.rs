@@ -0,0 +1,109 @@
+// Timings of the string's hot paths, run with `cargo bench --bench tick`.
+// Criterion times each case; afterwards its medians are printed next to the
+// figures in benches/baseline.txt so a regression shows up as a ratio well
+// over 1. `SAVE_BASELINE=1 cargo bench --bench tick` writes the new figures
+// over the baseline.
+extern crate chord_sim;
+extern crate criterion;
+extern crate serde_json;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::hint::black_box;
+use std::path::PathBuf;
+
+use criterion::{BenchmarkId, Criterion};
+
+use chord_sim::view::{Transform, Viewport};
+use chord_sim::{ChordOf, Scalar, Vect};
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/baseline.txt");
+const K: f64 = 1./4096.;
+// Every case, by the name criterion files it under and the baseline uses.
+const CASES: [&str; 8] = ["tick/80", "tick/1000", "tick/10000", "tick/100000", "tick_f32/1000", "tick_f32/100000",
+                          "tick_1000_in_a_row/1000", "transform/10000"];
+
+// A plucked string of n dots, in f64 or f32. Chord::new counts segments in a
+// u16, which stops short of the biggest case, so the shape goes in as
+// samples.
+fn string<T: Scalar>(n: usize) -> ChordOf<T> {
+    let samples: Vec<T> = (0..n).map(|i| {
+        let u = i as f64/(n-1) as f64;
+        T::of(n as f64*0.375*u.min(1. - u))
+    }).collect();
+    ChordOf::from_samples(&samples, None, T::of(K))
+}
+
+fn benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tick");
+    for &n in &[80, 1_000, 10_000, 100_000] {
+        let mut chord = string::<f64>(n);
+        group.bench_function(BenchmarkId::from_parameter(n), |b| b.iter(|| chord.tick()));
+    }
+    group.finish();
+    // The same strings in single precision.
+    let mut group = c.benchmark_group("tick_f32");
+    for &n in &[1_000, 100_000] {
+        let mut chord = string::<f32>(n);
+        group.bench_function(BenchmarkId::from_parameter(n), |b| b.iter(|| chord.tick()));
+    }
+    group.finish();
+    // A thousand ticks back to back, where the dots stay in cache from one
+    // tick to the next as long as they fit.
+    let mut group = c.benchmark_group("tick_1000_in_a_row");
+    group.sample_size(20);
+    let mut chord = string::<f64>(1_000);
+    group.bench_function(BenchmarkId::from_parameter(1_000), |b| b.iter(|| {
+        for _ in 0..1_000 {
+            chord.tick();
+        }
+    }));
+    group.finish();
+    let mut group = c.benchmark_group("transform");
+    let chord = string::<f64>(10_000);
+    let view = Viewport {x: 0., y: 0., w: 800., h: 600.};
+    group.bench_function(BenchmarkId::from_parameter(10_000), |b| b.iter(|| {
+        let transform = Transform::fit(black_box(&chord), view, 300., 10.);
+        for d in chord.dots() {
+            black_box::<Vect>(transform.apply(d.pos()));
+        }
+    }));
+    group.finish();
+}
+
+// Criterion's median for the case name in nanoseconds, from the estimates
+// it left under the target directory, if it ran that case.
+fn median(name: &str) -> Option<f64> {
+    let target = env::var_os("CARGO_TARGET_DIR").map_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target"), PathBuf::from);
+    let path = target.join("criterion").join(name).join("new").join("estimates.json");
+    let estimates: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+    estimates["median"]["point_estimate"].as_f64()
+}
+
+fn main() {
+    let mut c = Criterion::default().configure_from_args();
+    benches(&mut c);
+    c.final_summary();
+
+    let results: Vec<(&str, f64)> = CASES.iter().filter_map(|&name| median(name).map(|ns| (name, ns))).collect();
+    let baseline: BTreeMap<String, f64> = fs::read_to_string(BASELINE).unwrap_or_default().lines()
+        .filter(|l| !l.starts_with('#'))
+        .filter_map(|l| l.split_once(' ').and_then(|(name, ns)| Some((name.to_string(), ns.trim().parse().ok()?))))
+        .collect();
+    println!("{:<26} {:>14} {:>14} {:>7}", "case", "ns", "baseline ns", "ratio");
+    for &(name, ns) in &results {
+        match baseline.get(name) {
+            Some(&base) => println!("{:<26} {:>14.0} {:>14.0} {:>7.2}", name, ns, base, ns/base),
+            None => println!("{:<26} {:>14.0} {:>14} {:>7}", name, ns, "-", "-"),
+        }
+    }
+    if env::var_os("SAVE_BASELINE").is_some_and(|v| v == "1") && results.len() == CASES.len() {
+        let mut text = String::from("# Median nanoseconds per call, from `SAVE_BASELINE=1 cargo bench --bench tick`.\n");
+        for &(name, ns) in &results {
+            text += &format!("{} {:.0}\n", name, ns);
+        }
+        fs::write(BASELINE, text).expect("couldn't write the baseline");
+        println!("saved {}", BASELINE);
+    }
+}
@@ -0,0 +1,21 @@
+# A plucked string from Python, ticked and looked at: how far its energy
+# drifts, and, with matplotlib about, its shape. Run once the module is
+# installed with maturin develop from python/:
+#     python3 examples/python_chord.py
+import guitar_string
+
+c = guitar_string.Chord.pluck(200, 1e-4, 0.3, 4.0)
+start = c.total_energy()
+c.run(10000)
+y = c.positions_y()
+print("%d dots after t=%g: energy %.6g, was %.6g, moved %.3g%%" % (
+    len(c), c.time, c.total_energy(), start, 100*(c.total_energy() - start)/start))
+print("largest displacement %.4g, pickup at 0.1 %.4g" % (max(abs(v) for v in y), c.pickup(0.1)))
+
+try:
+    import matplotlib.pyplot as plt
+except ImportError:
+    pass
+else:
+    plt.plot(c.positions_x(), y)
+    plt.show()
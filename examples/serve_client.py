@@ -0,0 +1,28 @@
+#!/usr/bin/env python3
+# Draws the string streamed by `chord_sim --serve PORT` in the terminal,
+# one line of JSON at a time. Run it alongside the simulation:
+#
+#     cargo run --release -- --serve 4000 &
+#     python3 examples/serve_client.py 4000
+import json
+import shutil
+import socket
+import sys
+
+port = int(sys.argv[1]) if len(sys.argv) > 1 else 4000
+rows = 21
+
+with socket.create_connection(("127.0.0.1", port)) as sock:
+    for line in sock.makefile("r"):
+        frame = json.loads(line)
+        positions = frame["positions"]
+        cols = shutil.get_terminal_size().columns
+        x0, x1 = positions[0][0], positions[-1][0]
+        height = max(abs(y) for _, y in positions) or 1.
+        grid = [[" "]*cols for _ in range(rows)]
+        for x, y in positions:
+            c = min(cols - 1, int((x - x0)/(x1 - x0 or 1.)*(cols - 1)))
+            r = min(rows - 1, int((1. - y/height)/2.*(rows - 1) + 0.5))
+            grid[r][c] = "o"
+        print("\x1b[H\x1b[J" + "\n".join("".join(r) for r in grid))
+        print("t = {:.0f}, largest displacement {:.3g}".format(frame["t"], height))
@@ -0,0 +1,57 @@
+// Ticks the string at the window's pace and draws it the way the window
+// does: fitted across the canvas between margins, its rest line through
+// the middle and six pixels per unit of displacement.
+import init, { new_chord } from "./pkg/chord_sim.js";
+
+const SEGMENTS = 80;
+const K = 1/4096;
+// Units of simulated time a second, and the most ticks a frame.
+const SIM_SPEED = 500;
+const MAX_STEPS = 200;
+const Y_SCALE = 6;
+
+await init();
+const canvas = document.getElementById("string");
+const context = canvas.getContext("2d");
+const preset = document.getElementById("preset");
+const status = document.getElementById("status");
+let chord = new_chord(SEGMENTS, K, preset.value);
+
+const margin = () => Math.min(canvas.width/16, 50);
+const xScale = () => (canvas.width - 2*margin())/SEGMENTS;
+
+preset.addEventListener("change", () => {
+  chord.free();
+  chord = new_chord(SEGMENTS, K, preset.value);
+});
+document.getElementById("reset").addEventListener("click", () => chord.reset());
+canvas.addEventListener("click", event => {
+  const box = canvas.getBoundingClientRect();
+  const u = (event.clientX - box.left - margin())/(canvas.width - 2*margin());
+  chord.excite(u, 1);
+});
+
+let last = performance.now();
+let lag = 0;
+function frame(now) {
+  lag += (now - last)/1000*SIM_SPEED;
+  last = now;
+  const steps = Math.min(Math.floor(lag), MAX_STEPS);
+  chord.tick(steps);
+  lag = steps === MAX_STEPS ? 0 : lag - steps;
+
+  const xy = chord.positions();
+  context.fillStyle = "#000";
+  context.fillRect(0, 0, canvas.width, canvas.height);
+  context.strokeStyle = "#fff";
+  context.beginPath();
+  for (let i = 0; i < xy.length; i += 2) {
+    const x = margin() + xy[i]*xScale();
+    const y = canvas.height/2 + xy[i + 1]*Y_SCALE;
+    if (i === 0) context.moveTo(x, y); else context.lineTo(x, y);
+  }
+  context.stroke();
+  status.textContent = `t = ${chord.time().toFixed(0)}, energy ${chord.total_energy().toExponential(3)}`;
+  requestAnimationFrame(frame);
+}
+requestAnimationFrame(frame);
@@ -0,0 +1,87 @@
+# The module as Python sees it: the README's pluck, the arrays coming back
+# through the buffer protocol, and the arguments it turns down.
+import array
+
+import pytest
+
+import guitar_string
+
+
+def test_a_pluck_runs_and_keeps_its_energy():
+    c = guitar_string.Chord.pluck(200, 1e-4, 0.3, 4.0)
+    start = c.total_energy()
+    c.run(10000)
+    y = c.positions_y()
+    assert len(c) == 201
+    assert len(y) == 201
+    assert c.time == 10000.0
+    assert y[0] == 0.0 and y[-1] == 0.0
+    assert 0 < max(abs(v) for v in y) <= 4.0
+    assert abs(c.total_energy() - start) < 1e-6*start
+
+
+def test_positions_are_a_buffer_of_doubles():
+    c = guitar_string.Chord.sine(50, 1e-4)
+    for values in (c.positions_x(), c.positions_y(), c.positions_z(),
+                   c.velocities_x(), c.velocities_y(), c.velocities_z()):
+        assert isinstance(values, array.array)
+        assert values.typecode == "d"
+        view = memoryview(values)
+        assert view.format == "d" and view.itemsize == 8 and len(view) == 51
+    x = c.positions_x()
+    assert list(x) == sorted(x)
+    assert c.positions_y()[25] == pytest.approx(c.pickup(0.5))
+
+
+def test_ticking_and_resetting():
+    c = guitar_string.Chord(80, 1e-4)
+    before = list(c.positions_y())
+    c.tick()
+    c.run(99)
+    assert c.time == 100.0
+    assert list(c.positions_y()) != before
+    c.reset()
+    assert c.time == 0.0
+    assert list(c.positions_y()) == before
+
+
+def test_exciting_adds_a_triangle():
+    c = guitar_string.Chord.strike(40, 1e-4, 0.5, 0.1, 0.0)
+    assert max(c.positions_y()) == 0.0
+    flat = c.potential_energy()
+    c.excite(0.25, 2.0)
+    y = c.positions_y()
+    assert max(y) == pytest.approx(2.0)
+    assert y.index(max(y)) == 10
+    assert c.potential_energy() > flat and c.kinetic_energy() == 0.0
+
+
+def test_damping_takes_energy_out():
+    damped = guitar_string.Chord.pluck(100, 1e-4, 0.5, 4.0)
+    undamped = guitar_string.Chord.pluck(100, 1e-4, 0.5, 4.0)
+    start = damped.total_energy()
+    damped.set_damping(1e-3)
+    damped.run(5000)
+    undamped.run(5000)
+    assert damped.total_energy() < undamped.total_energy() - 1e-3*start
+
+
+@pytest.mark.parametrize("make", [
+    lambda: guitar_string.Chord(1, 1e-4),
+    lambda: guitar_string.Chord(80, 0.0),
+    lambda: guitar_string.Chord.sine(80, float("inf")),
+    lambda: guitar_string.Chord.pluck(80, 1e-4, 1.0, 4.0),
+    lambda: guitar_string.Chord.pluck(80, 1e-4, 0.5, float("nan")),
+    lambda: guitar_string.Chord.strike(80, 1e-4, 0.5, 0.0, 1.0),
+])
+def test_bad_strings_raise_value_error(make):
+    with pytest.raises(ValueError):
+        make()
+
+
+def test_bad_settings_raise_value_error():
+    c = guitar_string.Chord(80, 1e-4)
+    with pytest.raises(ValueError):
+        c.excite(0.0, 1.0)
+    with pytest.raises(ValueError):
+        c.set_damping(-1.0)
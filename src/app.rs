@@ -0,0 +1,1638 @@
+// The window's run: the state it keeps from frame to frame, and what each
+// frame does with the strings and the input that came in.
+use std::f64;
+use std::thread;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use {Chord, Guitar, KarplusStrong, Membrane, StringModel};
+#[cfg(feature = "audio")]
+use audio;
+use cli;
+use config;
+use csv;
+use decay;
+use envelope;
+use gamepad;
+use guitar::{pluck_shape, pluck_shape_at};
+use headless;
+use history;
+use hud;
+use midi;
+use pair;
+use physics;
+use pitch;
+use render::Key;
+use screen::{Screen, Theme, ENVELOPE_PERIODS, MODE_COUNT, MODES_EVERY, RECORD_DIR, RECORD_EVERY, SCOPE_SAMPLES, TRAIL_STEP, ZOOM_STEP};
+use script;
+use serve;
+use setup::{divergence, initial_strings, preset, timestamp, warn_if_unstable, DECAY_WINDOW, PICKUP_POS};
+use stats;
+use trajectory;
+use wav;
+
+const DAMPING: f64 = 1./(1<<11) as f64;
+// Ticks between strings in a strum.
+const STRUM_DELAY: u64 = 25;
+// Height of the noise burst a Karplus-Strong line compared with the string
+// is plucked with, about the size of a P pluck's signal at the pickup.
+const KS_NOISE: f64 = 5.;
+// Height of the fretboard toggled with F, just below the resting string.
+const BARRIER: f64 = 2.;
+// Starting strength, frequency and place of the driver toggled with X. The
+// frequency is close to the default string's fundamental.
+const DRIVE_AMPLITUDE: f64 = 1./(1<<13) as f64;
+const DRIVE_FREQUENCY: f64 = 7e-5;
+const DRIVE_POS: f64 = 0.25;
+// Magnet toggled with \: under the pickup, its distance below the string and
+// its strength, which sags the default string about 0.13 toward it and
+// lowers the pitch about 1%.
+const MAGNET_OFFSET: f64 = 3.;
+const MAGNET_STRENGTH: f64 = 2e-6;
+// Bow toggled with B: position along the string, speed and grip.
+const BOW_POS: f64 = 0.1;
+const BOW_SPEED: f64 = 1e-3;
+const BOW_FORCE: f64 = 4e-4;
+// How many of the pickup's samples the recorder keeps around.
+const RECORD_LIMIT: usize = 1<<20;
+// Exported audio plays the simulation at AUDIO_SPEED units of simulated time
+// per second, which puts the default string's fundamental near A2, and is
+// resampled from the tick rate that implies to WAV_RATE.
+const AUDIO_SPEED: f64 = 1.6e6;
+const WAV_RATE: u32 = 44100;
+// Ticks of pickup signal the pitch readout looks back over, and how often
+// the caption showing it is refreshed.
+const PITCH_WINDOW: u64 = 1<<16;
+const CAPTION_EVERY: Duration = Duration::from_millis(500);
+// Fret positions, as fractions of the string, held down by the number keys.
+const FRETS: [(Key, f64); 3] = [(Key::Num1, 1./2.), (Key::Num2, 2./3.), (Key::Num3, 3./4.)];
+// Frames the frame and tick rates are averaged over.
+const RATE_WINDOW: usize = 60;
+
+// Rolling frames and physics ticks per second over the last RATE_WINDOW
+// frames.
+struct FrameRate {
+    frames: VecDeque<(Instant, u64)>,
+}
+
+impl FrameRate {
+    fn new() -> FrameRate {
+        FrameRate {frames: VecDeque::with_capacity(RATE_WINDOW + 1)}
+    }
+    // Notes a frame finishing at now after running ticks physics ticks.
+    fn frame(&mut self, now: Instant, ticks: u64) {
+        self.frames.push_back((now, ticks));
+        if self.frames.len() > RATE_WINDOW + 1 {
+            self.frames.pop_front();
+        }
+    }
+    // Frames and ticks per second, once there are two frames to go on.
+    fn rates(&self) -> Option<(f64, f64)> {
+        let (first, last) = (self.frames.front()?.0, self.frames.back()?.0);
+        let span = (last - first).as_secs_f64();
+        if span <= 0. {
+            return None;
+        }
+        let ticks: u64 = self.frames.iter().skip(1).map(|&(_, t)| t).sum();
+        Some(((self.frames.len()-1) as f64/span, ticks as f64/span))
+    }
+}
+
+// How long before a frame's deadline the pacer stops sleeping and spins,
+// since a sleep can overshoot by about this much.
+const SPIN: Duration = Duration::from_micros(500);
+
+// Holds frames to at most fps a second. Each frame's work is timed, and
+// the rest of its budget slept away, spinning for the last moments so the
+// frames come out evenly. A frame that runs over its budget doesn't wait
+// at all and counts as late.
+struct FramePacer {
+    budget: Option<Duration>,
+    start: Instant,
+    // Time the last frame spent working, before any waiting.
+    work: Duration,
+    late: u64,
+}
+
+impl FramePacer {
+    // A pacer for fps frames a second, or with no limit if fps is 0.
+    fn new(fps: f64) -> FramePacer {
+        let budget = if fps > 0. {Some(Duration::from_secs_f64(1./fps))} else {None};
+        FramePacer {budget, start: Instant::now(), work: Duration::ZERO, late: 0}
+    }
+    // Ends the frame begun by the last call, waiting out what's left of
+    // its budget, and starts timing the next one.
+    fn wait(&mut self) {
+        let now = Instant::now();
+        self.work = now - self.start;
+        let deadline = match self.budget {
+            Some(budget) => self.start + budget,
+            None => now,
+        };
+        if now >= deadline {
+            if self.budget.is_some() {
+                self.late += 1;
+            }
+            // Give the audio thread its chance at the string all the same.
+            thread::yield_now();
+            self.start = Instant::now();
+            return;
+        }
+        if deadline - now > SPIN {
+            thread::sleep(deadline - now - SPIN);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+        self.start = deadline;
+    }
+}
+
+// Simulation speeds stepped through by comma and slash, as multiples of
+// SIM_SPEED, and the one the program starts at.
+const SPEEDS: [f64; 7] = [0.1, 0.2, 0.5, 1., 2., 5., 10.];
+const NORMAL_SPEED: usize = 3;
+// Factor the whammy bar scales k by while Up (or, dividing, Down) is held.
+const WHAMMY: f64 = 1.25;
+// Semitones a full MIDI pitch bend takes the string either way.
+const MIDI_BEND: f64 = 2.;
+// Smallest and largest multiple of the panel's pluck height the gamepad's
+// right trigger plucks to, released and pulled all the way.
+const GAMEPAD_PLUCK: (f64, f64) = (0.1, 2.);
+// How long the gamepad's controls show in the panel after it's plugged in.
+const GAMEPAD_NOTICE: Duration = Duration::from_secs(5);
+// The gamepad's controls, as the panel lists them.
+const GAMEPAD_HELP: [&str; 4] = ["gamepad", "left stick y: bend k", "A: pluck at left stick x", "right trigger: pluck height"];
+// Mass ratio 7 tapers the string to when --taper leaves it uniform, and how
+// long the glide between the two takes.
+const TAPER: f64 = 4.;
+const TAPER_GLIDE: Duration = Duration::from_secs(3);
+// Palm mute held with M: where along the string it starts, and its damping
+// as a multiple of the string's impedance, which gives about the quickest
+// decay.
+const MUTE_FROM: f64 = 0.8;
+const MUTE_STRENGTH: f64 = 0.27;
+// Dots along each side of the membrane shown with U, and the radius in dot
+// spacings and peak speed of a strike on it, or the height of its round
+// mode.
+const MEMBRANE_DOTS: usize = 61;
+const STRIKE_RADIUS: f64 = 4.;
+const STRIKE_SPEED: f64 = 0.05;
+const CIRCULAR_AMPLITUDE: f64 = 3.;
+// Starting shapes picked by the keypad keys.
+const PRESETS: [Key; 5] = [Key::Kp1, Key::Kp2, Key::Kp3, Key::Kp4, Key::Kp5];
+// Simulated time units per second of wall-clock time, and the most physics
+// steps a single frame may run before the backlog is dropped.
+const SIM_SPEED: f64 = 500.;
+const MAX_STEPS: u32 = 200;
+// Snapshots a second the physics thread sends for drawing.
+const SNAPSHOT_RATE: f64 = 100.;
+// Frames between energy printouts while they're enabled.
+const ENERGY_EVERY: u64 = 60;
+// Modes logged with Y, and the ticks between rows.
+const LOG_MODES: usize = 16;
+const LOG_EVERY: u64 = 100;
+
+// Collects the pickup signal at one point of a string, one sample per
+// tick. Only the newest limit samples are guaranteed to be kept.
+pub(crate) struct Recorder {
+    position: f64,
+    limit: usize,
+    samples: Vec<f64>,
+}
+
+impl Recorder {
+    pub(crate) fn new(position: f64, limit: usize) -> Recorder {
+        Recorder {position, limit, samples: Vec::new()}
+    }
+    pub(crate) fn record<M: StringModel>(&mut self, model: &M) {
+        self.push(model.pickup(self.position));
+    }
+    pub(crate) fn push(&mut self, sample: f64) {
+        self.samples.push(sample);
+        if self.samples.len() >= 2*self.limit {
+            let excess = self.samples.len() - self.limit;
+            self.samples.drain(..excess);
+        }
+    }
+    pub(crate) fn latest(&self) -> f64 {
+        self.samples.last().cloned().unwrap_or(0.)
+    }
+    pub(crate) fn samples(&self) -> &[f64] {
+        &self.samples
+    }
+}
+
+// Energies of the string's first modes, written to a CSV file as it runs,
+// a row whenever another LOG_EVERY ticks have gone by.
+struct ModeLog {
+    csv: csv::CsvWriter<BufWriter<File>>,
+    next: u64,
+}
+
+impl ModeLog {
+    fn create(path: &Path) -> io::Result<ModeLog> {
+        let columns: Vec<String> = (1..LOG_MODES+1).map(|m| format!("mode{}", m)).collect();
+        let csv = csv::CsvWriter::new(BufWriter::new(File::create(path)?), &columns)?;
+        Ok(ModeLog {csv, next: 0})
+    }
+    // Writes a row for chord if it's due. A string that has gone back in
+    // time, by a reset or a load, starts the count again.
+    fn log(&mut self, chord: &Chord) -> io::Result<()> {
+        let ticks = chord.ticks();
+        if ticks + LOG_EVERY <= self.next {
+            self.next = ticks;
+        }
+        if ticks < self.next {
+            return Ok(());
+        }
+        self.next = ticks + LOG_EVERY;
+        self.csv.row(ticks, &chord.mode_energies(LOG_MODES))
+    }
+}
+
+// Lines of the settings panel for chord, the focused setting picked out in
+// the accent colour and a warning added once k is past what the integrator
+// can take at this dt.
+fn panel(chord: &Chord, focus: hud::Param, pluck_scale: f64, pluck_position: f64, theme: &Theme) -> Vec<(String, (u8, u8, u8))> {
+    let mut lines: Vec<(String, (u8, u8, u8))> = hud::PARAMS.iter().map(|&param| {
+        let value = match param {
+            hud::Param::K => chord.target_k(),
+            hud::Param::Damping => chord.damping(),
+            hud::Param::Dt => chord.dt(),
+            hud::Param::PluckScale => pluck_scale,
+            hud::Param::PluckPosition => pluck_position,
+        };
+        (param.show(value), if param == focus {theme.accent} else {theme.guide})
+    }).collect();
+    let limit = chord.max_stable_k(chord.dt());
+    if chord.target_k() > limit {
+        lines.push((format!("unstable past k {:.3e}", limit), theme.fixed));
+    }
+    lines
+}
+
+// Whether pressing k does something to the lone string that undo should be
+// able to take back, as against changing only what's shown or how fast.
+fn is_edit(k: Key, chord: &Chord) -> bool {
+    match k {
+        Key::P | Key::R | Key::L | Key::I | Key::Q | Key::H | Key::F | Key::X | Key::B | Key::Backslash
+            | Key::Up | Key::Down => true,
+        Key::PageUp | Key::PageDown => chord.is_driven(),
+        _ => PRESETS.contains(&k) || FRETS.iter().any(|&(key, _)| key == k),
+    }
+}
+
+// Rests a palm on or lifts it off the end of the string near the bridge.
+fn palm_mute(chord: &mut Chord, on: bool) {
+    match (on, chord.is_muted()) {
+        (true, false) => {
+            let damping = MUTE_STRENGTH*chord.impedance();
+            chord.set_mute(MUTE_FROM, damping);
+        },
+        (false, true) => chord.clear_mute(),
+        _ => {},
+    }
+}
+
+// Ticks model steps times, recording its pickups and feeding the first to
+// the pitch and decay meters.
+fn tick_model<M: StringModel>(model: &mut M, steps: u32, recorder: &mut Recorder, mut second: Option<&mut Recorder>,
+                              tracker: &mut pitch::PitchTracker, decay: &mut decay::DecayMeter) {
+    for _ in 0..steps {
+        model.tick();
+        recorder.record(model);
+        if let Some(ref mut r) = second {
+            r.record(model);
+        }
+        tracker.feed(recorder.latest());
+        decay.feed(recorder.latest());
+    }
+}
+
+// A Karplus-Strong line tuned to chord's fundamental and freshly plucked,
+// running at the WAV export's rate.
+fn compare_with(chord: &Chord) -> KarplusStrong {
+    let mut ks = KarplusStrong::tuned(chord.linear_fundamental(), chord.dt(), WAV_RATE as f64/AUDIO_SPEED);
+    ks.pluck_noise(KS_NOISE);
+    ks
+}
+
+// Newest string-<timestamp>.json in the working directory.
+fn latest_save() -> Option<PathBuf> {
+    fs::read_dir(".").ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let stamp = name.strip_prefix("string-")?.strip_suffix(".json")?.parse::<u64>().ok()?;
+            Some((stamp, entry.path()))
+        })
+        .max_by_key(|&(stamp, _)| stamp)
+        .map(|(_, path)| path)
+}
+
+// Why a run ended early: what to tell the user, and the status to exit
+// with.
+#[derive(Debug)]
+pub struct Failure {
+    pub message: String,
+    pub status: i32,
+}
+
+impl Failure {
+    fn new(message: String, status: i32) -> Failure {
+        Failure {message, status}
+    }
+}
+
+// Runs whatever options ask for: one of the tests, a headless run, a
+// replay or the window.
+pub fn run(options: &cli::Options) -> Result<(), Failure> {
+    let script = load_script(options)?;
+    let (mut chord, pair) = strings(options);
+    if let Some(ticks) = options.time_reversal {
+        headless::time_reversal_test(&mut chord, ticks);
+        return Ok(());
+    }
+    if options.wave_speed_test {
+        return headless::wave_speed_test(&chord).map_err(|message| Failure::new(message, 1));
+    }
+    if options.modes_test {
+        headless::modes_test(&chord);
+        return Ok(());
+    }
+    let server = start_server(options)?;
+    let stats = open_stats(options)?;
+    if let Some(ref h) = options.headless {
+        return headless::run_headless(&mut chord, h, script, server, stats)
+            .map_err(|err| Failure::new(format!("headless run failed: {}", err), 1));
+    }
+    let replayed = match options.replay {
+        Some(ref path) => Some(trajectory::Trajectory::load(path).map_err(|err| Failure::new(err.to_string(), 1))?),
+        None => None,
+    };
+    let mut screen = open_screen(options)?;
+    if let Some(ref trajectory) = replayed {
+        replay(&mut screen, trajectory, options.fps);
+        return Ok(());
+    }
+    App::with_strings(screen, chord, pair, options, script, server, stats).run();
+    Ok(())
+}
+
+// The script options name, if they name one.
+fn load_script(options: &cli::Options) -> Result<Option<script::Script>, Failure> {
+    match options.script {
+        Some(ref path) => script::Script::load(path).map(Some).map_err(|err| Failure::new(err.to_string(), 2)),
+        None => Ok(None),
+    }
+}
+
+// The strings options ask for, with a warning for any that will blow up.
+fn strings(options: &cli::Options) -> (Chord, Option<pair::Pair>) {
+    let (chord, pair) = initial_strings(options);
+    warn_if_unstable(&chord);
+    if let Some(ref p) = pair {
+        warn_if_unstable(&p.other);
+    }
+    (chord, pair)
+}
+
+fn start_server(options: &cli::Options) -> Result<Option<serve::Server>, Failure> {
+    match options.serve {
+        Some(port) => match serve::Server::start(port, options.serve_rate) {
+            Ok(server) => {
+                println!("streaming the string on 127.0.0.1:{}", port);
+                Ok(Some(server))
+            },
+            Err(err) => Err(Failure::new(format!("failed to listen on port {}: {}", port, err), 1)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn open_stats(options: &cli::Options) -> Result<Option<stats::StatsLog>, Failure> {
+    match options.log_stats {
+        Some(ref path) => match stats::StatsLog::create(path, options.log_every, PICKUP_POS) {
+            Ok(log) => Ok(Some(log)),
+            Err(err) => Err(Failure::new(format!("failed to create {}: {}", path.display(), err), 1)),
+        },
+        None => Ok(None),
+    }
+}
+
+// The window options ask for, in the theme they ask for.
+fn open_screen(options: &cli::Options) -> Result<Screen, Failure> {
+    let backend = options.backend.ok_or_else(|| Failure::new(
+        "chord_sim was built without a window; --headless runs the string without one.".to_string(), 1))?;
+    let mut screen = Screen::new(options.width, options.height, backend)
+        .map_err(|err| Failure::new(format!("{}\n--headless runs the string without a window.", err), 1))?;
+    screen.theme = options.theme;
+    Ok(screen)
+}
+
+// Plays a recorded trajectory in the window, at the pace the string ran at
+// live, until Escape.
+fn replay(screen: &mut Screen, trajectory: &trajectory::Trajectory, fps: f64) {
+    let frames = trajectory.frames();
+    // Frames a second at normal speed.
+    let rate = SIM_SPEED/(trajectory.every as f64*trajectory.dt);
+    let mut pacer = FramePacer::new(fps);
+    let mut position = 0.;
+    let mut paused = false;
+    let mut speed = NORMAL_SPEED;
+    let mut last = Instant::now();
+    let mut shown = None;
+    let mut chord = trajectory.frame(0);
+    loop {
+        let now = Instant::now();
+        if !paused {
+            position += (now - last).as_secs_f64()*rate*SPEEDS[speed];
+        }
+        last = now;
+        // It stops on the last frame.
+        if position >= (frames-1) as f64 {
+            position = (frames-1) as f64;
+            paused = true;
+        }
+        let i = position as usize;
+        if shown != Some(i) {
+            chord = trajectory.frame(i);
+            screen.renderer.set_title(&format!("Replay - frame {} of {} - tick {}", i + 1, frames, i as u64*trajectory.every));
+            shown = Some(i);
+        }
+        screen.draw(&chord, &[]);
+        screen.tick();
+        if screen.should_end {break;}
+        screen.steer_camera(&chord);
+        for k in screen.pressed.clone() {
+            if k == Key::Home {
+                screen.camera = None;
+            }
+            if k == Key::Space {
+                // Playing on from the end starts over.
+                if paused && i == frames-1 {
+                    position = 0.;
+                }
+                paused = !paused;
+            }
+            if k == Key::Left || k == Key::Right {
+                paused = true;
+                let j = if k == Key::Left {i.saturating_sub(1)} else {(i + 1).min(frames-1)};
+                position = j as f64;
+            }
+            if k == Key::Comma || k == Key::Slash {
+                speed = if k == Key::Comma {speed.saturating_sub(1)} else {(speed + 1).min(SPEEDS.len()-1)};
+                println!("speed {}x", SPEEDS[speed]);
+            }
+            if k == Key::Equals || k == Key::KpPlus {
+                screen.zoom(ZOOM_STEP);
+            }
+            if k == Key::Minus || k == Key::KpMinus {
+                screen.zoom(1./ZOOM_STEP);
+            }
+        }
+        pacer.wait();
+    }
+}
+
+// Everything the window keeps from one frame to the next: the lone string
+// and what's run beside or in place of it, the threads ticking it, the
+// input coming in, and what's being shown.
+pub struct App {
+    screen: Screen,
+    // The lone string, shared with the thread ticking it, and the latest
+    // snapshot of it to draw.
+    shared: Arc<Mutex<Chord>>,
+    latest: Arc<Chord>,
+    // The string it's compared with, beside it, if it is.
+    pair: Option<pair::Pair>,
+    comparing: bool,
+    script: Option<script::Script>,
+    server: Option<serve::Server>,
+    stats: Option<stats::StatsLog>,
+    // With audio the physics thread owns ticking and the window only draws.
+    #[cfg(feature = "audio")]
+    audio: Option<audio::Audio>,
+    realtime: bool,
+    // Without audio the string gets a physics thread of its own. The
+    // window draws its snapshots, and only takes the lock to change it.
+    physics: Option<physics::Physics>,
+    midi: Option<midi::Midi>,
+    midi_mute: bool,
+    gamepad: Option<gamepad::Gamepad>,
+    // When the gamepad was last plugged in, while its controls show.
+    gamepad_plugged: Option<Instant>,
+    last: Instant,
+    lag: f64,
+    frame: u64,
+    print_energy: bool,
+    drive_frequency: f64,
+    recorder: Recorder,
+    // What the second pickup hears, when there is one.
+    second: Option<Recorder>,
+    tracker: pitch::PitchTracker,
+    decay: decay::DecayMeter,
+    captioned: Instant,
+    frame_rate: FrameRate,
+    pacer: FramePacer,
+    last_time: f64,
+    show_modes: bool,
+    show_nodes: bool,
+    envelope: envelope::Envelope,
+    mode_log: Option<ModeLog>,
+    guitar: Option<Guitar>,
+    // Drum head shown in place of the string with U.
+    membrane: Option<Membrane>,
+    // Karplus-Strong line run in step with the lone string with ;, and its
+    // signal.
+    comparison: Option<KarplusStrong>,
+    compared: Recorder,
+    paused: bool,
+    step: bool,
+    speed: usize,
+    steps_per_frame: u32,
+    // k of the string before the whammy bar was pushed, while it is.
+    whammy_base: Option<f64>,
+    // Taper 7 glides the string from and to, and when it started, while it
+    // does.
+    taper_glide: Option<(f64, f64, Instant)>,
+    // Dot held by the mouse, if any.
+    grabbed: Option<usize>,
+    // Note last played over MIDI, and the k it tuned the string to; k the
+    // pitch bend works from; and whether the note's release muted it.
+    midi_note: Option<u8>,
+    midi_k: Option<f64>,
+    midi_bend: f64,
+    midi_muted: bool,
+    // Settings panel shown with Tab, and how hard and where P plucks.
+    hud: hud::Hud,
+    // The lone string as it was before each thing done to it by hand, for
+    // Ctrl+Z.
+    history: history::History,
+    pluck_scale: f64,
+    pluck_position: f64,
+    // Settings in effect, which an edited config file changes.
+    settings: cli::Options,
+    watch: config::Watch,
+}
+
+impl App {
+    // The window's run of the strings options ask for, drawn on screen.
+    pub fn new(screen: Screen, options: &cli::Options) -> Result<App, Failure> {
+        let script = load_script(options)?;
+        let (chord, pair) = strings(options);
+        let server = start_server(options)?;
+        let stats = open_stats(options)?;
+        Ok(App::with_strings(screen, chord, pair, options, script, server, stats))
+    }
+    fn with_strings(mut screen: Screen, chord: Chord, pair: Option<pair::Pair>, options: &cli::Options,
+                    script: Option<script::Script>, server: Option<serve::Server>, stats: Option<stats::StatsLog>) -> App {
+        let shared = Arc::new(Mutex::new(chord));
+        let pickups = options.pickups.map_or((PICKUP_POS, None), |(u, v)| (u, Some(v)));
+        #[cfg(feature = "audio")]
+        let audio = audio::Audio::start(shared.clone(), pickups, AUDIO_SPEED)
+            .map_err(|err| eprintln!("audio disabled: {}", err)).ok();
+        #[cfg(feature = "audio")]
+        let realtime = audio.is_some();
+        #[cfg(not(feature = "audio"))]
+        let realtime = false;
+        let physics = if realtime {
+            None
+        } else {
+            Some(physics::Physics::start(shared.clone(), SIM_SPEED, SNAPSHOT_RATE, pickups))
+        };
+        // A fixed number of steps per frame has the window tick the string,
+        // and so does a comparison, so the two strings keep in step.
+        let comparing = pair.is_some();
+        if options.steps_per_frame > 0 || comparing {
+            #[cfg(feature = "audio")]
+            if let Some(ref a) = audio {
+                a.set_paused(true);
+            }
+            if let Some(ref physics) = physics {
+                physics.send(physics::Command::Pause(true));
+            }
+        }
+        let midi = options.midi.as_ref().and_then(|path| midi::Midi::open(path)
+            .map_err(|err| eprintln!("MIDI disabled: {}: {}", path.display(), err)).ok());
+        let gamepad = options.gamepad.as_ref().map(|path| gamepad::Gamepad::watch(path));
+        let latest = Arc::new(shared.lock().unwrap().clone());
+        screen.draw(&latest, &[]);
+        App {screen, shared, latest, pair, comparing, script, server, stats,
+            #[cfg(feature = "audio")]
+            audio,
+            realtime, physics, midi, midi_mute: options.midi_mute, gamepad, gamepad_plugged: None,
+            last: Instant::now(), lag: 0., frame: 0, print_energy: false, drive_frequency: DRIVE_FREQUENCY,
+            recorder: Recorder::new(pickups.0, RECORD_LIMIT), second: pickups.1.map(|u| Recorder::new(u, RECORD_LIMIT)),
+            tracker: pitch::PitchTracker::new(PITCH_WINDOW), decay: decay::DecayMeter::new(DECAY_WINDOW),
+            captioned: Instant::now(), frame_rate: FrameRate::new(), pacer: FramePacer::new(options.fps), last_time: 0.,
+            show_modes: false, show_nodes: false, envelope: envelope::Envelope::default(), mode_log: None,
+            guitar: None, membrane: None, comparison: None, compared: Recorder::new(PICKUP_POS, RECORD_LIMIT),
+            paused: false, step: false, speed: NORMAL_SPEED, steps_per_frame: options.steps_per_frame,
+            whammy_base: None, taper_glide: None, grabbed: None,
+            midi_note: None, midi_k: None, midi_bend: 1., midi_muted: false,
+            hud: hud::Hud::default(), history: history::History::default(), pluck_scale: 1., pluck_position: 0.5,
+            settings: options.clone(), watch: config::Watch::new(options.config.clone())}
+    }
+    // Runs frames until the window closes.
+    pub fn run(mut self) {
+        while self.frame() {}
+        if let Some(physics) = self.physics.take() {
+            physics.stop();
+        }
+        if let Some(Err(err)) = self.stats.take().map(|s| s.finish()) {
+            eprintln!("failed to finish the statistics log: {}", err);
+        }
+    }
+    // The lone string as it stands.
+    pub fn chord(&self) -> Chord {
+        self.shared.lock().unwrap().clone()
+    }
+    // Runs one frame: ticks what's due, takes in the script, MIDI and
+    // gamepad, draws, then does what the window's input asks. False once
+    // the window should close.
+    pub fn frame(&mut self) -> bool {
+        self.frame += 1;
+        let now = Instant::now();
+        let steps = self.advance(now);
+        let quit = self.run_script();
+        self.read_midi();
+        self.read_gamepad(now);
+        self.show(now, steps);
+        self.screen.tick();
+        if self.screen.should_end || quit {
+            return false;
+        }
+        let shared = self.shared.clone();
+        let mut guard = shared.lock().unwrap();
+        let chord = &mut *guard;
+        self.reload_config(chord);
+        self.handle_held(chord);
+        for k in self.screen.pressed.clone() {
+            self.handle_key(k, chord);
+        }
+        for k in self.screen.released.clone() {
+            for chord in pair::both(chord, &mut self.pair) {
+                for &(key, u) in &FRETS {
+                    if k == key {
+                        let i = chord.index_at(u);
+                        chord.unfret(i);
+                    }
+                }
+            }
+        }
+        if let Some(ref mut p) = self.pair {
+            p.hold(chord);
+        }
+        drop(guard);
+        self.pacer.wait();
+        true
+    }
+    // Ticks whatever this thread ticks for the time gone by since the last
+    // frame, and picks up the snapshots the other threads sent meanwhile.
+    // How many ticks ran here.
+    fn advance(&mut self, now: Instant) -> u32 {
+        // The guitar and membrane tick here, and so does the string while
+        // paused with audio, since the audio thread then stands still.
+        // So does everything with a fixed number of steps per frame, or
+        // beside a Karplus-Strong line or another string, which has the
+        // other threads stand still.
+        let ticked_here = self.guitar.is_some() || self.membrane.is_some() || (self.realtime && self.paused) || self.steps_per_frame > 0
+            || self.comparison.is_some() || self.comparing;
+        if !self.paused && ticked_here {
+            self.lag += (now - self.last).as_secs_f64()*SIM_SPEED*SPEEDS[self.speed];
+        }
+        self.last = now;
+        let dt = match (&self.membrane, &self.guitar) {
+            (Some(m), _) => m.dt(),
+            (None, Some(g)) => g.strings()[0].dt(),
+            (None, None) => self.latest.dt(),
+        };
+        // Ticks to run here this frame: one for a step, steps_per_frame when
+        // that's set, and otherwise as many as the clock has made due.
+        let steps = if self.step {
+            1
+        } else if !ticked_here || self.paused {
+            0
+        } else if self.steps_per_frame > 0 {
+            self.steps_per_frame
+        } else {
+            (self.lag/dt).floor().min(MAX_STEPS as f64) as u32
+        };
+        let ticking = Instant::now();
+        if let Some(ref mut m) = self.membrane {
+            for _ in 0..steps {
+                m.tick();
+            }
+        }
+        match self.guitar {
+            Some(ref mut g) => tick_model(g, steps, &mut self.recorder, self.second.as_mut(), &mut self.tracker, &mut self.decay),
+            None if ticked_here && self.membrane.is_none() => {
+                let mut chord = self.shared.lock().unwrap();
+                tick_model(&mut *chord, steps, &mut self.recorder, self.second.as_mut(), &mut self.tracker, &mut self.decay);
+                if let Some(ref mut ks) = self.comparison {
+                    for _ in 0..steps {
+                        ks.tick();
+                        self.compared.record(ks);
+                    }
+                }
+                if let Some(ref mut p) = self.pair {
+                    for _ in 0..steps {
+                        p.other.tick();
+                    }
+                }
+            },
+            None => {},
+        }
+        if let (Some(s), true) = (&mut self.stats, steps > 0) {
+            s.ticked(ticking.elapsed());
+        }
+        self.lag -= steps as f64*dt;
+        self.step = false;
+        if !ticked_here || self.steps_per_frame > 0 || steps == MAX_STEPS {
+            self.lag = 0.;
+        }
+        // The other threads' snapshots would lag behind a string the window
+        // ticks itself, so they're thrown away then.
+        let live = self.steps_per_frame == 0 && self.comparison.is_none() && !self.comparing;
+        let mut snapshots = None;
+        if let Some(ref physics) = self.physics {
+            let sent = physics.snapshots();
+            if live {
+                snapshots = Some(sent);
+            }
+        }
+        // The audio thread stands still while paused, and sends nothing.
+        #[cfg(feature = "audio")]
+        if let Some(ref a) = self.audio {
+            let sent = a.snapshots();
+            if live && !self.paused && !sent.is_empty() {
+                snapshots = Some(sent);
+            }
+        }
+        match snapshots {
+            Some(snapshots) => for snapshot in snapshots {
+                self.take_snapshot(snapshot);
+            },
+            None => self.latest = Arc::new(self.shared.lock().unwrap().clone()),
+        }
+        steps
+    }
+    // Hears the samples a snapshot brings and draws its string from then on.
+    fn take_snapshot(&mut self, snapshot: physics::Snapshot) {
+        for &s in &snapshot.samples {
+            self.recorder.push(s);
+            self.tracker.feed(s);
+            self.decay.feed(s);
+        }
+        if let Some(ref mut r) = self.second {
+            for &s in &snapshot.second {
+                r.push(s);
+            }
+        }
+        if let Some(ref mut s) = self.stats {
+            s.ticked(snapshot.ticking);
+        }
+        self.latest = snapshot.chord;
+    }
+    // Whether the script has asked to quit.
+    fn run_script(&mut self) -> bool {
+        // Script events come due as the string's clock passes them, here
+        // within a frame of their time.
+        let mut quit = false;
+        if let (Some(script), None, None) = (&mut self.script, &self.guitar, &self.membrane) {
+            let actions = script.due(self.latest.time());
+            if !actions.is_empty() {
+                let mut chord = self.shared.lock().unwrap();
+                for action in &actions {
+                    for c in pair::both(&mut chord, &mut self.pair) {
+                        action.apply(c);
+                    }
+                }
+                if actions.iter().any(|a| matches!(*a, script::Action::Pluck {..} | script::Action::Strike {..} | script::Action::Reset)) {
+                    self.decay.reset();
+                }
+                quit = actions.contains(&script::Action::Quit);
+            }
+        }
+        quit
+    }
+    fn read_midi(&mut self) {
+        // MIDI plays the lone string, and is ignored while the guitar or
+        // membrane is out.
+        let messages = self.midi.as_ref().map(|m| m.messages()).unwrap_or_default();
+        for message in messages {
+            let message = match message {
+                Ok(message) => message,
+                Err(err) => {
+                    eprintln!("MIDI input stopped: {}", err);
+                    self.midi = None;
+                    break;
+                },
+            };
+            if self.guitar.is_some() || self.membrane.is_some() {
+                continue;
+            }
+            let k = match message {
+                midi::Message::NoteOn {note, ..} => {
+                    let mut tuned = (*self.latest).clone();
+                    let k = tuned.tune_to(midi::note_frequency(note)/AUDIO_SPEED, self.latest.dt());
+                    self.midi_note = Some(note);
+                    self.midi_muted = false;
+                    *self.midi_k.insert(k.min(self.latest.max_stable_k(self.latest.dt())/2.))*self.midi_bend
+                },
+                midi::Message::NoteOff {note} => {
+                    self.midi_muted |= self.midi_mute && self.midi_note == Some(note);
+                    continue;
+                },
+                midi::Message::PitchBend(bend) => {
+                    self.midi_bend = 2f64.powf(bend*MIDI_BEND/6.);
+                    *self.midi_k.get_or_insert(self.latest.target_k())*self.midi_bend
+                },
+            };
+            let pluck = match message {
+                midi::Message::NoteOn {velocity, ..} => Some(velocity as f64/127.),
+                _ => None,
+            };
+            if pluck.is_some() {
+                self.history.push(&self.shared.lock().unwrap(), &self.pair);
+            }
+            match self.physics {
+                Some(ref physics) if !self.comparing => {
+                    physics.send(physics::Command::SetK(k));
+                    if let Some(scale) = pluck {
+                        physics.send(physics::Command::Pluck(scale*self.pluck_scale, self.pluck_position));
+                    }
+                },
+                _ => {
+                    let mut chord = self.shared.lock().unwrap();
+                    let (position, pluck_scale) = (self.pluck_position, self.pluck_scale);
+                    for c in pair::both(&mut chord, &mut self.pair) {
+                        c.set_target_k(k);
+                        if let Some(scale) = pluck {
+                            c.excite(|u| pluck_shape_at(u, position)*scale*pluck_scale);
+                        }
+                    }
+                },
+            }
+            if pluck.is_some() {
+                self.decay.reset();
+            }
+        }
+    }
+    fn read_gamepad(&mut self, now: Instant) {
+        // The gamepad's A plucks the lone string as P does, where the left
+        // stick points and as hard as the right trigger says.
+        let changes = self.gamepad.as_mut().map(|g| g.update()).unwrap_or_default();
+        for change in changes {
+            let pad = self.gamepad.as_ref().unwrap();
+            match change {
+                gamepad::Change::Connected => {
+                    println!("gamepad connected");
+                    self.gamepad_plugged = Some(now);
+                },
+                gamepad::Change::Disconnected(why) => {
+                    println!("gamepad disconnected: {}", why);
+                    self.gamepad_plugged = None;
+                },
+                gamepad::Change::Pressed(gamepad::BUTTON_A) if self.guitar.is_none() && self.membrane.is_none() => {
+                    let position = 0.5 + 0.45*pad.stick(gamepad::LEFT_X);
+                    let (low, high) = GAMEPAD_PLUCK;
+                    let scale = self.pluck_scale*(low + (high - low)*pad.trigger(gamepad::RIGHT_TRIGGER));
+                    self.history.push(&self.shared.lock().unwrap(), &self.pair);
+                    match self.physics {
+                        Some(ref physics) if !self.comparing => physics.send(physics::Command::Pluck(scale, position)),
+                        _ => {
+                            let mut chord = self.shared.lock().unwrap();
+                            for c in pair::both(&mut chord, &mut self.pair) {
+                                c.excite(|u| pluck_shape_at(u, position)*scale);
+                            }
+                        },
+                    }
+                    if let Some(ref mut ks) = self.comparison {
+                        ks.pluck_noise(KS_NOISE);
+                    }
+                    self.decay.reset();
+                },
+                gamepad::Change::Pressed(_) => {},
+            }
+        }
+    }
+    // Draws the frame, with the caption, panel and overlays brought up to
+    // date first.
+    fn show(&mut self, now: Instant, steps: u32) {
+        let latest = self.latest.clone();
+        let shown = &*latest;
+        if let (Some(server), None, None) = (&mut self.server, &self.guitar, &self.membrane) {
+            server.offer(shown);
+        }
+        if let (Some(p), true) = (&mut self.pair, steps > 0) {
+            p.record(shown);
+        }
+        // Stop a string that's blown up, rather than drawing garbage.
+        let diverged = divergence(shown).or_else(|| self.pair.as_ref().and_then(|p| divergence(&p.other)));
+        if let (None, None, false, Some(message)) = (&self.guitar, &self.membrane, self.paused, diverged) {
+            eprintln!("{}; paused, R resets it", message);
+            self.paused = true;
+            #[cfg(feature = "audio")]
+            if let Some(ref a) = self.audio {
+                a.set_paused(true);
+            }
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Pause(true));
+            }
+        }
+        // The string's clock also counts ticks run on other threads.
+        let ticks = if self.guitar.is_some() || self.membrane.is_some() {
+            steps as u64
+        } else {
+            ((shown.time() - self.last_time)/shown.dt()).round().max(0.) as u64
+        };
+        self.last_time = shown.time();
+        self.frame_rate.frame(now, ticks);
+        let rates = self.frame_rate.rates().map(|(fps, tps)| {
+            let mut rates = format!("{:.0} fps {:.0} ticks/s {:.1} ms", fps, tps, self.pacer.work.as_secs_f64()*1e3);
+            if self.steps_per_frame > 0 {
+                rates += &format!(" {} ticks/frame", self.steps_per_frame);
+            }
+            if self.pacer.late > 0 {
+                rates += &format!(" {} late", self.pacer.late);
+            }
+            rates
+        });
+        self.screen.overlay = rates.clone().unwrap_or_default();
+        if now - self.captioned >= CAPTION_EVERY {
+            // Pitch as heard in the audio output, at AUDIO_SPEED.
+            let mut caption = match self.tracker.frequency(AUDIO_SPEED/shown.dt()) {
+                Some(f) => format!("String - {:.1} Hz {}", f, pitch::note_name(f)),
+                None => "String - \u{2014}".to_string(),
+            };
+            if let Some(t) = self.decay.decay_time(AUDIO_SPEED/shown.dt()) {
+                caption += &format!(" - T60 {:.1} s", t);
+            }
+            if let Some(ref rates) = rates {
+                caption += &format!(" - {}", rates);
+            }
+            self.screen.renderer.set_title(&caption);
+            self.captioned = now;
+        }
+        if self.print_energy && self.frame.is_multiple_of(ENERGY_EVERY) {
+            println!("frame {}: energy {}", self.frame, shown.total_energy());
+        }
+        if !self.show_modes || self.guitar.is_some() || self.membrane.is_some() {
+            self.screen.modes.clear();
+        } else if self.screen.modes.is_empty() || self.frame.is_multiple_of(MODES_EVERY) {
+            self.screen.modes = shown.mode_amplitudes(MODE_COUNT);
+        }
+        if !self.show_nodes || self.guitar.is_some() || self.membrane.is_some() || self.comparing {
+            self.screen.envelope.clear();
+        } else {
+            let window = if self.settings.envelope_window > 0. {
+                self.settings.envelope_window
+            } else {
+                let f = self.tracker.frequency(1./shown.dt()).unwrap_or_else(|| shown.linear_fundamental());
+                ENVELOPE_PERIODS/f
+            };
+            self.envelope.feed(shown, window);
+            self.screen.envelope = if self.envelope.is_full(window) {self.envelope.envelope()} else {Vec::new()};
+        }
+        if let Some(Err(err)) = self.mode_log.as_mut().map(|log| log.log(shown)) {
+            eprintln!("stopped logging mode energies: {}", err);
+            self.mode_log = None;
+        }
+        self.screen.panel = match self.hud.focus() {
+            Some(focus) if self.guitar.is_none() && self.membrane.is_none() => panel(shown, focus, self.pluck_scale, self.pluck_position, &self.screen.theme),
+            _ => Vec::new(),
+        };
+        if self.gamepad.as_ref().is_some_and(|g| g.is_connected()) && (!self.screen.panel.is_empty() || self.gamepad_plugged.is_some_and(|t| now - t < GAMEPAD_NOTICE)) {
+            let guide = self.screen.theme.guide;
+            self.screen.panel.extend(GAMEPAD_HELP.iter().map(|line| (line.to_string(), guide)));
+        }
+        self.screen.second_trace = self.second.as_ref()
+            .map(|r| r.samples()[r.samples().len().saturating_sub(SCOPE_SAMPLES)..].to_vec())
+            .unwrap_or_default();
+        let drawing = Instant::now();
+        match (&self.guitar, &self.membrane, &self.comparison, &self.pair) {
+            (_, Some(m), _, _) => self.screen.draw_membrane(m),
+            (Some(g), None, _, _) => self.screen.draw_guitar(g, self.recorder.samples()),
+            (None, None, _, Some(p)) => self.screen.draw_pair(shown, p),
+            (None, None, Some(_), None) => self.screen.draw_comparison(shown, self.compared.samples()),
+            (None, None, None, None) => self.screen.draw(shown, self.recorder.samples()),
+        }
+        let logged = match (&mut self.stats, &self.guitar, &self.membrane) {
+            (Some(s), None, None) => {
+                s.drew(drawing.elapsed());
+                s.log(shown)
+            },
+            _ => Ok(()),
+        };
+        if let Err(err) = logged {
+            eprintln!("stopped logging statistics: {}", err);
+            self.stats = None;
+        }
+    }
+    // A config file takes effect as it's saved. The display changes at
+    // once and k and the damping glide to their new values, while a
+    // change to what the string is built from builds it afresh.
+    fn reload_config(&mut self, chord: &mut Chord) {
+        if let Some(path) = self.watch.changed() {
+            match cli::reload(&self.settings, &path) {
+                Ok(new) => {
+                    self.screen.theme = new.theme;
+                    if new.fps != self.settings.fps {
+                        self.pacer = FramePacer::new(new.fps);
+                    }
+                    if (new.width, new.height) != (self.settings.width, self.settings.height) && !self.screen.fullscreen {
+                        if let Err(err) = self.screen.set_mode(new.width, new.height, false) {
+                            eprintln!("failed to resize the window: {}", err);
+                        }
+                    }
+                    if new.steps_per_frame != self.settings.steps_per_frame {
+                        self.steps_per_frame = new.steps_per_frame;
+                        #[cfg(feature = "audio")]
+                        if let Some(ref a) = self.audio {
+                            a.set_paused(self.paused || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing);
+                        }
+                        if let Some(ref physics) = self.physics {
+                            physics.send(physics::Command::Pause(self.paused || self.guitar.is_some() || self.membrane.is_some()
+                                || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing));
+                        }
+                    }
+                    let rebuilt = new.segments != self.settings.segments || new.preset != self.settings.preset
+                        || new.position != self.settings.position || new.angle != self.settings.angle
+                        || new.bridge != self.settings.bridge || new.gravity != self.settings.gravity || new.taper != self.settings.taper;
+                    if rebuilt {
+                        let (rebuilt, rebuilt_pair) = initial_strings(&new);
+                        *chord = rebuilt;
+                        self.pair = rebuilt_pair;
+                        warn_if_unstable(chord);
+                        self.decay.reset();
+                        self.midi_k = None;
+                    } else {
+                        for chord in pair::both(chord, &mut self.pair) {
+                            if new.k != self.settings.k {
+                                chord.set_target_k(new.k);
+                            }
+                            if new.damping != self.settings.damping {
+                                chord.set_target_damping(new.damping);
+                            }
+                            if new.damping2 != self.settings.damping2 {
+                                chord.set_damping2(new.damping2);
+                            }
+                            if new.dt != self.settings.dt {
+                                chord.set_dt(new.dt);
+                            }
+                            if new.integrator != self.settings.integrator {
+                                chord.set_integrator(new.integrator);
+                            }
+                            if (new.noise, new.seed) != (self.settings.noise, self.settings.seed) {
+                                chord.set_noise(new.noise, new.seed);
+                            }
+                        }
+                    }
+                    println!("reloaded {}", path.display());
+                    self.settings = new;
+                },
+                Err(err) => eprintln!("{}; keeping the settings in effect", err),
+            }
+        }
+    }
+    // What held keys and the pointer do to the string, frame after frame.
+    fn handle_held(&mut self, chord: &mut Chord) {
+        let muted = self.screen.is_held(Key::M) || self.midi_muted;
+        match self.guitar {
+            Some(ref mut g) => {
+                for string in g.strings_mut().iter_mut() {
+                    palm_mute(string, muted);
+                }
+            },
+            None => for chord in pair::both(chord, &mut self.pair) {
+                palm_mute(chord, muted);
+            },
+        }
+        // The wheel and middle button move the lone string's view before
+        // anything the pointer does to the string goes by it.
+        if self.guitar.is_none() && self.membrane.is_none() && self.comparison.is_none() && !self.comparing {
+            self.screen.steer_camera(chord);
+        }
+        // Whatever is about to be done to the lone string by hand this
+        // frame is kept for undo first, before any of it.
+        let edited = self.guitar.is_none() && self.membrane.is_none() && self.screen.pressed.iter().any(|&k| is_edit(k, chord));
+        let grabbing = self.screen.clicked && self.grabbed.is_none() && self.guitar.is_none() && self.membrane.is_none() && self.comparison.is_none()
+            && !self.comparing && self.screen.dot_under_mouse(chord).is_some();
+        if edited || grabbing {
+            self.history.push(chord, &self.pair);
+        }
+        // Holding H rests a finger on the lone string under the pointer.
+        let touch = self.screen.is_held(Key::H) && self.guitar.is_none() && self.membrane.is_none();
+        let u = self.screen.mouse_fraction(chord);
+        for chord in pair::both(chord, &mut self.pair) {
+            if touch {
+                chord.touch(u);
+            } else if chord.touched().is_some() {
+                chord.release_touch();
+            }
+        }
+        // Up and Down step the panel's focused setting instead while it's
+        // showing.
+        if self.guitar.is_none() && self.membrane.is_none() && self.hud.focus().is_none() {
+            // The gamepad's left stick bends as far as it's pushed, up
+            // for sharp, and the keys win over it.
+            let stick = self.gamepad.as_ref().map_or(0., |g| g.stick(gamepad::LEFT_Y));
+            let bend = match (self.screen.is_held(Key::Up), self.screen.is_held(Key::Down)) {
+                (true, false) => WHAMMY,
+                (false, true) => 1./WHAMMY,
+                _ => WHAMMY.powf(-stick),
+            };
+            let k = if bend != 1. {
+                Some(*self.whammy_base.get_or_insert(chord.target_k())*bend)
+            } else {
+                self.whammy_base.take()
+            };
+            match (k, &self.physics) {
+                (Some(k), Some(physics)) if !self.comparing => physics.send(physics::Command::SetK(k)),
+                (Some(k), _) => for chord in pair::both(chord, &mut self.pair) {
+                    chord.set_target_k(k);
+                },
+                _ => {},
+            }
+        }
+        // 7 spreads the mass along the string a little more, or less, each
+        // frame of the glide, easing in and out, so its partials slide.
+        if let Some((from, to, start)) = self.taper_glide {
+            let t = (start.elapsed().as_secs_f64()/TAPER_GLIDE.as_secs_f64()).min(1.);
+            let eased = t*t*(3. - 2.*t);
+            for chord in pair::both(chord, &mut self.pair) {
+                chord.set_taper(from + (to - from)*eased);
+            }
+            if t == 1. {
+                self.taper_glide = None;
+            }
+        }
+        // A reset or load has dropped the grabbed dot.
+        if self.grabbed.is_some_and(|i| !chord.dots().get(i).is_some_and(|d| d.is_fixed())) {
+            self.grabbed = None;
+        }
+        if self.guitar.is_some() || self.membrane.is_some() || self.comparison.is_some() || self.comparing {
+            if let Some(i) = self.grabbed.take() {
+                chord.release(i);
+            }
+        } else {
+            if self.screen.clicked && self.grabbed.is_none() {
+                self.grabbed = self.screen.dot_under_mouse(chord).filter(|&i| chord.grab(i));
+            }
+            if let Some(i) = self.grabbed {
+                chord.drag_to(i, self.screen.mouse_height(chord));
+                if self.screen.unclicked {
+                    chord.release(i);
+                    self.grabbed = None;
+                }
+            }
+        }
+    }
+    fn handle_key(&mut self, k: Key, chord: &mut Chord) {
+        // Ctrl+Z and Ctrl+Y undo and redo, and leave Z and Y be.
+        if (k == Key::Z || k == Key::Y) && (self.screen.is_held(Key::LCtrl) || self.screen.is_held(Key::RCtrl)) {
+            if self.guitar.is_some() || self.membrane.is_some() {
+                return;
+            }
+            let done = if k == Key::Z {self.history.undo(chord, &mut self.pair)} else {self.history.redo(chord, &mut self.pair)};
+            if !done {
+                println!("nothing to {}", if k == Key::Z {"undo"} else {"redo"});
+                return;
+            }
+            if let Some(i) = self.grabbed.take() {
+                chord.release(i);
+            }
+            if self.comparison.is_some() {
+                self.comparison = Some(compare_with(chord));
+            }
+            self.decay.reset();
+            self.midi_k = None;
+            return;
+        }
+        if k == Key::Space {
+            self.paused = !self.paused;
+            #[cfg(feature = "audio")]
+            if let Some(ref a) = self.audio {
+                a.set_paused(self.paused || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing);
+            }
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Pause(self.paused || self.guitar.is_some() || self.membrane.is_some() || self.steps_per_frame > 0
+                    || self.comparison.is_some() || self.comparing));
+            }
+            self.lag = 0.;
+        }
+        if k == Key::LeftBracket || k == Key::RightBracket {
+            self.steps_per_frame = if k == Key::LeftBracket {
+                self.steps_per_frame/2
+            } else {
+                (self.steps_per_frame*2).clamp(1, cli::MAX_STEPS_PER_FRAME)
+            };
+            if self.steps_per_frame > 0 {
+                println!("{} steps per frame", self.steps_per_frame);
+            } else {
+                println!("keeping up with the clock");
+            }
+            #[cfg(feature = "audio")]
+            if let Some(ref a) = self.audio {
+                a.set_paused(self.paused || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing);
+            }
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Pause(self.paused || self.guitar.is_some() || self.membrane.is_some() || self.steps_per_frame > 0
+                    || self.comparison.is_some() || self.comparing));
+            }
+        }
+        if k == Key::Period && self.paused {
+            match self.physics {
+                Some(ref physics) if self.guitar.is_none() && self.membrane.is_none() && self.steps_per_frame == 0 && !self.comparing => {
+                    physics.send(physics::Command::Step)
+                },
+                _ => self.step = true,
+            }
+        }
+        if k == Key::Comma || k == Key::Slash {
+            self.speed = if k == Key::Comma {self.speed.saturating_sub(1)} else {(self.speed + 1).min(SPEEDS.len()-1)};
+            println!("speed {}x", SPEEDS[self.speed]);
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Speed(SPEEDS[self.speed]));
+            }
+        }
+        if k == Key::Equals || k == Key::KpPlus {
+            self.screen.zoom(ZOOM_STEP);
+        }
+        if k == Key::Minus || k == Key::KpMinus {
+            self.screen.zoom(1./ZOOM_STEP);
+        }
+        if k == Key::C {
+            self.screen.colour_by_speed = !self.screen.colour_by_speed;
+        }
+        if k == Key::N {
+            self.show_modes = !self.show_modes;
+        }
+        if k == Key::Quote {
+            self.show_nodes = !self.show_nodes;
+            self.envelope.clear();
+        }
+        if k == Key::Y {
+            match self.mode_log.take() {
+                Some(log) => match log.csv.finish() {
+                    Ok(_) => println!("stopped logging mode energies"),
+                    Err(err) => eprintln!("failed to finish the mode log: {}", err),
+                },
+                None => {
+                    let path = format!("modes-{}.csv", timestamp());
+                    match ModeLog::create(Path::new(&path)) {
+                        Ok(log) => {
+                            self.mode_log = Some(log);
+                            println!("logging mode energies to {}", path);
+                        },
+                        Err(err) => eprintln!("failed to create {}: {}", path, err),
+                    }
+                },
+            }
+        }
+        if k == Key::F11 {
+            self.screen.toggle_fullscreen();
+        }
+        if k == Key::F9 {
+            let fps = self.frame_rate.rates().map_or(60., |(fps, _)| fps);
+            let path = Path::new(RECORD_DIR).join(format!("string-{}.gif", timestamp()));
+            self.screen.toggle_recording(&path, RECORD_EVERY, fps);
+        }
+        if k == Key::F12 {
+            self.screen.screenshot = Some(PathBuf::from(format!("string-{}.bmp", timestamp())));
+        }
+        if k == Key::T {
+            self.screen.cycle_trails();
+        }
+        if k == Key::Num7 && self.guitar.is_none() && self.membrane.is_none() {
+            // Pressed again mid-glide it turns back from where it got to.
+            let from = chord.taper();
+            let tapered = if self.settings.taper != 1. {self.settings.taper} else {TAPER};
+            let to = if from == 1. {tapered} else {1.};
+            println!("gliding the mass ratio from {:.3} to {}", from, to);
+            self.taper_glide = Some((from, to, Instant::now()));
+        }
+        if k == Key::Num9 || k == Key::Num0 {
+            self.screen.stretch_trails(if k == Key::Num0 {TRAIL_STEP} else {1./TRAIL_STEP});
+        }
+        if k == Key::O {
+            self.screen.show_scope = !self.screen.show_scope;
+        }
+        if k == Key::V {
+            self.screen.cycle_theme();
+        }
+        if k == Key::K {
+            self.screen.smooth = !self.screen.smooth;
+        }
+        if k == Key::Num8 {
+            self.screen.show_com = !self.screen.show_com;
+        }
+        if k == Key::Home {
+            self.screen.camera = None;
+        }
+        if k == Key::A {
+            self.screen.antialias = !self.screen.antialias;
+            println!("antialiasing {}", if self.screen.antialias {"on"} else {"off"});
+        }
+        if k == Key::Z {
+            self.screen.from_above = !self.screen.from_above;
+        }
+        if k == Key::D {
+            self.screen.draw_mode = self.screen.draw_mode.next();
+        }
+        // A comparison keeps to its two strings.
+        if (k == Key::G || k == Key::U) && !self.comparing {
+            if k == Key::G {
+                self.guitar = match self.guitar {
+                    Some(_) => None,
+                    None => Some(Guitar::new(80, AUDIO_SPEED)),
+                };
+                self.membrane = None;
+            } else {
+                self.membrane = match self.membrane {
+                    Some(_) => None,
+                    None => Some(Membrane::new_strike(MEMBRANE_DOTS, MEMBRANE_DOTS, chord.k(), STRIKE_RADIUS, STRIKE_SPEED)),
+                };
+                self.guitar = None;
+            }
+            self.comparison = None;
+            self.decay.reset();
+            // The lone string stands still while the guitar or membrane
+            // is out.
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Pause(self.paused || self.guitar.is_some() || self.membrane.is_some() || self.steps_per_frame > 0
+                    || self.comparison.is_some() || self.comparing));
+            }
+        }
+        if let Some(ref mut m) = self.membrane {
+            if k == Key::P {
+                m.strike(STRIKE_RADIUS, STRIKE_SPEED);
+            }
+            if k == Key::R {
+                m.reset();
+            }
+            if k == Key::J {
+                let on = !m.has_diagonals();
+                m.set_diagonals(on);
+            }
+            if k == Key::Kp1 || k == Key::Kp2 {
+                let (cols, rows, k0) = (m.cols(), m.rows(), m.k());
+                *m = if k == Key::Kp1 {
+                    Membrane::new_strike(cols, rows, k0, STRIKE_RADIUS, STRIKE_SPEED)
+                } else {
+                    Membrane::new_circular(cols, rows, k0, CIRCULAR_AMPLITUDE)
+                };
+            }
+            return;
+        }
+        if let Some(ref mut g) = self.guitar {
+            if k == Key::Return {
+                g.strum(STRUM_DELAY);
+                self.decay.reset();
+            }
+            if k == Key::J {
+                g.toggle_bridge();
+            }
+            let strings = [Key::Num1, Key::Num2, Key::Num3, Key::Num4, Key::Num5, Key::Num6];
+            if let Some(i) = strings.iter().position(|&s| s == k) {
+                g.pluck(i);
+                self.decay.reset();
+            }
+            return;
+        }
+        if k == Key::Semicolon && !self.comparing {
+            self.comparison = match self.comparison {
+                Some(_) => None,
+                None => {
+                    chord.excite(pluck_shape);
+                    Some(compare_with(chord))
+                },
+            };
+            self.compared = Recorder::new(PICKUP_POS, RECORD_LIMIT);
+            self.decay.reset();
+            #[cfg(feature = "audio")]
+            if let Some(ref a) = self.audio {
+                a.set_paused(self.paused || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing);
+            }
+            if let Some(ref physics) = self.physics {
+                physics.send(physics::Command::Pause(self.paused || self.steps_per_frame > 0 || self.comparison.is_some() || self.comparing));
+            }
+        }
+        if k == Key::Tab {
+            self.hud.cycle();
+        }
+        if let (Some(param), true, None, None) = (self.hud.focus(), k == Key::Up || k == Key::Down, &self.guitar, &self.membrane) {
+            let up = k == Key::Up;
+            match param {
+                hud::Param::PluckScale => self.pluck_scale = param.step(self.pluck_scale, up),
+                hud::Param::PluckPosition => self.pluck_position = param.step(self.pluck_position, up),
+                _ => for chord in pair::both(chord, &mut self.pair) {
+                    match param {
+                        hud::Param::K => {
+                            let k = param.step(chord.target_k(), up);
+                            chord.set_target_k(k);
+                        },
+                        hud::Param::Damping => {
+                            let damping = param.step(chord.damping(), up);
+                            chord.set_damping(damping);
+                        },
+                        _ => {
+                            let dt = param.step(chord.dt(), up);
+                            chord.set_dt(dt);
+                        },
+                    }
+                },
+            }
+        }
+        if k == Key::Q {
+            for chord in pair::both(chord, &mut self.pair) {
+                let d = if chord.damping() == 0. {DAMPING} else {0.};
+                chord.set_damping(d);
+            }
+        }
+        if k == Key::P {
+            match self.physics {
+                Some(ref physics) if !self.comparing => physics.send(physics::Command::Pluck(self.pluck_scale, self.pluck_position)),
+                _ => {
+                    let (position, scale) = (self.pluck_position, self.pluck_scale);
+                    for chord in pair::both(chord, &mut self.pair) {
+                        chord.excite(|u| pluck_shape_at(u, position)*scale);
+                    }
+                },
+            }
+            if let Some(ref mut ks) = self.comparison {
+                ks.pluck_noise(KS_NOISE);
+            }
+            self.decay.reset();
+        }
+        if k == Key::PageUp || k == Key::PageDown {
+            self.drive_frequency *= if k == Key::PageUp {1.01} else {1./1.01};
+            println!("drive frequency {}", self.drive_frequency);
+        }
+        for chord in pair::both(chord, &mut self.pair) {
+            if k == Key::Backslash {
+                if chord.magnet().is_some() {
+                    chord.clear_magnet();
+                } else {
+                    chord.set_magnet(PICKUP_POS, MAGNET_OFFSET, MAGNET_STRENGTH);
+                }
+            }
+            if k == Key::F {
+                let b = if chord.barrier().is_none() {Some(BARRIER)} else {None};
+                chord.set_barrier(b);
+            }
+            if k == Key::X {
+                if chord.is_driven() {
+                    chord.clear_driver();
+                } else {
+                    let i = chord.index_at(DRIVE_POS);
+                    chord.set_driver(i, DRIVE_AMPLITUDE, self.drive_frequency);
+                }
+            }
+            if (k == Key::PageUp || k == Key::PageDown) && chord.is_driven() {
+                let i = chord.index_at(DRIVE_POS);
+                chord.set_driver(i, DRIVE_AMPLITUDE, self.drive_frequency);
+            }
+            if k == Key::B {
+                if chord.is_bowed() {
+                    chord.clear_bow();
+                } else {
+                    chord.set_bow(BOW_POS, BOW_SPEED, BOW_FORCE);
+                }
+            }
+        }
+        if k == Key::W {
+            let samples = wav::resample(self.recorder.samples(), AUDIO_SPEED/chord.dt(), WAV_RATE as f64);
+            let path = format!("string-{}.wav", timestamp());
+            let written = match self.second {
+                Some(ref r) => {
+                    let right = wav::resample(r.samples(), AUDIO_SPEED/chord.dt(), WAV_RATE as f64);
+                    wav::write_wav_stereo(Path::new(&path), &samples, &right, WAV_RATE)
+                },
+                None => wav::write_wav(Path::new(&path), &samples, WAV_RATE),
+            };
+            match written {
+                Ok(()) => println!("wrote {}", path),
+                Err(err) => eprintln!("failed to write {}: {}", path, err),
+            }
+            if self.comparison.is_some() {
+                let samples = wav::resample(self.compared.samples(), AUDIO_SPEED/chord.dt(), WAV_RATE as f64);
+                let path = format!("string-{}-ks.wav", timestamp());
+                match wav::write_wav(Path::new(&path), &samples, WAV_RATE) {
+                    Ok(()) => println!("wrote {}", path),
+                    Err(err) => eprintln!("failed to write {}: {}", path, err),
+                }
+            }
+        }
+        if k == Key::R {
+            for chord in pair::both(chord, &mut self.pair) {
+                chord.reset();
+            }
+            self.decay.reset();
+        }
+        if let Some(i) = PRESETS.iter().position(|&p| p == k) {
+            for chord in pair::both(chord, &mut self.pair) {
+                *chord = preset(i, chord);
+            }
+            self.decay.reset();
+            self.midi_k = None;
+        }
+        // The line starts over in tune with whatever the string has
+        // become.
+        if self.comparison.is_some() && (k == Key::R || PRESETS.contains(&k)) {
+            self.comparison = Some(compare_with(chord));
+        }
+        if k == Key::S {
+            let path = format!("string-{}.json", timestamp());
+            match chord.save(Path::new(&path)) {
+                Ok(()) => println!("saved {}", path),
+                Err(err) => eprintln!("failed to save {}: {}", path, err),
+            }
+        }
+        if k == Key::L {
+            match latest_save() {
+                Some(path) => match Chord::load(&path) {
+                    Ok(loaded) => {
+                        for chord in pair::both(chord, &mut self.pair) {
+                            *chord = loaded.clone();
+                        }
+                        self.decay.reset();
+                        self.midi_k = None;
+                        println!("loaded {}", path.display());
+                    },
+                    Err(err) => eprintln!("failed to load {}: {}", path.display(), err),
+                },
+                None => eprintln!("no saved string to load"),
+            }
+        }
+        if k == Key::E {
+            self.print_energy = !self.print_energy;
+        }
+        for chord in pair::both(chord, &mut self.pair) {
+            if k == Key::I {
+                let next = chord.integrator().next();
+                chord.set_integrator(next);
+            }
+            for &(key, u) in &FRETS {
+                if k == key {
+                    let i = chord.index_at(u);
+                    chord.fret(i);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorder_keeps_the_newest_samples() {
+        let mut recorder = Recorder::new(0.5, 4);
+        for i in 0..20 {
+            recorder.push(i as f64);
+        }
+        let samples = recorder.samples();
+        assert!(samples.len() >= 4 && samples.len() < 8);
+        assert_eq!(&samples[samples.len()-4..], &[16., 17., 18., 19.]);
+        assert_eq!(recorder.latest(), 19.);
+    }
+
+    #[test]
+    fn pacer_counts_frames_over_budget() {
+        let mut pacer = FramePacer::new(1000.);
+        thread::sleep(Duration::from_millis(5));
+        pacer.wait();
+        assert_eq!(pacer.late, 1);
+        pacer.wait();
+        assert_eq!(pacer.late, 1);
+        let mut unpaced = FramePacer::new(0.);
+        thread::sleep(Duration::from_millis(5));
+        unpaced.wait();
+        assert_eq!(unpaced.late, 0);
+    }
+
+    #[test]
+    fn a_missing_script_fails_with_status_2() {
+        let options = cli::parse(vec!["--script".to_string(), "/nonexistent/script.txt".to_string()]).unwrap();
+        let failure = load_script(&options).err().unwrap();
+        assert_eq!(failure.status, 2);
+    }
+
+    #[test]
+    fn palm_mute_rests_and_lifts() {
+        let mut chord = Chord::new(20, 1e-3);
+        palm_mute(&mut chord, true);
+        assert!(chord.is_muted());
+        palm_mute(&mut chord, false);
+        assert!(!chord.is_muted());
+    }
+}
@@ -0,0 +1,152 @@
+// Real-time audio output. A physics thread ticks the shared string as fast
+// as the output device consumes samples and pushes the pickup signal into a
+// queue that the device callback drains. With a second pickup the two play
+// as the left and right channels. After each batch the window is sent a
+// physics::Snapshot of the string and of the pickup at every tick, as the
+// physics thread sends it, so recording and listening work the same.
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use cpal;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use Chord;
+use physics::Snapshot;
+
+// Samples queued ahead of the device, and how many the physics thread works
+// out per lock of the string.
+const BUFFER: usize = 4096;
+const BATCH: usize = 256;
+// Snapshots that can wait for the window; a batch goes by every few
+// milliseconds, a few to a frame.
+const QUEUE: usize = 8;
+// Pickup displacement that maps to full scale.
+const FULL_SCALE: f64 = 20.;
+
+pub struct Audio {
+    _stream: cpal::Stream,
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    physics: Option<JoinHandle<()>>,
+    snapshots: Receiver<Snapshot>,
+}
+
+impl Audio {
+    // Starts playing the displacement at the fraction pickup along chord, or
+    // at the first of pickups on the left and the second on the right.
+    // speed is the units of simulated time that pass per second of audio,
+    // so each output sample costs speed/(dt·sample rate) ticks.
+    pub fn start(chord: Arc<Mutex<Chord>>, pickups: (f64, Option<f64>), speed: f64) -> Result<Audio, String> {
+        let (pickup, second) = pickups;
+        let device = cpal::default_host().default_output_device()
+            .ok_or("no audio output device")?;
+        let config = device.default_output_config().map_err(|err| err.to_string())?;
+        if config.sample_format() != cpal::SampleFormat::F32 {
+            return Err(format!("unsupported sample format {:?}", config.sample_format()));
+        }
+        let rate = config.sample_rate().0 as f64;
+        let channels = config.channels() as usize;
+        let queue = Arc::new(Mutex::new(VecDeque::with_capacity(BUFFER)));
+        let running = Arc::new(AtomicBool::new(true));
+        let paused = Arc::new(AtomicBool::new(false));
+        let (sender, snapshots) = mpsc::sync_channel(QUEUE);
+
+        let output = queue.clone();
+        let stream = device.build_output_stream(&config.into(),
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                // The device must never wait on the physics thread, so a
+                // busy or empty queue plays silence instead.
+                // A mono device gets the two mixed, and any channels past
+                // the second get the first.
+                let mut queue = output.try_lock().ok();
+                for frame in data.chunks_mut(channels) {
+                    let (l, r) = queue.as_mut().and_then(|q| q.pop_front()).unwrap_or((0., 0.));
+                    match *frame {
+                        [ref mut mono] => *mono = (l + r)/2.,
+                        [ref mut left, ref mut right, ref mut rest @ ..] => {
+                            *left = l;
+                            *right = r;
+                            for c in rest.iter_mut() {
+                                *c = l;
+                            }
+                        },
+                        _ => {},
+                    }
+                }
+            },
+            |err| eprintln!("audio stream error: {}", err),
+            None,
+        ).map_err(|err| err.to_string())?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        let flag = running.clone();
+        let hold = paused.clone();
+        let physics = thread::spawn(move || {
+            let mut owed = 0.;
+            let mut batch = Vec::with_capacity(BATCH);
+            let mut samples = Vec::new();
+            let mut seconds = Vec::new();
+            let mut ticking = Duration::ZERO;
+            while flag.load(Ordering::Relaxed) {
+                if hold.load(Ordering::Relaxed) || queue.lock().unwrap().len() >= BUFFER {
+                    thread::sleep(Duration::from_millis(1));
+                    continue;
+                }
+                let snapshot = {
+                    let mut chord = chord.lock().unwrap();
+                    let ticks_per_sample = speed/chord.dt()/rate;
+                    let started = Instant::now();
+                    for _ in 0..BATCH {
+                        owed += ticks_per_sample;
+                        while owed >= 1. {
+                            chord.tick();
+                            samples.push(chord.pickup(pickup));
+                            if let Some(u) = second {
+                                seconds.push(chord.pickup(u));
+                            }
+                            owed -= 1.;
+                        }
+                        let l = (chord.pickup(pickup)/FULL_SCALE).clamp(-1., 1.) as f32;
+                        let r = second.map_or(l, |u| (chord.pickup(u)/FULL_SCALE).clamp(-1., 1.) as f32);
+                        batch.push((l, r));
+                    }
+                    ticking += started.elapsed();
+                    Snapshot {chord: Arc::new(chord.clone()), samples: mem::take(&mut samples), second: mem::take(&mut seconds),
+                              ticking: mem::take(&mut ticking)}
+                };
+                queue.lock().unwrap().extend(batch.drain(..));
+                // A window that's behind gets the samples with the next
+                // snapshot instead.
+                if let Err(TrySendError::Full(snapshot)) = sender.try_send(snapshot) {
+                    samples = snapshot.samples;
+                    seconds = snapshot.second;
+                    ticking = snapshot.ticking;
+                }
+            }
+        });
+        Ok(Audio {_stream: stream, running, paused, physics: Some(physics), snapshots})
+    }
+    // Stops ticking the string while paused; the device plays silence once
+    // the queue runs dry.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+    // Snapshots sent since the last call, oldest first.
+    pub fn snapshots(&self) -> Vec<Snapshot> {
+        self.snapshots.try_iter().collect()
+    }
+}
+
+impl Drop for Audio {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(physics) = self.physics.take() {
+            let _ = physics.join();
+        }
+    }
+}
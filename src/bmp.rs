@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Writes an image given as rows of RGB bytes from the top left as a 24-bit
+// uncompressed BMP file.
+pub fn write_bmp(path: &Path, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    encode_bmp(&mut out, width, height, rgb)?;
+    out.flush()
+}
+
+pub fn encode_bmp<W: Write>(out: &mut W, width: usize, height: usize, rgb: &[u8]) -> io::Result<()> {
+    if rgb.len() != width*height*3 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput,
+            format!("{} bytes for a {}x{} image", rgb.len(), width, height)));
+    }
+    // Rows are padded to a multiple of four bytes.
+    let row = (width*3 + 3) & !3;
+    let data_len = (row*height) as u32;
+    out.write_all(b"BM")?;
+    out.write_all(&(54 + data_len).to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    out.write_all(&54u32.to_le_bytes())?;
+    out.write_all(&40u32.to_le_bytes())?;
+    out.write_all(&(width as i32).to_le_bytes())?;
+    out.write_all(&(height as i32).to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&24u16.to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?; // uncompressed
+    out.write_all(&data_len.to_le_bytes())?;
+    out.write_all(&2835u32.to_le_bytes())?; // 72 dpi
+    out.write_all(&2835u32.to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    out.write_all(&0u32.to_le_bytes())?;
+    // Bottom row first, each pixel blue, green, red.
+    let mut line = vec![0u8; row];
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let p = &rgb[(y*width + x)*3..][..3];
+            line[x*3..x*3 + 3].copy_from_slice(&[p[2], p[1], p[0]]);
+        }
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+// Reads a BMP file as encode_bmp writes them: its width, height and rows of
+// RGB bytes from the top left.
+pub fn read_bmp(path: &Path) -> io::Result<(usize, usize, Vec<u8>)> {
+    decode_bmp(&fs::read(path)?)
+}
+
+// Only 24-bit uncompressed images are understood, stored either way up.
+pub fn decode_bmp(bytes: &[u8]) -> io::Result<(usize, usize, Vec<u8>)> {
+    let invalid = |what: &str| io::Error::new(io::ErrorKind::InvalidData, what.to_string());
+    if bytes.len() < 54 || &bytes[..2] != b"BM" {
+        return Err(invalid("not a BMP file"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i+1]]);
+    let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i+1], bytes[i+2], bytes[i+3]]);
+    if u16_at(28) != 24 || u32_at(30) != 0 {
+        return Err(invalid("only 24-bit uncompressed BMP files are supported"));
+    }
+    let offset = u32_at(10) as usize;
+    let width = u32_at(18) as i32;
+    let height = u32_at(22) as i32;
+    if width <= 0 || height == 0 {
+        return Err(invalid("the image is empty"));
+    }
+    let (width, bottom_up) = (width as usize, height > 0);
+    let height = height.unsigned_abs() as usize;
+    let row = (width*3 + 3) & !3;
+    if bytes.len() < offset + row*height {
+        return Err(invalid("the pixel data is cut short"));
+    }
+    let mut rgb = Vec::with_capacity(width*height*3);
+    for y in 0..height {
+        let stored = if bottom_up {height - 1 - y} else {y};
+        let line = &bytes[offset + stored*row..][..width*3];
+        for p in line.chunks_exact(3) {
+            rgb.extend_from_slice(&[p[2], p[1], p[0]]);
+        }
+    }
+    Ok((width, height, rgb))
+}
@@ -0,0 +1,94 @@
+// Records the window to an animated GIF. Frames are handed to a writer
+// thread that encodes them straight to disk, so a long recording costs no
+// more memory than a short one; when the writer falls behind, frames are
+// dropped rather than holding up the simulation.
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+
+use gif;
+
+// Frames that can wait for the writer before new ones get dropped, and how
+// hard it works at picking each frame's palette (1 best, 30 fastest).
+const QUEUE: usize = 4;
+const QUANTIZE_SPEED: i32 = 20;
+
+pub struct Capture {
+    sender: Option<SyncSender<Vec<u8>>>,
+    writer: Option<JoinHandle<io::Result<u64>>>,
+    size: usize,
+    every: u64,
+    seen: u64,
+    dropped: u64,
+}
+
+impl Capture {
+    // Starts writing a width by height GIF at path that keeps one frame in
+    // every, each shown for delay hundredths of a second.
+    pub fn start(path: &Path, width: u16, height: u16, every: u64, delay: u16) -> io::Result<Capture> {
+        let out = BufWriter::new(File::create(path)?);
+        let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(encoding)?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(encoding)?;
+        let (sender, frames) = mpsc::sync_channel::<Vec<u8>>(QUEUE);
+        let writer = thread::spawn(move || {
+            let mut written = 0;
+            for rgb in frames {
+                let mut frame = gif::Frame::from_rgb_speed(width, height, &rgb, QUANTIZE_SPEED);
+                frame.delay = delay;
+                encoder.write_frame(&frame).map_err(encoding)?;
+                written += 1;
+            }
+            encoder.into_inner()?.flush()?;
+            Ok(written)
+        });
+        let size = width as usize*height as usize*3;
+        Ok(Capture {sender: Some(sender), writer: Some(writer), size, every: every.max(1), seen: 0, dropped: 0})
+    }
+    // Whether the frame about to be shown is one to keep.
+    pub fn wants_frame(&mut self) -> bool {
+        self.seen += 1;
+        (self.seen - 1).is_multiple_of(self.every)
+    }
+    // Queues a frame of RGB rows from the top left, unless the writer is
+    // too far behind to take it or the window has changed size since the
+    // recording began.
+    pub fn push(&mut self, rgb: Vec<u8>) {
+        if rgb.len() != self.size {
+            self.dropped += 1;
+            return;
+        }
+        if let Some(ref sender) = self.sender {
+            match sender.try_send(rgb) {
+                Ok(()) => {},
+                Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => self.dropped += 1,
+            }
+        }
+    }
+    // Waits for the writer to finish the file, returning how many frames it
+    // holds and how many were dropped on the way.
+    pub fn finish(mut self) -> io::Result<(u64, u64)> {
+        self.sender = None;
+        let written = match self.writer.take().map(|w| w.join()) {
+            Some(Ok(result)) => result?,
+            _ => return Err(io::Error::other("GIF writer panicked")),
+        };
+        Ok((written, self.dropped))
+    }
+}
+
+// A recording still going when the program ends is finished off, so the
+// file isn't left truncated.
+impl Drop for Capture {
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+fn encoding(err: gif::EncodingError) -> io::Error {
+    io::Error::other(err)
+}
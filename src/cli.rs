@@ -0,0 +1,775 @@
+// Command line options, laid over whatever string.toml sets. Anything given
+// in neither keeps the defaults below.
+use std::path::{Path, PathBuf};
+
+use config::Config;
+use Integrator;
+use screen::{Theme, THEMES};
+
+pub const USAGE: &str = "\
+usage: chord_sim [options]
+
+The string:
+  --dots N          dots along the string, both fixed ends included; at
+                    least 3 (default 81)
+  --k K             spring constant of each segment, in force per unit of
+                    stretch; fractions like 1/4096 work (default 1/4096)
+  --damping D       drag on each dot, in force per unit of velocity
+                    (default 0; Q toggles 1/2048)
+  --damping2 D      drag on the second difference of the velocity along the
+                    string, which takes the high partials first (default 0)
+  --gravity G       acceleration of every dot down the window; the string
+                    first settles into the sag it hangs in and is plucked
+                    about that (default 0)
+  --noise A         push every free dot across the string at random each
+                    tick, the pushes drawn from a normal distribution of
+                    standard deviation A, which excites every mode at once
+                    (default 0)
+  --seed N          where the noise's pushes start from; the same seed gives
+                    the same pushes every run (default 0)
+  --taper R         make the dots heavier along the string, linearly, until
+                    the last is R times as heavy as the first; 7 in the
+                    window glides between this and a uniform string
+                    (default 1, uniform)
+  --bridge-stiffness K
+  --bridge-resistance R
+                    let the right-hand end move across the string, held by a
+                    spring of stiffness K and a dashpot of resistance R, so
+                    energy leaves through it as through a bridge (default
+                    pinned; giving either sets the other to 0)
+  --dt DT           simulated time per tick (default 1)
+  --integrator I    euler, verlet, rk4 or constrained, which keeps every
+                    segment the length it was built with (default euler)
+  --preset NAME     starting shape (default triangle):
+                      triangle  pluck at the middle
+                      sine      the fundamental
+                      pluck     pluck at --position
+                      strike    hammer blow at --position, starting flat
+                      pulse     bump at --position running to the right
+  --position U      fraction of the way along the string for pluck, strike
+                    and pulse, strictly between 0 and 1 (default 0.2)
+  --angle DEGREES   for pluck, how far out of the window's plane the string
+                    is pulled; Z looks at it from above (default 0)
+  --script PATH     play the timed events in PATH into the string, such as
+                    \"at 25000 fret index=40\", one to a line
+
+The window:
+  --width W         width in pixels (default 800)
+  --height H        height in pixels (default 600)
+  --theme NAME      dark, light or \"high contrast\" (default dark)
+  --backend NAME    sdl, or pixels for a window drawn with wgpu; only
+                    those built in are there (default sdl if built, else
+                    pixels)
+  --fps F           most frames drawn per second, 0 for no limit
+                    (default 60)
+  --replay PATH     play back a trajectory written with --trajectory
+                    instead of simulating; Space pauses, Left and Right
+                    step a frame
+  --compare A,B     run two strings side by side from the same start, one
+                    above the other over a strip showing how far apart
+                    their dots are; A and B are each an integrator name,
+                    k=K or damping=D, or several joined by +, such as
+                    euler,verlet or k=1/4096,verlet+k=1/2048
+  --pickups U,V     listen at two places along the string, the fractions U
+                    and V of the way, each strictly between 0 and 1: they
+                    play as the left and right channels, W writes them as
+                    a stereo WAV, and the oscilloscope overlays the two
+                    (default one pickup, at 0.8)
+  --steps-per-frame N
+                    run exactly N ticks for each frame drawn instead of
+                    keeping up with the clock; [ and ] halve and double it
+                    (default 0, keep up with the clock)
+  --envelope-window T
+                    simulated time the node and antinode marks shown with '
+                    look back over (default 0, two periods of the pitch
+                    heard)
+  --midi PATH       play the string from the raw MIDI device PATH, such as
+                    /dev/snd/midiC1D0: a note tunes the string to it and
+                    plucks it as hard as the key was struck, and pitch bend
+                    bends it up to two semitones either way
+  --midi-mute       have a note's release mute the string, as M does
+  --gamepad PATH    watch for a gamepad at the joystick device PATH, or off
+                    not to: the left stick's y bends k as Up and Down do,
+                    button A plucks at the left stick's x as hard as the
+                    right trigger is pulled (default /dev/input/js0)
+  --serve PORT      also stream the string as newline-delimited JSON to
+                    clients connecting to PORT on this machine, in the window
+                    or headless
+  --serve-rate HZ   most frames a second to stream (default 30)
+  --log-stats PATH  write the string's clock, energy, largest displacement
+                    and pickup signal to the CSV file PATH as it runs, with
+                    the wall-clock time spent ticking and drawing, in the
+                    window or headless
+  --log-every N     ticks between rows of --log-stats (default 1000)
+
+Running without a window:
+  --headless        run without a window and write the string to a CSV file
+  --ticks N         ticks to run when headless (default 100000)
+  --every K         write every Kth tick, starting with tick 0 (default 1)
+  --output PATH     CSV file to write (default string-<timestamp>.csv)
+  --pickup          write only the pickup signal instead of every dot's
+                    displacement
+  --trajectory PATH also write the dots' positions every --every ticks to
+                    PATH, for --replay
+  --export-state PATH
+                    write every dot's position, velocity and whether it's
+                    fixed at the end to the CSV file PATH, or just the
+                    positions as an n by 3 array if PATH ends in .npy
+  --export-series PATH
+                    write the pickup signal and total energy of every tick
+                    to the CSV file PATH
+  --time-reversal-test N
+                    run the string N ticks, turn every velocity round, run
+                    N more and print how far the dots end up from where
+                    they started, which is small with a time-reversible
+                    integrator
+  --wave-speed-test send a pulse down the string and back, and print how
+                    fast it went against the speed the tension and mass
+                    give, and whether it came back upside down
+  --modes-test      print the frequencies of the string's first 8 modes and
+                    how far each is from the harmonic series, which a
+                    --taper pulls them off
+
+Settings:
+  --config PATH     read settings from PATH instead of the first of
+                    ./string.toml and $XDG_CONFIG_HOME/chord_sim/string.toml
+  --dump-config     print the settings in effect as TOML and quit
+
+  --help            show this message";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Preset {
+    Triangle,
+    Sine,
+    Pluck,
+    Strike,
+    Pulse,
+}
+
+const PRESET_NAMES: [(Preset, &str); 5] = [
+    (Preset::Triangle, "triangle"),
+    (Preset::Sine, "sine"),
+    (Preset::Pluck, "pluck"),
+    (Preset::Strike, "strike"),
+    (Preset::Pulse, "pulse"),
+];
+// The libraries the window can be opened with, each behind its feature.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Backend {
+    Sdl,
+    Pixels,
+}
+
+impl Backend {
+    fn built(self) -> bool {
+        match self {
+            Backend::Sdl => cfg!(feature = "window"),
+            Backend::Pixels => cfg!(feature = "pixels"),
+        }
+    }
+}
+
+const BACKEND_NAMES: [(Backend, &str); 2] = [
+    (Backend::Sdl, "sdl"),
+    (Backend::Pixels, "pixels"),
+];
+const INTEGRATOR_NAMES: [(Integrator, &str); 4] = [
+    (Integrator::Euler, "euler"),
+    (Integrator::Verlet, "verlet"),
+    (Integrator::Rk4, "rk4"),
+    (Integrator::Constrained, "constrained"),
+];
+
+const DEFAULT_DOTS: u64 = 81;
+const DEFAULT_K: f64 = 1./4096.;
+const DEFAULT_POSITION: f64 = 0.2;
+const DEFAULT_WIDTH: u64 = 800;
+const DEFAULT_HEIGHT: u64 = 600;
+const DEFAULT_FPS: f64 = 60.;
+const DEFAULT_SERVE_RATE: f64 = 30.;
+const DEFAULT_GAMEPAD: &str = "/dev/input/js0";
+const DEFAULT_LOG_EVERY: u64 = 1000;
+// Most ticks a frame may be asked to run.
+pub const MAX_STEPS_PER_FRAME: u32 = 1<<20;
+// Bounds on the window so a typo can't ask for a gigapixel surface.
+const MAX_SIDE: u64 = 16384;
+
+// What one string of a comparison is run with in place of the settings
+// both share, and the text it was given in, which labels it.
+#[derive(Clone, Default)]
+pub struct Side {
+    pub name: String,
+    pub integrator: Option<Integrator>,
+    pub k: Option<f64>,
+    pub damping: Option<f64>,
+}
+
+impl Side {
+    // options with this side's settings in place of their own.
+    pub fn applied(&self, options: &Options) -> Options {
+        let mut options = options.clone();
+        options.integrator = self.integrator.unwrap_or(options.integrator);
+        options.k = self.k.unwrap_or(options.k);
+        options.damping = self.damping.unwrap_or(options.damping);
+        options
+    }
+}
+
+#[derive(Clone)]
+pub struct Headless {
+    pub ticks: u64,
+    pub every: u64,
+    pub output: Option<PathBuf>,
+    pub pickup_only: bool,
+    pub trajectory: Option<PathBuf>,
+    pub export_state: Option<PathBuf>,
+    pub export_series: Option<PathBuf>,
+}
+
+#[derive(Clone)]
+pub struct Options {
+    // Segments between the dots, one fewer than there are dots.
+    pub segments: u16,
+    pub k: f64,
+    pub damping: f64,
+    pub damping2: f64,
+    pub gravity: f64,
+    // Standard deviation of the random push on each dot each tick, and its
+    // seed.
+    pub noise: f64,
+    pub seed: u64,
+    // Mass of the last dot over the first's, the mass growing linearly
+    // between them.
+    pub taper: f64,
+    // Stiffness and resistance of the right-hand end, if it isn't pinned.
+    pub bridge: Option<(f64, f64)>,
+    pub dt: f64,
+    pub integrator: Integrator,
+    pub preset: Preset,
+    pub position: f64,
+    // Degrees out of the window's plane a pluck pulls the string.
+    pub angle: f64,
+    pub width: isize,
+    pub height: isize,
+    pub theme: Theme,
+    // What opens the window, or None in a build with no window at all.
+    pub backend: Option<Backend>,
+    // Frames per second to stay under, or 0 to draw as fast as possible.
+    pub fps: f64,
+    // Ticks run for each frame drawn, or 0 to keep up with the clock.
+    pub steps_per_frame: u32,
+    // Simulated time the motion envelope spans, or 0 to follow the pitch.
+    pub envelope_window: f64,
+    // Set when the string should run without a window.
+    pub headless: Option<Headless>,
+    // Ticks each way of a time-reversal test to run instead of the window.
+    pub time_reversal: Option<u64>,
+    // Whether to time a pulse down the string instead of the window.
+    pub wave_speed_test: bool,
+    // Whether to print the string's mode frequencies instead of the window.
+    pub modes_test: bool,
+    // The two strings to run side by side, if the window compares two.
+    pub compare: Option<(Side, Side)>,
+    // File of timed events to play into the string.
+    pub script: Option<PathBuf>,
+    // Trajectory file to play back in the window instead of simulating.
+    pub replay: Option<PathBuf>,
+    // Port to stream the string to clients on, and frames a second to send.
+    pub serve: Option<u16>,
+    pub serve_rate: f64,
+    // Fractions along the string of a left and right pickup, if there are
+    // two.
+    pub pickups: Option<(f64, f64)>,
+    // Raw MIDI device to play the string from, and whether a released note
+    // mutes it.
+    pub midi: Option<PathBuf>,
+    pub midi_mute: bool,
+    // Joystick device to watch for a gamepad at, if any.
+    pub gamepad: Option<PathBuf>,
+    // CSV file to log how the string is doing to, and ticks between rows.
+    pub log_stats: Option<PathBuf>,
+    pub log_every: u64,
+    // Config file named with --config, if one was, and the settings the
+    // command line gave, which win over the file however it's edited.
+    pub config: Option<PathBuf>,
+    pub flags: Config,
+    pub dump_config: bool,
+    pub help: bool,
+}
+
+impl Options {
+    // The settings in effect, in the form the config file takes.
+    pub fn to_config(&self) -> Config {
+        let mut config = Config::default();
+        config.simulation.dots = Some(self.segments as u64 + 1);
+        config.simulation.k = Some(self.k);
+        config.simulation.dt = Some(self.dt);
+        config.simulation.damping = Some(self.damping);
+        config.simulation.damping2 = Some(self.damping2);
+        config.simulation.gravity = Some(self.gravity);
+        config.simulation.noise = Some(self.noise);
+        config.simulation.seed = Some(self.seed);
+        config.simulation.taper = Some(self.taper);
+        if let Some((stiffness, resistance)) = self.bridge {
+            config.simulation.bridge_stiffness = Some(stiffness);
+            config.simulation.bridge_resistance = Some(resistance);
+        }
+        config.simulation.integrator = Some(name_of(&INTEGRATOR_NAMES, self.integrator).to_string());
+        config.display.width = Some(self.width as u64);
+        config.display.height = Some(self.height as u64);
+        config.display.theme = Some(self.theme.name.to_string());
+        config.display.backend = self.backend.map(|b| name_of(&BACKEND_NAMES, b).to_string());
+        config.display.fps = Some(self.fps);
+        config.display.steps_per_frame = Some(self.steps_per_frame as u64);
+        config.display.envelope_window = Some(self.envelope_window);
+        config.initial.preset = Some(name_of(&PRESET_NAMES, self.preset).to_string());
+        if self.preset == Preset::Pluck || self.preset == Preset::Strike || self.preset == Preset::Pulse {
+            config.initial.position = Some(self.position);
+        }
+        if self.preset == Preset::Pluck {
+            config.initial.angle = Some(self.angle);
+        }
+        config
+    }
+}
+
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut flags = Config::default();
+    let mut config_path = None;
+    let mut dump_config = false;
+    let mut time_reversal = None;
+    let mut wave_speed_test = false;
+    let mut modes_test = false;
+    let mut compare = None;
+    let mut script = None;
+    let mut headless = false;
+    let mut ticks = 100_000;
+    let mut every = 1;
+    let mut output = None;
+    let mut pickup_only = false;
+    let mut trajectory = None;
+    let mut export_state = None;
+    let mut export_series = None;
+    let mut replay = None;
+    let mut serve = None;
+    let mut serve_rate = None;
+    let mut midi = None;
+    let mut midi_mute = false;
+    let mut gamepad = None;
+    let mut pickups = None;
+    let mut log_stats = None;
+    let mut log_every = None;
+    let mut help = false;
+    // Last option given that only means something headless.
+    let mut headless_only = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if ["--ticks", "--every", "--output", "--pickup", "--trajectory", "--export-state", "--export-series"]
+            .contains(&arg.as_str()) {
+            headless_only = Some(arg.clone());
+        }
+        match arg.as_str() {
+            "--dots" => flags.simulation.dots = Some(number(&arg, args.next())?),
+            "--k" => flags.simulation.k = Some(real(&arg, args.next())?),
+            "--damping" => flags.simulation.damping = Some(real(&arg, args.next())?),
+            "--damping2" => flags.simulation.damping2 = Some(real(&arg, args.next())?),
+            "--gravity" => flags.simulation.gravity = Some(real(&arg, args.next())?),
+            "--noise" => flags.simulation.noise = Some(real(&arg, args.next())?),
+            "--seed" => flags.simulation.seed = Some(number(&arg, args.next())?),
+            "--taper" => flags.simulation.taper = Some(real(&arg, args.next())?),
+            "--bridge-stiffness" => flags.simulation.bridge_stiffness = Some(real(&arg, args.next())?),
+            "--bridge-resistance" => flags.simulation.bridge_resistance = Some(real(&arg, args.next())?),
+            "--dt" => flags.simulation.dt = Some(real(&arg, args.next())?),
+            "--integrator" => flags.simulation.integrator = Some(value(&arg, args.next())?),
+            "--preset" => flags.initial.preset = Some(value(&arg, args.next())?),
+            "--position" => flags.initial.position = Some(real(&arg, args.next())?),
+            "--angle" => flags.initial.angle = Some(real(&arg, args.next())?),
+            "--width" => flags.display.width = Some(number(&arg, args.next())?),
+            "--height" => flags.display.height = Some(number(&arg, args.next())?),
+            "--theme" => flags.display.theme = Some(value(&arg, args.next())?),
+            "--backend" => flags.display.backend = Some(value(&arg, args.next())?),
+            "--fps" => flags.display.fps = Some(real(&arg, args.next())?),
+            "--steps-per-frame" => flags.display.steps_per_frame = Some(number(&arg, args.next())?),
+            "--envelope-window" => flags.display.envelope_window = Some(real(&arg, args.next())?),
+            "--config" => config_path = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--dump-config" => dump_config = true,
+            "--compare" => compare = Some(sides(&arg, args.next())?),
+            "--script" => script = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--time-reversal-test" => time_reversal = Some(number(&arg, args.next())?),
+            "--wave-speed-test" => wave_speed_test = true,
+            "--modes-test" => modes_test = true,
+            "--headless" => headless = true,
+            "--ticks" => ticks = number(&arg, args.next())?,
+            "--every" => every = number(&arg, args.next())?,
+            "--output" => output = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--pickup" => pickup_only = true,
+            "--trajectory" => trajectory = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--export-state" => export_state = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--export-series" => export_series = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--replay" => replay = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--serve" => serve = Some(number(&arg, args.next())?),
+            "--midi" => midi = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--midi-mute" => midi_mute = true,
+            "--pickups" => pickups = Some(two_pickups(&arg, args.next())?),
+            "--gamepad" => gamepad = Some(value(&arg, args.next())?),
+            "--serve-rate" => serve_rate = Some(real(&arg, args.next())?),
+            "--log-stats" => log_stats = Some(PathBuf::from(value(&arg, args.next())?)),
+            "--log-every" => log_every = Some(number(&arg, args.next())?),
+            "--help" | "-h" => help = true,
+            _ => return Err(format!("unknown option {}", arg)),
+        }
+    }
+    if let (false, Some(arg)) = (headless, headless_only) {
+        return Err(format!("{} only applies with --headless", arg));
+    }
+    if headless && time_reversal.is_some() {
+        return Err("--time-reversal-test runs instead of --headless, not with it".to_string());
+    }
+    if wave_speed_test && (headless || time_reversal.is_some() || replay.is_some() || serve.is_some() || midi.is_some()) {
+        return Err("--wave-speed-test runs on its own, without --headless, --time-reversal-test, --replay, --serve or --midi".to_string());
+    }
+    if modes_test && (headless || time_reversal.is_some() || wave_speed_test || replay.is_some() || serve.is_some()
+        || midi.is_some() || compare.is_some() || pickups.is_some() || log_stats.is_some()) {
+        return Err("--modes-test runs on its own, without --headless, --time-reversal-test, --wave-speed-test, --replay, --serve, --midi, --compare, --pickups or --log-stats".to_string());
+    }
+    if compare.is_some() && (headless || time_reversal.is_some() || wave_speed_test || replay.is_some()) {
+        return Err("--compare runs in the window, without --headless, --time-reversal-test, --wave-speed-test or --replay".to_string());
+    }
+    if replay.is_some() && (headless || time_reversal.is_some()) {
+        return Err("--replay plays in the window, without --headless or --time-reversal-test".to_string());
+    }
+    if serve.is_some() && (replay.is_some() || time_reversal.is_some()) {
+        return Err("--serve streams a running string, not --replay or --time-reversal-test".to_string());
+    }
+    if let Some(port) = serve.filter(|&p| p == 0 || p > u16::MAX as u64) {
+        return Err(format!("--serve needs a port from 1 to {}, not {}", u16::MAX, port));
+    }
+    if midi.is_some() && (headless || replay.is_some() || time_reversal.is_some()) {
+        return Err("--midi plays the string in the window, without --headless, --replay or --time-reversal-test".to_string());
+    }
+    if midi.is_none() && midi_mute {
+        return Err("--midi-mute only applies with --midi".to_string());
+    }
+    if pickups.is_some() && (headless || replay.is_some() || time_reversal.is_some() || wave_speed_test) {
+        return Err("--pickups listens to the string in the window, without --headless, --replay, --time-reversal-test or --wave-speed-test".to_string());
+    }
+    if gamepad.is_some() && (headless || replay.is_some() || time_reversal.is_some() || wave_speed_test) {
+        return Err("--gamepad plays the string in the window, without --headless, --replay, --time-reversal-test or --wave-speed-test".to_string());
+    }
+    if serve.is_none() && serve_rate.is_some() {
+        return Err("--serve-rate only applies with --serve".to_string());
+    }
+    if log_stats.is_some() && (time_reversal.is_some() || wave_speed_test || replay.is_some()) {
+        return Err("--log-stats logs a running string, in the window or headless, not --time-reversal-test, --wave-speed-test or --replay".to_string());
+    }
+    if log_stats.is_none() && log_every.is_some() {
+        return Err("--log-every only applies with --log-stats".to_string());
+    }
+    let log_every = log_every.unwrap_or(DEFAULT_LOG_EVERY);
+    if log_every == 0 {
+        return Err("--log-every must be at least 1".to_string());
+    }
+    let serve_rate = serve_rate.unwrap_or(DEFAULT_SERVE_RATE);
+    if !(serve_rate > 0. && serve_rate.is_finite()) {
+        return Err(format!("--serve-rate must be positive and finite, not {}", serve_rate));
+    }
+    if every == 0 {
+        return Err("--every must be at least 1".to_string());
+    }
+    // A broken config file shouldn't stand in the way of reading the help.
+    let file = match config_path {
+        _ if help => Config::default(),
+        Some(ref path) => Config::load(path)?,
+        None => Config::discover()?,
+    };
+    let mut options = resolve(file.overlaid(flags.clone()))?;
+    options.config = config_path;
+    options.flags = flags;
+    options.headless = if headless {
+        Some(Headless {ticks, every, output, pickup_only, trajectory, export_state, export_series})
+    } else {
+        None
+    };
+    options.time_reversal = time_reversal;
+    options.wave_speed_test = wave_speed_test;
+    options.modes_test = modes_test;
+    options.compare = compare;
+    options.script = script;
+    options.replay = replay;
+    options.serve = serve.map(|p| p as u16);
+    options.serve_rate = serve_rate;
+    options.midi = midi;
+    options.midi_mute = midi_mute;
+    options.pickups = pickups;
+    options.gamepad = match gamepad.as_deref() {
+        Some("off") => None,
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(PathBuf::from(DEFAULT_GAMEPAD)),
+    };
+    options.log_stats = log_stats;
+    options.log_every = log_every;
+    options.dump_config = dump_config;
+    options.help = help;
+    Ok(options)
+}
+
+// Checks the merged settings and fills in the defaults for what's unset.
+pub fn resolve(config: Config) -> Result<Options, String> {
+    let s = config.simulation;
+    let d = config.display;
+    let i = config.initial;
+    let dots = s.dots.unwrap_or(DEFAULT_DOTS);
+    if !(3..=u16::MAX as u64 + 1).contains(&dots) {
+        return Err(format!("dots must be between 3 and {}, not {}", u16::MAX as u64 + 1, dots));
+    }
+    let k = s.k.unwrap_or(DEFAULT_K);
+    if !(k > 0. && k.is_finite()) {
+        return Err(format!("k must be positive and finite, not {}", k));
+    }
+    let dt = s.dt.unwrap_or(1.);
+    if !(dt > 0. && dt.is_finite()) {
+        return Err(format!("dt must be positive and finite, not {}", dt));
+    }
+    let damping = s.damping.unwrap_or(0.);
+    if !(damping >= 0. && damping.is_finite()) {
+        return Err(format!("damping must be zero or more and finite, not {}", damping));
+    }
+    let damping2 = s.damping2.unwrap_or(0.);
+    if !(damping2 >= 0. && damping2.is_finite()) {
+        return Err(format!("damping2 must be zero or more and finite, not {}", damping2));
+    }
+    let gravity = s.gravity.unwrap_or(0.);
+    if !gravity.is_finite() {
+        return Err(format!("gravity must be finite, not {}", gravity));
+    }
+    let noise = s.noise.unwrap_or(0.);
+    if !(noise >= 0. && noise.is_finite()) {
+        return Err(format!("noise must be zero or more and finite, not {}", noise));
+    }
+    let seed = s.seed.unwrap_or(0);
+    let taper = s.taper.unwrap_or(1.);
+    if !(taper > 0. && taper.is_finite()) {
+        return Err(format!("taper must be positive and finite, not {}", taper));
+    }
+    let bridge = match (s.bridge_stiffness, s.bridge_resistance) {
+        (None, None) => None,
+        (stiffness, resistance) => Some((stiffness.unwrap_or(0.), resistance.unwrap_or(0.))),
+    };
+    if let Some((stiffness, resistance)) = bridge {
+        if !(stiffness >= 0. && stiffness.is_finite()) {
+            return Err(format!("bridge_stiffness must be zero or more and finite, not {}", stiffness));
+        }
+        if !(resistance >= 0. && resistance.is_finite()) {
+            return Err(format!("bridge_resistance must be zero or more and finite, not {}", resistance));
+        }
+    }
+    let integrator = match s.integrator {
+        Some(ref name) => named(&INTEGRATOR_NAMES, name, "integrator")?,
+        None => Integrator::Euler,
+    };
+    let preset = match i.preset {
+        Some(ref name) => named(&PRESET_NAMES, name, "preset")?,
+        None => Preset::Triangle,
+    };
+    if i.position.is_some() && (preset == Preset::Triangle || preset == Preset::Sine) {
+        return Err("position only applies to the pluck, strike and pulse presets".to_string());
+    }
+    let position = i.position.unwrap_or(DEFAULT_POSITION);
+    if !(position > 0. && position < 1.) {
+        return Err(format!("position must be strictly between 0 and 1, not {}", position));
+    }
+    if i.angle.is_some() && preset != Preset::Pluck {
+        return Err("angle only applies to the pluck preset".to_string());
+    }
+    let angle = i.angle.unwrap_or(0.);
+    if !angle.is_finite() {
+        return Err(format!("angle must be finite, not {}", angle));
+    }
+    let width = d.width.unwrap_or(DEFAULT_WIDTH);
+    let height = d.height.unwrap_or(DEFAULT_HEIGHT);
+    for &(name, side) in &[("width", width), ("height", height)] {
+        if !(1..=MAX_SIDE).contains(&side) {
+            return Err(format!("{} must be between 1 and {} pixels, not {}", name, MAX_SIDE, side));
+        }
+    }
+    let theme = match d.theme {
+        Some(ref name) => *THEMES.iter().find(|t| t.name == name.as_str()).ok_or_else(|| {
+            let names: Vec<String> = THEMES.iter().map(|t| format!("\"{}\"", t.name)).collect();
+            format!("unknown theme \"{}\", expected one of {}", name, names.join(", "))
+        })?,
+        None => THEMES[0],
+    };
+    let backend = match d.backend {
+        Some(ref name) => {
+            let backend = named(&BACKEND_NAMES, name, "backend")?;
+            if !backend.built() {
+                return Err(format!("chord_sim was built without the {} backend", name));
+            }
+            Some(backend)
+        },
+        None => BACKEND_NAMES.iter().map(|&(b, _)| b).find(|b| b.built()),
+    };
+    let fps = d.fps.unwrap_or(DEFAULT_FPS);
+    if !(fps >= 0. && fps.is_finite()) {
+        return Err(format!("fps must be zero or more and finite, not {}", fps));
+    }
+    let steps_per_frame = d.steps_per_frame.unwrap_or(0);
+    if steps_per_frame > MAX_STEPS_PER_FRAME as u64 {
+        return Err(format!("steps_per_frame must be at most {}, not {}", MAX_STEPS_PER_FRAME, steps_per_frame));
+    }
+    let envelope_window = d.envelope_window.unwrap_or(0.);
+    if !(envelope_window >= 0. && envelope_window.is_finite()) {
+        return Err(format!("envelope_window must be zero or more and finite, not {}", envelope_window));
+    }
+    Ok(Options {
+        segments: (dots - 1) as u16,
+        k,
+        damping,
+        damping2,
+        gravity,
+        noise,
+        seed,
+        taper,
+        bridge,
+        dt,
+        integrator,
+        preset,
+        position,
+        angle,
+        width: width as isize,
+        height: height as isize,
+        theme,
+        backend,
+        fps,
+        steps_per_frame: steps_per_frame as u32,
+        envelope_window,
+        headless: None,
+        time_reversal: None,
+        wave_speed_test: false,
+        modes_test: false,
+        compare: None,
+        script: None,
+        replay: None,
+        serve: None,
+        serve_rate: DEFAULT_SERVE_RATE,
+        midi: None,
+        midi_mute: false,
+        pickups: None,
+        gamepad: None,
+        log_stats: None,
+        log_every: DEFAULT_LOG_EVERY,
+        config: None,
+        flags: Config::default(),
+        dump_config: false,
+        help: false,
+    })
+}
+
+// The settings options was run with, with the config file at path as it is
+// now in place of the one read at the start. Only the settings a config
+// file can hold change; the rest are as in options.
+pub fn reload(options: &Options, path: &Path) -> Result<Options, String> {
+    let settings = resolve(Config::load(path)?.overlaid(options.flags.clone()))
+        .map_err(|err| format!("{}: {}", path.display(), err))?;
+    Ok(Options {
+        headless: options.headless.clone(),
+        time_reversal: options.time_reversal,
+        wave_speed_test: options.wave_speed_test,
+        modes_test: options.modes_test,
+        compare: options.compare.clone(),
+        script: options.script.clone(),
+        replay: options.replay.clone(),
+        serve: options.serve,
+        serve_rate: options.serve_rate,
+        midi: options.midi.clone(),
+        midi_mute: options.midi_mute,
+        pickups: options.pickups,
+        gamepad: options.gamepad.clone(),
+        log_stats: options.log_stats.clone(),
+        log_every: options.log_every,
+        config: options.config.clone(),
+        flags: options.flags.clone(),
+        dump_config: options.dump_config,
+        help: options.help,
+        ..settings
+    })
+}
+
+// The two sides of --compare, split at the comma.
+fn sides(option: &str, arg: Option<String>) -> Result<(Side, Side), String> {
+    let arg = value(option, arg)?;
+    match arg.split_once(',') {
+        Some((a, b)) => Ok((side(option, a)?, side(option, b)?)),
+        None => Err(format!("{} expects two comma-separated sides, such as euler,verlet, not {}", option, arg)),
+    }
+}
+
+// One side of --compare: integrator names, k=K and damping=D joined by +.
+fn side(option: &str, text: &str) -> Result<Side, String> {
+    let text = text.trim();
+    let mut side = Side {name: text.to_string(), ..Side::default()};
+    for part in text.split('+').map(str::trim) {
+        match part.split_once('=') {
+            Some(("k", k)) => {
+                let k = real(option, Some(k.to_string()))?;
+                if !(k > 0. && k.is_finite()) {
+                    return Err(format!("{} needs k positive and finite, not {}", option, k));
+                }
+                side.k = Some(k);
+            },
+            Some(("damping", damping)) => {
+                let damping = real(option, Some(damping.to_string()))?;
+                if !(damping >= 0. && damping.is_finite()) {
+                    return Err(format!("{} needs damping zero or more and finite, not {}", option, damping));
+                }
+                side.damping = Some(damping);
+            },
+            Some((name, _)) => return Err(format!("{} can set k or damping, not {}", option, name)),
+            None => side.integrator = Some(named(&INTEGRATOR_NAMES, part, "integrator")?),
+        }
+    }
+    Ok(side)
+}
+
+fn named<T: Copy>(names: &[(T, &str)], name: &str, what: &str) -> Result<T, String> {
+    names.iter().find(|&&(_, n)| n == name).map(|&(t, _)| t).ok_or_else(|| {
+        let known: Vec<&str> = names.iter().map(|&(_, n)| n).collect();
+        format!("unknown {} {}, expected one of {}", what, name, known.join(", "))
+    })
+}
+
+fn name_of<T: Copy + PartialEq>(names: &[(T, &'static str)], t: T) -> &'static str {
+    names.iter().find(|&&(u, _)| u == t).map_or("", |&(_, n)| n)
+}
+
+fn value(option: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("{} needs a value", option))
+}
+
+fn number(option: &str, arg: Option<String>) -> Result<u64, String> {
+    let arg = value(option, arg)?;
+    arg.parse().map_err(|_| format!("{} expects a whole number, not {}", option, arg))
+}
+
+// The two fractions along the string of --pickups.
+fn two_pickups(option: &str, arg: Option<String>) -> Result<(f64, f64), String> {
+    let arg = value(option, arg)?;
+    let (u, v) = arg.split_once(',')
+        .ok_or_else(|| format!("{} expects two comma-separated positions, such as 0.2,0.8, not {}", option, arg))?;
+    let u = real(option, Some(u.trim().to_string()))?;
+    let v = real(option, Some(v.trim().to_string()))?;
+    if let Some(bad) = [u, v].iter().find(|&&p| !(p > 0. && p < 1.)) {
+        return Err(format!("{} positions must be strictly between 0 and 1, not {}", option, bad));
+    }
+    Ok((u, v))
+}
+
+// A decimal number, or a fraction of two of them.
+fn real(option: &str, arg: Option<String>) -> Result<f64, String> {
+    let arg = value(option, arg)?;
+    let parsed = match arg.split_once('/') {
+        Some((a, b)) => a.trim().parse::<f64>().and_then(|a| b.trim().parse::<f64>().map(|b| a/b)),
+        None => arg.parse(),
+    };
+    parsed.map_err(|_| format!("{} expects a number, not {}", option, arg))
+}
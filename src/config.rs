@@ -0,0 +1,275 @@
+// Settings read from string.toml. Every value is optional: whatever the file
+// leaves out falls through to the command line's defaults, and anything
+// given on the command line wins over the file.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+use serde_json::{self, Value};
+use toml_edit::{self, DocumentMut, Item};
+
+pub const FILE_NAME: &str = "string.toml";
+
+// Time between looks at whether the config file has changed.
+const POLL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Simulation {
+    pub dots: Option<u64>,
+    pub k: Option<f64>,
+    pub dt: Option<f64>,
+    pub damping: Option<f64>,
+    pub damping2: Option<f64>,
+    pub gravity: Option<f64>,
+    pub noise: Option<f64>,
+    pub seed: Option<u64>,
+    pub taper: Option<f64>,
+    pub bridge_stiffness: Option<f64>,
+    pub bridge_resistance: Option<f64>,
+    pub integrator: Option<String>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Display {
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub theme: Option<String>,
+    pub backend: Option<String>,
+    pub fps: Option<f64>,
+    pub steps_per_frame: Option<u64>,
+    pub envelope_window: Option<f64>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Initial {
+    pub preset: Option<String>,
+    pub position: Option<f64>,
+    pub angle: Option<f64>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub simulation: Simulation,
+    pub display: Display,
+    pub initial: Initial,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, Value>,
+}
+
+impl Config {
+    // Reads a config file, warning about any keys it doesn't know.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        let config = Config::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+        for key in config.unknown_keys() {
+            eprintln!("warning: {}: unknown key {}", path.display(), key);
+        }
+        Ok(config)
+    }
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let document: DocumentMut = text.parse().map_err(|err: toml_edit::TomlError| err.to_string())?;
+        serde_json::from_value(table_value(document.as_table())).map_err(|err| err.to_string())
+    }
+    // The first config file on the search path, if there is one: the
+    // working directory, then $XDG_CONFIG_HOME (or ~/.config).
+    pub fn find() -> Option<PathBuf> {
+        let config_home = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+        let mut candidates = vec![PathBuf::from(FILE_NAME)];
+        if let Some(dir) = config_home {
+            candidates.push(dir.join("chord_sim").join(FILE_NAME));
+        }
+        candidates.into_iter().find(|p| p.is_file())
+    }
+    // The config file on the search path, or an empty one when there's none.
+    pub fn discover() -> Result<Config, String> {
+        match Config::find() {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+    // This config with every value over gives in its place.
+    pub fn overlaid(self, over: Config) -> Config {
+        Config {
+            simulation: Simulation {
+                dots: over.simulation.dots.or(self.simulation.dots),
+                k: over.simulation.k.or(self.simulation.k),
+                dt: over.simulation.dt.or(self.simulation.dt),
+                damping: over.simulation.damping.or(self.simulation.damping),
+                damping2: over.simulation.damping2.or(self.simulation.damping2),
+                gravity: over.simulation.gravity.or(self.simulation.gravity),
+                noise: over.simulation.noise.or(self.simulation.noise),
+                seed: over.simulation.seed.or(self.simulation.seed),
+                taper: over.simulation.taper.or(self.simulation.taper),
+                bridge_stiffness: over.simulation.bridge_stiffness.or(self.simulation.bridge_stiffness),
+                bridge_resistance: over.simulation.bridge_resistance.or(self.simulation.bridge_resistance),
+                integrator: over.simulation.integrator.or(self.simulation.integrator),
+                unknown: self.simulation.unknown,
+            },
+            display: Display {
+                width: over.display.width.or(self.display.width),
+                height: over.display.height.or(self.display.height),
+                theme: over.display.theme.or(self.display.theme),
+                backend: over.display.backend.or(self.display.backend),
+                fps: over.display.fps.or(self.display.fps),
+                steps_per_frame: over.display.steps_per_frame.or(self.display.steps_per_frame),
+                envelope_window: over.display.envelope_window.or(self.display.envelope_window),
+                unknown: self.display.unknown,
+            },
+            initial: Initial {
+                preset: over.initial.preset.or(self.initial.preset),
+                position: over.initial.position.or(self.initial.position),
+                angle: over.initial.angle.or(self.initial.angle),
+                unknown: self.initial.unknown,
+            },
+            unknown: self.unknown,
+        }
+    }
+    // Dotted names of the keys the file had that mean nothing here.
+    pub fn unknown_keys(&self) -> Vec<String> {
+        let sections = [
+            ("simulation.", &self.simulation.unknown),
+            ("display.", &self.display.unknown),
+            ("initial.", &self.initial.unknown),
+            ("", &self.unknown),
+        ];
+        sections.iter()
+            .flat_map(|&(prefix, keys)| keys.keys().map(move |k| format!("{}{}", prefix, k)))
+            .collect()
+    }
+    // The config as a TOML document, leaving out anything unset.
+    pub fn to_toml(&self) -> String {
+        let s = &self.simulation;
+        let d = &self.display;
+        let i = &self.initial;
+        let sections = [
+            ("simulation", vec![
+                ("dots", s.dots.map(|v| v.to_string())),
+                ("k", s.k.map(float)),
+                ("dt", s.dt.map(float)),
+                ("damping", s.damping.map(float)),
+                ("damping2", s.damping2.map(float)),
+                ("gravity", s.gravity.map(float)),
+                ("noise", s.noise.map(float)),
+                ("seed", s.seed.map(|v| v.to_string())),
+                ("taper", s.taper.map(float)),
+                ("bridge_stiffness", s.bridge_stiffness.map(float)),
+                ("bridge_resistance", s.bridge_resistance.map(float)),
+                ("integrator", s.integrator.as_ref().map(|v| string(v))),
+            ]),
+            ("display", vec![
+                ("width", d.width.map(|v| v.to_string())),
+                ("height", d.height.map(|v| v.to_string())),
+                ("theme", d.theme.as_ref().map(|v| string(v))),
+                ("backend", d.backend.as_ref().map(|v| string(v))),
+                ("fps", d.fps.map(float)),
+                ("steps_per_frame", d.steps_per_frame.map(|v| v.to_string())),
+                ("envelope_window", d.envelope_window.map(float)),
+            ]),
+            ("initial", vec![
+                ("preset", i.preset.as_ref().map(|v| string(v))),
+                ("position", i.position.map(float)),
+                ("angle", i.angle.map(float)),
+            ]),
+        ];
+        let mut toml = String::new();
+        for (name, values) in sections.iter() {
+            if !toml.is_empty() {
+                toml.push('\n');
+            }
+            toml += &format!("[{}]\n", name);
+            for &(key, ref value) in values {
+                if let Some(ref value) = *value {
+                    toml += &format!("{} = {}\n", key, value);
+                }
+            }
+        }
+        toml
+    }
+}
+
+// The parsed TOML as the JSON-like value serde reads configs from.
+fn table_value(table: &toml_edit::Table) -> Value {
+    Value::Object(table.iter().filter_map(|(k, item)| item_value(item).map(|v| (k.to_string(), v))).collect())
+}
+
+fn item_value(item: &Item) -> Option<Value> {
+    match *item {
+        Item::None => None,
+        Item::Value(ref v) => Some(value(v)),
+        Item::Table(ref t) => Some(table_value(t)),
+        Item::ArrayOfTables(ref a) => Some(Value::Array(a.iter().map(table_value).collect())),
+    }
+}
+
+fn value(v: &toml_edit::Value) -> Value {
+    match *v {
+        toml_edit::Value::String(ref s) => Value::from(s.value().clone()),
+        toml_edit::Value::Integer(ref i) => Value::from(*i.value()),
+        toml_edit::Value::Float(ref f) => Value::from(*f.value()),
+        toml_edit::Value::Boolean(ref b) => Value::from(*b.value()),
+        toml_edit::Value::Datetime(ref d) => Value::from(d.value().to_string()),
+        toml_edit::Value::Array(ref a) => Value::Array(a.iter().map(value).collect()),
+        toml_edit::Value::InlineTable(ref t) => Value::Object(
+            t.iter().map(|(k, v)| (k.to_string(), value(v))).collect()),
+    }
+}
+
+// Debug formatting always keeps a decimal point or an exponent, as TOML
+// floats need.
+fn float(v: f64) -> String {
+    format!("{:?}", v)
+}
+
+// JSON string escapes are all valid in TOML's basic strings.
+fn string(v: &str) -> String {
+    Value::from(v).to_string()
+}
+
+// Tells when the config file has been written to since it was last looked
+// at. Without a path of its own it follows the search path, so a file that
+// turns up while running is noticed too.
+pub struct Watch {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    polled: Instant,
+}
+
+impl Watch {
+    pub fn new(path: Option<PathBuf>) -> Watch {
+        let modified = path.clone().or_else(Config::find).and_then(|p| modified(&p));
+        Watch {path, modified, polled: Instant::now()}
+    }
+    // The file to read again, if it has changed since the last look. It
+    // looks at most once every POLL, however often it's asked.
+    pub fn changed(&mut self) -> Option<PathBuf> {
+        if self.polled.elapsed() < POLL {
+            return None;
+        }
+        self.polled = Instant::now();
+        let path = self.path.clone().or_else(Config::find)?;
+        let modified = modified(&path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(path)
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -0,0 +1,40 @@
+use std::io::{self, Write};
+
+// Comma separated rows of a tick number followed by a fixed set of values,
+// under a header row naming the columns.
+pub struct CsvWriter<W: Write> {
+    out: W,
+    columns: usize,
+}
+
+impl<W: Write> CsvWriter<W> {
+    // Writes the header, "tick" and then the names of the value columns.
+    pub fn new(mut out: W, columns: &[String]) -> io::Result<CsvWriter<W>> {
+        write!(out, "tick")?;
+        for c in columns {
+            write!(out, ",{}", c)?;
+        }
+        writeln!(out)?;
+        Ok(CsvWriter {out, columns: columns.len()})
+    }
+    pub fn row(&mut self, tick: u64, values: &[f64]) -> io::Result<()> {
+        if values.len() != self.columns {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("{} values for {} columns", values.len(), self.columns)));
+        }
+        write!(self.out, "{}", tick)?;
+        for &v in values {
+            // Plain decimals run to dozens of digits far from 1.
+            if v == 0. || (1e-4..1e15).contains(&v.abs()) || !v.is_finite() {
+                write!(self.out, ",{}", v)?;
+            } else {
+                write!(self.out, ",{:e}", v)?;
+            }
+        }
+        writeln!(self.out)
+    }
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::VecDeque;
+
+// Level in dB below the peak at which the fit of the decay starts, and how
+// far past that it has to fall before the fit is trusted, as in the T20
+// reverberation time of room acoustics.
+const FIT_START: f64 = 5.;
+const FIT_RANGE: f64 = 20.;
+// Fall the decay time is quoted for.
+const DECAY_DB: f64 = 60.;
+// Peak below which the string counts as silent, as an RMS.
+const SILENCE: f64 = 1e-3;
+// Floor on the level, below the peak, so a string that comes exactly to
+// rest doesn't feed the fit minus infinity.
+const FLOOR_DB: f64 = 120.;
+
+// Times how long a signal's RMS envelope takes to fall 60 dB once it's past
+// the knee of the attack, 5 dB down from its peak: measured when it gets
+// that far, and until then extrapolated from a straight-line fit of the
+// level in dB, once that has fallen 20 dB. The envelope is the RMS over the last window samples, which follows a
+// decay of any speed with the same slope, only late by half a window.
+pub struct DecayMeter {
+    window: usize,
+    count: u64,
+    squares: VecDeque<f64>,
+    sum: f64,
+    // Highest level so far, in dB.
+    peak: Option<f64>,
+    fit: Fit,
+    // Samples the envelope took to fall 60 dB, once it has.
+    measured: Option<f64>,
+}
+
+// Running least-squares line through (sample, level) pairs, with samples
+// counted from the first one so the sums keep their precision.
+#[derive(Default)]
+struct Fit {
+    start: u64,
+    n: f64,
+    t: f64,
+    level: f64,
+    tt: f64,
+    t_level: f64,
+    // Lowest level fitted so far.
+    lowest: f64,
+}
+
+impl Fit {
+    fn add(&mut self, sample: u64, level: f64) {
+        if self.n == 0. {
+            self.start = sample;
+            self.lowest = level;
+        }
+        let t = (sample - self.start) as f64;
+        self.n += 1.;
+        self.t += t;
+        self.level += level;
+        self.tt += t*t;
+        self.t_level += t*level;
+        self.lowest = self.lowest.min(level);
+    }
+    // dB per sample.
+    fn slope(&self) -> Option<f64> {
+        let d = self.n*self.tt - self.t*self.t;
+        if self.n < 2. || d <= 0. {
+            return None;
+        }
+        Some((self.n*self.t_level - self.t*self.level)/d)
+    }
+}
+
+impl DecayMeter {
+    // Meter whose envelope is the RMS over window samples, which should span
+    // a period or so of the fundamental.
+    pub fn new(window: usize) -> DecayMeter {
+        DecayMeter {
+            window: window.max(1),
+            count: 0,
+            squares: VecDeque::with_capacity(window.max(1) + 1),
+            sum: 0.,
+            peak: None,
+            fit: Fit::default(),
+            measured: None,
+        }
+    }
+    // Forgets the signal so far, as when the string is plucked afresh.
+    pub fn reset(&mut self) {
+        *self = DecayMeter::new(self.window);
+    }
+    pub fn feed(&mut self, sample: f64) {
+        self.count += 1;
+        self.squares.push_back(sample*sample);
+        self.sum += sample*sample;
+        if self.squares.len() > self.window {
+            self.sum -= self.squares.pop_front().unwrap_or_default();
+        }
+        // Add the sum up afresh once a window, before rounding left over
+        // from a loud start can swamp a quiet end.
+        if self.count.is_multiple_of(self.window as u64) {
+            self.sum = self.squares.iter().sum();
+        }
+        let rms = (self.sum.max(0.)/self.squares.len() as f64).sqrt();
+        let level = 20.*rms.log10();
+        match self.peak {
+            // A new peak, from the attack or a fresh pluck, starts over.
+            Some(peak) if level <= peak => {},
+            _ => {
+                if rms >= SILENCE {
+                    self.peak = Some(level);
+                    self.fit = Fit::default();
+                    self.measured = None;
+                }
+                return;
+            },
+        }
+        let peak = self.peak.unwrap_or_default();
+        let level = level.max(peak - FLOOR_DB);
+        if level <= peak - FIT_START {
+            self.fit.add(self.count, level);
+        }
+        if self.measured.is_none() && level <= peak - FIT_START - DECAY_DB {
+            self.measured = Some((self.count - self.fit.start) as f64);
+        }
+    }
+    // Samples the envelope takes to fall 60 dB, converted to seconds at
+    // sample_rate, or None until the signal has decayed far enough to say.
+    pub fn decay_time(&self, sample_rate: f64) -> Option<f64> {
+        if let Some(samples) = self.measured {
+            return Some(samples/sample_rate);
+        }
+        let peak = self.peak?;
+        if self.fit.lowest > peak - FIT_START - FIT_RANGE {
+            return None;
+        }
+        let slope = self.fit.slope().filter(|&s| s < 0.)?;
+        Some(-DECAY_DB/slope/sample_rate)
+    }
+}
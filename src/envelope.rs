@@ -0,0 +1,73 @@
+// How far each dot of a string has strayed from the straight line between
+// its ends lately: the most over a sliding window of simulated time. The
+// window is kept as the maxima of a few buckets, so sliding it along costs
+// a bucket at a time rather than a frame at a time, at the price of the
+// oldest bucket hanging on until the whole of it has passed.
+use std::collections::VecDeque;
+
+use Chord;
+
+const BUCKETS: usize = 16;
+
+struct Bucket {
+    start: f64,
+    max: Vec<f64>,
+}
+
+#[derive(Default)]
+pub struct Envelope {
+    buckets: VecDeque<Bucket>,
+    // When the envelope started being tracked, and the latest time fed.
+    since: f64,
+    latest: f64,
+}
+
+impl Envelope {
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+    // Takes in chord as it is now, keeping the last window units of
+    // simulated time. A string whose clock went back, as after a reset, or
+    // with a different number of dots starts the envelope over.
+    pub fn feed(&mut self, chord: &Chord, window: f64) {
+        let time = chord.time();
+        let dots = chord.dots();
+        if self.buckets.back().is_some_and(|b| time < b.start || b.max.len() != dots.len()) {
+            self.clear();
+        }
+        if self.buckets.is_empty() {
+            self.since = time;
+        }
+        self.latest = time;
+        let span = window/BUCKETS as f64;
+        if self.buckets.back().is_none_or(|b| time >= b.start + span) {
+            self.buckets.push_back(Bucket {start: time, max: vec![0.; dots.len()]});
+        }
+        while self.buckets.front().is_some_and(|b| b.start + span < time - window) {
+            self.buckets.pop_front();
+        }
+        let (left, right) = (dots[0].pos(), dots[dots.len()-1].pos());
+        let last = (dots.len() - 1) as f64;
+        if let Some(bucket) = self.buckets.back_mut() {
+            for (i, (m, d)) in bucket.max.iter_mut().zip(dots).enumerate() {
+                let off = d.pos() - (left + (right - left)*(i as f64/last));
+                *m = m.max(off.y.hypot(off.z));
+            }
+        }
+    }
+    // Whether the envelope has been tracked for a whole window yet.
+    pub fn is_full(&self, window: f64) -> bool {
+        !self.buckets.is_empty() && self.latest - self.since >= window
+    }
+    // Each dot's largest displacement over the window.
+    pub fn envelope(&self) -> Vec<f64> {
+        let mut envelope: Vec<f64> = Vec::new();
+        for bucket in &self.buckets {
+            envelope.resize(bucket.max.len(), 0.);
+            for (e, &m) in envelope.iter_mut().zip(&bucket.max) {
+                *e = e.max(m);
+            }
+        }
+        envelope
+    }
+}
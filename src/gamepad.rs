@@ -0,0 +1,137 @@
+// A gamepad through Linux's joystick interface, a device file such as
+// /dev/input/js0 that reads as 8-byte events, watched on a thread of its
+// own. Until the file turns up the thread looks for it once a second, and
+// when a read fails, as it does the moment the pad is unplugged, it goes
+// back to looking, so a pad can come and go while the window runs. With
+// none plugged in nothing comes through at all.
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+// Axes and buttons as the kernel numbers them for an Xbox-style pad.
+pub const LEFT_X: u8 = 0;
+pub const LEFT_Y: u8 = 1;
+pub const RIGHT_TRIGGER: u8 = 5;
+pub const BUTTON_A: u8 = 0;
+
+const AXES: usize = 8;
+// Stick deflection, as a fraction of full, read as none, so a stick that
+// doesn't quite centre leaves the string alone.
+const DEAD_ZONE: f64 = 0.12;
+const LOOK_EVERY: Duration = Duration::from_secs(1);
+
+// Event types, and the flag on the events the kernel sends on opening
+// with the state everything is already in.
+const BUTTON: u8 = 0x01;
+const AXIS: u8 = 0x02;
+const INITIAL: u8 = 0x80;
+
+enum Event {
+    Connected,
+    Disconnected(String),
+    Axis(u8, i16),
+    Button(u8, bool),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Connected,
+    Disconnected(String),
+    Pressed(u8),
+}
+
+pub struct Gamepad {
+    events: Receiver<Event>,
+    connected: bool,
+    axes: [i16; AXES],
+}
+
+impl Gamepad {
+    pub fn watch(path: &Path) -> Gamepad {
+        let (sender, events) = mpsc::channel();
+        let path = path.to_path_buf();
+        thread::spawn(move || watch(&path, &sender));
+        Gamepad {events, connected: false, axes: [0; AXES]}
+    }
+    // Takes in what has happened since the last call, returning the
+    // comings and goings and button presses in order.
+    pub fn update(&mut self) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for event in self.events.try_iter() {
+            match event {
+                Event::Connected => {
+                    self.connected = true;
+                    changes.push(Change::Connected);
+                },
+                Event::Disconnected(why) => {
+                    self.connected = false;
+                    self.axes = [0; AXES];
+                    changes.push(Change::Disconnected(why));
+                },
+                Event::Axis(axis, value) => if let Some(a) = self.axes.get_mut(axis as usize) {
+                    *a = value;
+                },
+                Event::Button(button, true) => changes.push(Change::Pressed(button)),
+                Event::Button(_, false) => {},
+            }
+        }
+        changes
+    }
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+    // Deflection of a stick axis from -1 to 1, none inside the dead zone;
+    // up and left are negative.
+    pub fn stick(&self, axis: u8) -> f64 {
+        let v = self.axes[axis as usize] as f64/i16::MAX as f64;
+        if v.abs() < DEAD_ZONE {0.} else {v.clamp(-1., 1.)}
+    }
+    // How far a trigger is pulled, from 0 to 1. Triggers rest at the
+    // bottom of their axis, which the kernel reports on connecting.
+    pub fn trigger(&self, axis: u8) -> f64 {
+        ((self.axes[axis as usize] as f64/i16::MAX as f64 + 1.)/2.).clamp(0., 1.)
+    }
+}
+
+fn watch(path: &Path, sender: &Sender<Event>) {
+    loop {
+        if let Ok(device) = File::open(path) {
+            if sender.send(Event::Connected).is_err() {
+                return;
+            }
+            let why = read(device, sender);
+            if sender.send(Event::Disconnected(why)).is_err() {
+                return;
+            }
+        }
+        thread::sleep(LOOK_EVERY);
+    }
+}
+
+// Passes on events until the device stops, and says why it did.
+fn read(mut device: File, sender: &Sender<Event>) -> String {
+    let mut event = [0; 8];
+    loop {
+        match device.read_exact(&mut event) {
+            Ok(()) => {},
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(ref err) if err.kind() == io::ErrorKind::UnexpectedEof => return "the device closed".to_string(),
+            Err(err) => return err.to_string(),
+        }
+        // The first four bytes are a timestamp in milliseconds.
+        let value = i16::from_le_bytes([event[4], event[5]]);
+        let number = event[7];
+        let sent = match event[6] & !INITIAL {
+            AXIS => sender.send(Event::Axis(number, value)),
+            BUTTON => sender.send(Event::Button(number, value != 0 && event[6] & INITIAL == 0)),
+            _ => Ok(()),
+        };
+        if sent.is_err() {
+            // The window has gone.
+            return String::new();
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use serde::{Deserialize, Serialize};
+
+use scalar::Scalar;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Deserialize<'de> + Default"))]
+pub struct Vect<T = f64> {
+    pub x: T,
+    pub y: T,
+    // Out of the plane the window shows. Saves from before there was one
+    // load with it zero.
+    #[serde(default)]
+    pub z: T,
+}
+
+impl<T: Scalar> Add for Vect<T> {
+    type Output = Vect<T>;
+    fn add(self, vec: Vect<T>) -> Vect<T> {
+        Vect {x: self.x + vec.x, y: self.y + vec.y, z: self.z + vec.z}
+    }
+}
+
+impl<T: Scalar> Sub for Vect<T> {
+    type Output = Vect<T>;
+    fn sub(self, vec: Vect<T>) -> Vect<T> {
+        Vect {x: self.x - vec.x, y: self.y - vec.y, z: self.z - vec.z}
+    }
+}
+
+impl<T: Scalar> AddAssign for Vect<T> {
+    fn add_assign(&mut self, vec: Vect<T>) {
+        *self = *self + vec;
+    }
+}
+
+impl<T: Scalar> SubAssign for Vect<T> {
+    fn sub_assign(&mut self, vec: Vect<T>) {
+        *self = *self - vec;
+    }
+}
+
+impl<T: Scalar> Neg for Vect<T> {
+    type Output = Vect<T>;
+    fn neg(self) -> Vect<T> {
+        Vect {x: -self.x, y: -self.y, z: -self.z}
+    }
+}
+
+impl<T: Scalar> Mul<T> for Vect<T> {
+    type Output = Vect<T>;
+    fn mul(self, r: T) -> Vect<T> {
+        Vect {x: self.x*r, y: self.y*r, z: self.z*r}
+    }
+}
+
+// r*v as well as v*r. A blanket impl over T isn't allowed on a foreign type,
+// so each float gets its own.
+impl Mul<Vect<f64>> for f64 {
+    type Output = Vect<f64>;
+    fn mul(self, vec: Vect<f64>) -> Vect<f64> {
+        vec*self
+    }
+}
+
+impl Mul<Vect<f32>> for f32 {
+    type Output = Vect<f32>;
+    fn mul(self, vec: Vect<f32>) -> Vect<f32> {
+        vec*self
+    }
+}
+
+impl<T: Scalar> Div<T> for Vect<T> {
+    type Output = Vect<T>;
+    fn div(self, r: T) -> Vect<T> {
+        Vect {x: self.x/r, y: self.y/r, z: self.z/r}
+    }
+}
+
+impl<T: Scalar> fmt::Display for Vect<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        fmt::Display::fmt(&self.x, f)?;
+        write!(f, ", ")?;
+        fmt::Display::fmt(&self.y, f)?;
+        write!(f, ", ")?;
+        fmt::Display::fmt(&self.z, f)?;
+        write!(f, ")")
+    }
+}
+
+impl<T: Scalar> Vect<T> {
+    pub fn zero() -> Vect<T> {
+        Vect::flat(T::zero(), T::zero())
+    }
+    // (x, y) in the plane the window shows.
+    pub fn flat(x: T, y: T) -> Vect<T> {
+        Vect {x, y, z: T::zero()}
+    }
+    pub fn dot(&self, vec: Vect<T>) -> T {
+        self.x*vec.x + self.y*vec.y + self.z*vec.z
+    }
+    pub fn length_squared(&self) -> T {
+        self.dot(*self)
+    }
+    pub fn size(&self) -> T {
+        self.length_squared().sqrt()
+    }
+    // The unit vector along this one, or zero for the zero vector, which
+    // has no direction to keep.
+    pub fn normalized(&self) -> Vect<T> {
+        let size = self.size();
+        if size == T::zero() {
+            Vect::zero()
+        } else {
+            *self/size
+        }
+    }
+    pub fn scale(&mut self, r: T) {
+        self.x *= r;
+        self.y *= r;
+    }
+}
+
+// Point t of the way from p1 to p2 along the Catmull-Rom spline through
+// p0, p1, p2 and p3. At an end of a curve, pass its end point twice.
+pub fn catmull_rom(p0: Vect, p1: Vect, p2: Vect, p3: Vect, t: f64) -> Vect {
+    let (t2, t3) = (t*t, t*t*t);
+    let w0 = -0.5*t3 + t2 - 0.5*t;
+    let w1 = 1.5*t3 - 2.5*t2 + 1.;
+    let w2 = -1.5*t3 + 2.*t2 + 0.5*t;
+    let w3 = 0.5*t3 - 0.5*t2;
+    p0*w0 + p1*w1 + p2*w2 + p3*w3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catmull_rom_runs_from_p1_to_p2() {
+        let (p0, p1, p2, p3) = (Vect::flat(-1., 3.), Vect::flat(0., 1.), Vect::flat(2., 2.), Vect::flat(5., -4.));
+        assert_eq!(catmull_rom(p0, p1, p2, p3, 0.), p1);
+        assert_eq!(catmull_rom(p0, p1, p2, p3, 1.), p2);
+    }
+
+    #[test]
+    fn catmull_rom_through_collinear_points_is_straight() {
+        let along = |s: f64| Vect::flat(1. + 2.*s, -3. + 0.5*s);
+        let (p0, p1, p2, p3) = (along(-1.), along(0.), along(1.), along(2.));
+        for i in 0..=10 {
+            let t = i as f64/10.;
+            let p = catmull_rom(p0, p1, p2, p3, t);
+            assert!((p - along(t)).size() < 1e-12, "t = {}: {:?} against {:?}", t, p, along(t));
+        }
+        // Unevenly spaced along the line, it stays on it.
+        let (q0, q3) = (along(-3.), along(1.5));
+        let normal = Vect::flat(-0.5, 2.);
+        for i in 0..=10 {
+            let p = catmull_rom(q0, p1, p2, q3, i as f64/10.);
+            assert!((p - p1).dot(normal).abs() < 1e-12);
+        }
+    }
+
+    fn vect(x: f64, y: f64, z: f64) -> Vect {
+        Vect {x, y, z}
+    }
+
+    #[test]
+    fn vect_arithmetic_works_coordinate_by_coordinate() {
+        let (a, b) = (vect(1., -2., 0.5), vect(3., 4., -1.));
+        assert_eq!(a + b, vect(4., 2., -0.5));
+        assert_eq!(a - b, vect(-2., -6., 1.5));
+        assert_eq!(-a, vect(-1., 2., -0.5));
+        assert_eq!(a*2., vect(2., -4., 1.));
+        assert_eq!(2.*a, a*2.);
+        assert_eq!(a/2., a*0.5);
+        assert_eq!(a - a, Vect::zero());
+        let mut c = a;
+        c += b;
+        assert_eq!(c, a + b);
+        c -= b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn vect_dot_and_lengths_agree() {
+        let (a, b) = (vect(1., -2., 0.5), vect(3., 4., -1.));
+        assert_eq!(a.dot(b), 1.*3. - 2.*4. - 0.5);
+        assert_eq!(a.dot(b), b.dot(a));
+        assert_eq!(a.dot(a), a.length_squared());
+        assert_eq!(a.size(), a.length_squared().sqrt());
+        assert_eq!(vect(3., 4., 0.).size(), 5.);
+        assert_eq!(a.dot(Vect::zero()), 0.);
+    }
+
+    #[test]
+    fn normalized_keeps_the_direction_at_unit_length() {
+        for &a in &[vect(1., -2., 0.5), vect(0., 0., -7.), vect(1e-100, 3e-100, 0.), vect(1e150, -1e150, 1e150)] {
+            let unit = a.normalized();
+            assert!((unit.size() - 1.).abs() < 1e-15, "{} normalized to {} long", a, unit.size());
+            assert!((unit*a.size() - a).size() <= 1e-15*a.size(), "{} normalized to {}", a, unit);
+            assert!(unit.dot(a) > 0.);
+        }
+        assert_eq!(Vect::<f64>::zero().normalized(), Vect::zero());
+    }
+
+    #[test]
+    fn vect_prints_its_three_coordinates() {
+        assert_eq!(vect(1., -2.5, 0.).to_string(), "(1, -2.5, 0)");
+        assert_eq!(format!("{:.2}", vect(1., 2., 3.)), "(1.00, 2.00, 3.00)");
+        assert_eq!(format!("{:?}", vect(1., 2., 3.)), "Vect { x: 1.0, y: 2.0, z: 3.0 }");
+    }
+}
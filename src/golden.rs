@@ -0,0 +1,154 @@
+// Reference images of what the window draws, so a change to how strings
+// come out on screen shows up without anyone having to look. Scenes are
+// drawn into memory at a fixed size, with no window, and compared pixel by
+// pixel with BMP files checked in under goldens/. With UPDATE_GOLDENS=1 in
+// the environment the images are written afresh instead.
+use std::env;
+use std::path::PathBuf;
+
+use {Chord, Integrator};
+use app::Recorder;
+use bmp;
+use cli;
+use pair;
+use render::Framebuffer;
+use screen::{DrawMode, Screen, SCOPE_SAMPLES, THEMES};
+use setup::PICKUP_POS;
+
+pub const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+
+// Most any channel of a pixel may be out by and still match, so a colour
+// rounded the other way doesn't fail a scene.
+const TOLERANCE: u8 = 8;
+
+pub enum Outcome {
+    Matched,
+    Written,
+    // How many pixels were out by more than TOLERANCE, and where what was
+    // drawn instead has been written.
+    Differs {pixels: usize, actual: PathBuf},
+}
+
+pub fn updating() -> bool {
+    env::var_os("UPDATE_GOLDENS").is_some_and(|v| v == "1")
+}
+
+// Checks the scene name, drawn as rows of RGB bytes from the top left,
+// against its golden image, or writes the image when updating.
+pub fn check(name: &str, width: usize, height: usize, rgb: &[u8]) -> Result<Outcome, String> {
+    let path = PathBuf::from(DIR).join(format!("{}.bmp", name));
+    if updating() {
+        bmp::write_bmp(&path, width, height, rgb).map_err(|err| format!("{}: {}", path.display(), err))?;
+        return Ok(Outcome::Written);
+    }
+    let (w, h, golden) = bmp::read_bmp(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let pixels = if (w, h) != (width, height) {
+        width*height
+    } else {
+        golden.chunks_exact(3).zip(rgb.chunks_exact(3))
+            .filter(|(g, p)| g.iter().zip(p.iter()).any(|(&a, &b)| a.abs_diff(b) > TOLERANCE))
+            .count()
+    };
+    if pixels == 0 {
+        return Ok(Outcome::Matched);
+    }
+    let actual = PathBuf::from(DIR).join(format!("{}.actual.bmp", name));
+    bmp::write_bmp(&actual, width, height, rgb).map_err(|err| format!("{}: {}", actual.display(), err))?;
+    Ok(Outcome::Differs {pixels, actual})
+}
+
+// Size the golden scenes are drawn at, and their names.
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 96;
+pub const SCENES: [&str; 9] = ["triangle", "light", "clipped", "moving", "above", "compare", "antialiased", "antialiased-clipped",
+                                   "zoomed"];
+
+// Draws the golden scene name: a string in a known state, and the screen
+// set up to show some part of how it draws.
+fn draw_scene(screen: &mut Screen, name: &str) {
+    let k = 1./4096.;
+    match name {
+        "triangle" => screen.draw(&Chord::new(40, k), &[]),
+        "light" => {
+            screen.theme = THEMES[1];
+            screen.smooth = true;
+            screen.draw_mode = DrawMode::Lines;
+            screen.draw(&Chord::new_sine(40, k), &[]);
+        },
+        // Far taller than the window, so the lines are clipped.
+        "clipped" => {
+            screen.zoom(4.);
+            screen.draw(&Chord::new_pluck(40, k, 0.3, 15.), &[]);
+        },
+        "moving" => {
+            screen.theme = THEMES[2];
+            screen.colour_by_speed = true;
+            screen.draw_mode = DrawMode::Dots;
+            let mut chord = Chord::new_pluck(40, k, 0.2, 8.);
+            let mut recorder = Recorder::new(PICKUP_POS, SCOPE_SAMPLES);
+            for _ in 0..2000 {
+                chord.tick();
+                recorder.record(&chord);
+            }
+            screen.draw(&chord, recorder.samples());
+        },
+        // Both antialiased paths, blending over a light background and
+        // the oscilloscope under it.
+        "antialiased" => {
+            screen.theme = THEMES[1];
+            screen.antialias = true;
+            let mut chord = Chord::new_pluck(40, k, 0.2, 8.);
+            let mut recorder = Recorder::new(PICKUP_POS, SCOPE_SAMPLES);
+            for _ in 0..2000 {
+                chord.tick();
+                recorder.record(&chord);
+            }
+            screen.draw(&chord, recorder.samples());
+        },
+        // Dots and lines running off every side of the window.
+        "antialiased-clipped" => {
+            screen.antialias = true;
+            screen.zoom(4.);
+            screen.draw(&Chord::new_pluck(40, k, 0.3, 15.), &[]);
+        },
+        // Six notches of the wheel toward the left end of a pulse.
+        "zoomed" => {
+            let chord = Chord::new_pulse(40, k, 0.2, 0.05, 3.);
+            screen.mouse = (24, 30);
+            screen.wheel = 6;
+            screen.steer_camera(&chord);
+            screen.draw(&chord, &[]);
+        },
+        "above" => {
+            screen.from_above = true;
+            let mut chord = Chord::new_pluck_angled(40, k, 0.2, 8., 45f64.to_radians());
+            for _ in 0..500 {
+                chord.tick();
+            }
+            screen.draw(&chord, &[]);
+        },
+        _ => {
+            let mut chord = Chord::new_pluck(40, k, 0.2, 3.);
+            let side = |name: &str, integrator| cli::Side {name: name.to_string(), integrator: Some(integrator), ..cli::Side::default()};
+            let mut other = chord.clone();
+            other.set_integrator(Integrator::Verlet);
+            let mut pair = pair::Pair::new(other, (side("euler", Integrator::Euler), side("verlet", Integrator::Verlet)));
+            for _ in 0..pair::HISTORY {
+                for _ in 0..100 {
+                    chord.tick();
+                    pair.other.tick();
+                }
+                pair.record(&chord);
+            }
+            screen.draw_pair(&chord, &pair);
+        },
+    }
+}
+
+// The golden scene name drawn into memory, as rows of RGB bytes from the
+// top left.
+pub fn scene(name: &str) -> Result<Vec<u8>, String> {
+    let mut screen = Screen::with_renderer(Box::new(Framebuffer::new(WIDTH, HEIGHT)));
+    draw_scene(&mut screen, name);
+    screen.capture()
+}
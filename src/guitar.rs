@@ -0,0 +1,182 @@
+// Several strings played as one instrument.
+use geometry::Vect;
+use string::{Chord, Dot};
+
+// Movable end shared by several strings: a heavy dot held to its anchor by
+// a stiff, slightly lossy spring. It only moves across the strings, and
+// every string it carries feels the others through it.
+struct Bridge {
+    dot: Dot,
+    anchor: Vect,
+    stiffness: f64,
+    damping: f64,
+}
+
+impl Bridge {
+    pub fn new(anchor: Vect, mass: f64, stiffness: f64, damping: f64) -> Bridge {
+        let mut dot = Dot::new(anchor.x, anchor.y, false);
+        dot.set_mass(mass);
+        Bridge {dot, anchor, stiffness, damping}
+    }
+    pub fn tick(&mut self, pull: Vect, dt: f64) {
+        let y = pull.y - self.stiffness*(self.dot.pos().y - self.anchor.y)
+            - self.damping*self.dot.vel().y;
+        self.dot.set_force(Vect::flat(0., y));
+        self.dot.accelerate(dt);
+        self.dot.move_it(dt);
+    }
+}
+
+// Bridge the guitar's strings can share: its weight, its spring to the body
+// and how much energy it soaks up.
+const BRIDGE_MASS: f64 = 10.;
+const BRIDGE_STIFFNESS: f64 = 0.005;
+const BRIDGE_DAMPING: f64 = 1e-3;
+
+// Standard tuning in Hz, string 1 (high E) to string 6 (low E).
+pub const TUNING: [f64; 6] = [329.63, 246.94, 196.00, 146.83, 110.00, 82.41];
+
+// Six strings played together.
+pub struct Guitar {
+    strings: Vec<Chord>,
+    // Plucks still waiting for their turn in a strum, as (tick, string).
+    pending: Vec<(u64, usize)>,
+    ticks: u64,
+    // Shared right-hand end, when the strings are coupled.
+    bridge: Option<Bridge>,
+}
+
+impl Guitar {
+    // Strings of n segments tuned for playback at speed units of simulated
+    // time per second, the same scale as the audio output.
+    pub fn new(n: u16, speed: f64) -> Guitar {
+        let strings = TUNING.iter().map(|&hz| {
+            let mut string = Chord::from_shape(n, 1., |_| 0.);
+            string.tune_to(hz/speed, 1.);
+            string
+        }).collect();
+        Guitar {strings, pending: Vec::new(), ticks: 0, bridge: None}
+    }
+    pub fn strings(&self) -> &[Chord] {
+        &self.strings
+    }
+    pub fn strings_mut(&mut self) -> &mut [Chord] {
+        &mut self.strings
+    }
+    // Couples the strings through a shared movable bridge, or gives each
+    // its own rigid end again.
+    pub fn toggle_bridge(&mut self) {
+        if self.bridge.take().is_some() {
+            for s in self.strings.iter_mut() {
+                let x = s.dots()[s.dots().len()-1].pos().x;
+                s.set_end(Vect::flat(x, 0.));
+            }
+            return;
+        }
+        let end = self.strings[0].dots()[self.strings[0].dots().len()-1].pos();
+        self.bridge = Some(Bridge::new(end, BRIDGE_MASS, BRIDGE_STIFFNESS, BRIDGE_DAMPING));
+    }
+    pub fn tick(&mut self) {
+        let mut i = 0;
+        while i < self.pending.len() {
+            if self.pending[i].0 <= self.ticks {
+                let (_, string) = self.pending.swap_remove(i);
+                self.pluck(string);
+            } else {
+                i += 1;
+            }
+        }
+        if let Some(ref mut bridge) = self.bridge {
+            let pull = self.strings.iter()
+                .fold(Vect::zero(), |sum, s| sum + s.end_force());
+            bridge.tick(pull, self.strings[0].dt());
+            for s in self.strings.iter_mut() {
+                s.set_end(bridge.dot.pos());
+            }
+        }
+        for string in self.strings.iter_mut() {
+            string.tick();
+        }
+        self.ticks += 1;
+    }
+    pub fn pluck(&mut self, string: usize) {
+        if let Some(s) = self.strings.get_mut(string) {
+            s.excite(pluck_shape);
+        }
+    }
+    // Plucks every string from low to high, delay ticks apart.
+    pub fn strum(&mut self, delay: u64) {
+        let count = self.strings.len();
+        for j in 0..count {
+            self.pending.push((self.ticks + j as u64*delay, count-1-j));
+        }
+    }
+    // Mixed signal of every string's pickup at the same position.
+    pub fn pickup(&self, position: f64) -> f64 {
+        self.strings.iter().map(|s| s.pickup(position)).sum()
+    }
+}
+
+// Height of the triangular bump added by re-plucking.
+const REPLUCK: f64 = 10.;
+
+pub fn pluck_shape(u: f64) -> f64 {
+    pluck_shape_at(u, 0.5)
+}
+
+// The same bump centred at the fraction position along the string instead,
+// narrowed near the ends so it still falls to nothing before them.
+pub fn pluck_shape_at(u: f64, position: f64) -> f64 {
+    let half = 0.25f64.min(position).min(1. - position);
+    (1. - (u - position).abs()/half).max(0.)*REPLUCK
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    // Three strings on one bridge, the first two at hz cycles a tick and
+    // the third detune times higher.
+    fn on_a_bridge(hz: f64, detune: f64) -> Guitar {
+        let strings: Vec<Chord> = [hz, hz, hz*detune].iter().map(|&f| {
+            let mut string = Chord::from_shape(20, 1., |_| 0.);
+            string.tune_to(f, 1.);
+            string
+        }).collect();
+        let end = strings[0].dots()[strings[0].dots().len()-1].pos();
+        let bridge = Some(Bridge::new(end, BRIDGE_MASS, BRIDGE_STIFFNESS, BRIDGE_DAMPING));
+        Guitar {strings, pending: Vec::new(), ticks: 0, bridge}
+    }
+
+    // Largest amplitude each string's first mode reaches over ticks ticks.
+    fn peaks(guitar: &mut Guitar, ticks: u64) -> Vec<f64> {
+        let mut peaks = vec![0.; guitar.strings().len()];
+        for _ in 0..ticks {
+            guitar.tick();
+            for (peak, s) in peaks.iter_mut().zip(guitar.strings()) {
+                *peak = s.mode_amplitudes(1)[0].abs().max(*peak);
+            }
+        }
+        peaks
+    }
+
+    #[test]
+    fn a_string_in_tune_rings_along_and_one_a_tone_off_barely_moves() {
+        let mut guitar = on_a_bridge(1./2000., 1.12);
+        guitar.strings_mut()[0].excite(|u| 0.5*(PI*u).sin());
+        let peaks = peaks(&mut guitar, 60_000);
+        assert!(peaks[1] > 0.2, "the string in tune only got to {}", peaks[1]);
+        assert!(peaks[1] > 5.*peaks[2], "the string in tune got to {} and the one a tone off to {}", peaks[1], peaks[2]);
+    }
+
+    #[test]
+    fn without_the_bridge_nothing_rings_along() {
+        let mut guitar = on_a_bridge(1./2000., 1.12);
+        guitar.toggle_bridge();
+        guitar.strings_mut()[0].excite(|u| 0.5*(PI*u).sin());
+        let peaks = peaks(&mut guitar, 20_000);
+        assert!(peaks[1] == 0. && peaks[2] == 0., "the quiet strings got to {} and {}", peaks[1], peaks[2]);
+    }
+}
@@ -0,0 +1,193 @@
+// Runs without a window: the headless CSV run and the tests that print how
+// the string behaves.
+use std::f64;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use {Chord, Layout, Vect};
+use cli;
+use csv;
+use decay;
+use npy;
+use pulse;
+use script;
+use serve;
+use setup::{divergence, max_amplitude, preset, timestamp, DECAY_WINDOW, PICKUP_POS, PULSE_PRESET};
+use stats;
+use trajectory;
+
+// Modes --modes-test prints.
+const MODES_TESTED: usize = 8;
+
+// Runs the string with no window for the given number of ticks, writing
+// every'th tick to a CSV file, and prints how it went. A script's events
+// land on the first tick at or after their time.
+pub(crate) fn run_headless(chord: &mut Chord, options: &cli::Headless, mut script: Option<script::Script>,
+                mut server: Option<serve::Server>, mut stats: Option<stats::StatsLog>) -> io::Result<()> {
+    let path = options.output.clone()
+        .unwrap_or_else(|| PathBuf::from(format!("string-{}.csv", timestamp())));
+    let columns: Vec<String> = if options.pickup_only {
+        vec!["pickup".to_string()]
+    } else {
+        (0..chord.dots().len()).map(|i| format!("y{}", i)).collect()
+    };
+    let mut csv = csv::CsvWriter::new(BufWriter::new(File::create(&path)?), &columns)?;
+    let start = Instant::now();
+    let mut values = Vec::with_capacity(columns.len());
+    let mut write = |chord: &Chord, tick: u64| {
+        values.clear();
+        if options.pickup_only {
+            values.push(chord.pickup(PICKUP_POS));
+        } else {
+            values.extend(chord.dots().iter().map(|d| d.pos().y));
+        }
+        csv.row(tick, &values)
+    };
+    let mut trajectory = match options.trajectory {
+        Some(ref path) => Some(trajectory::TrajectoryWriter::create(path, chord, options.every)?),
+        None => None,
+    };
+    let mut series = match options.export_series {
+        Some(ref path) => {
+            let columns = ["pickup".to_string(), "energy".to_string()];
+            Some(csv::CsvWriter::new(BufWriter::new(File::create(path)?), &columns)?)
+        },
+        None => None,
+    };
+    let mut write_series = |chord: &Chord, tick: u64| match series {
+        Some(ref mut s) => s.row(tick, &[chord.pickup(PICKUP_POS), chord.total_energy()]),
+        None => Ok(()),
+    };
+    // Nothing looks at the dots between rows, so they can run split.
+    chord.set_layout(Layout::Split);
+    write(chord, 0)?;
+    write_series(chord, 0)?;
+    if let Some(ref mut t) = trajectory {
+        t.frame(chord)?;
+    }
+    if let Some(ref mut s) = stats {
+        s.log(chord)?;
+    }
+    // The decay meter hears the pickup once per row's worth of ticks.
+    let mut decay = decay::DecayMeter::new(DECAY_WINDOW/options.every as usize);
+    let mut tick = 0;
+    let mut diverged = None;
+    while tick < options.ticks {
+        let mut run = (options.every - tick % options.every).min(options.ticks - tick);
+        if let Some(ref mut script) = script {
+            let actions = script.due(chord.time());
+            if actions.contains(&script::Action::Quit) {
+                break;
+            }
+            for action in &actions {
+                action.apply(chord);
+            }
+            if let Some(at) = script.next_at() {
+                run = run.min(((at - chord.time())/chord.dt()).ceil().max(1.) as u64);
+            }
+        }
+        // The series wants every tick.
+        if options.export_series.is_some() {
+            run = 1;
+        }
+        if let Some(ref s) = stats {
+            run = run.min(s.due_in(chord.ticks()));
+        }
+        let started = Instant::now();
+        chord.run(run);
+        tick += run;
+        if let Some(ref mut s) = stats {
+            s.ticked(started.elapsed());
+            s.log(chord)?;
+        }
+        write_series(chord, tick)?;
+        if let Some(ref mut server) = server {
+            server.offer(chord);
+        }
+        if tick % options.every == 0 {
+            decay.feed(chord.pickup(PICKUP_POS));
+            write(chord, tick)?;
+            if let Some(ref mut t) = trajectory {
+                t.frame(chord)?;
+            }
+        }
+        diverged = divergence(chord);
+        if diverged.is_some() {
+            break;
+        }
+    }
+    csv.finish()?;
+    if let Some(t) = trajectory {
+        t.finish()?;
+    }
+    if let Some(s) = series {
+        s.finish()?;
+    }
+    if let Some(s) = stats {
+        s.finish()?;
+    }
+    if let Some(ref path) = options.export_state {
+        if path.extension().is_some_and(|e| e == "npy") {
+            let positions: Vec<Vec<f64>> = chord.dots().iter().map(|d| vec![d.pos().x, d.pos().y, d.pos().z]).collect();
+            npy::write_npy(path, &positions)?;
+        } else {
+            chord.export_csv(path)?;
+        }
+    }
+    if let Some(message) = diverged {
+        return Err(io::Error::other(format!("{}; stopped at tick {}, wrote {}", message, tick, path.display())));
+    }
+    let t60 = match decay.decay_time(1./options.every as f64) {
+        Some(ticks) => format!("{:.0} ticks", ticks),
+        None => "not yet known".to_string(),
+    };
+    let (com, momentum) = (chord.center_of_mass(), chord.total_momentum());
+    println!("ran {} ticks in {:.3} s, final max amplitude {}, T60 {}, centre of mass ({}, {}), momentum ({:e}, {:e}), wrote {}",
+             tick, start.elapsed().as_secs_f64(), max_amplitude(chord), t60, com.x, com.y, momentum.x, momentum.y, path.display());
+    Ok(())
+}
+
+// Runs the string ticks ticks, turns it round and runs it as many again,
+// then prints how far the furthest dot ended up from where it started.
+pub(crate) fn time_reversal_test(chord: &mut Chord, ticks: u64) {
+    let start: Vec<Vect> = chord.dots().iter().map(|d| d.pos()).collect();
+    chord.run(ticks);
+    let swing = max_amplitude(chord);
+    chord.reverse_velocities();
+    chord.run(ticks);
+    let error = chord.dots().iter().zip(&start).map(|(d, &p)| (d.pos() - p).size()).fold(0., f64::max);
+    println!("{} ticks each way with the {:?} integrator: max position error {:e}, against a swing of {} halfway",
+             ticks, chord.integrator(), error, swing);
+}
+
+// Sends a pulse down a string with the settings of chord and back, then
+// prints how fast it went each way against the wave speed, and which way up
+// it came back.
+pub(crate) fn wave_speed_test(chord: &Chord) -> Result<(), String> {
+    let mut pulse = preset(PULSE_PRESET, chord);
+    let theory = pulse.wave_speed();
+    let report = pulse::follow(&mut pulse).ok_or("the pulse didn't make it down the string and back")?;
+    let error = |speed: f64| (speed.abs()/theory - 1.)*100.;
+    println!("pulse of height {} going out at {:.6} ({:+.3}%), back at {:.6} ({:+.3}%), against a wave speed of {:.6}",
+             report.out.height, report.out.speed, error(report.out.speed),
+             report.back.speed, error(report.back.speed), theory);
+    let flipped = report.out.height.signum() != report.back.height.signum();
+    println!("it came back from the far end {} at height {}",
+             if flipped {"upside down, as off a fixed end"} else {"the same way up"}, report.back.height);
+    Ok(())
+}
+
+// Prints the frequencies of the first few modes of chord, each against the
+// harmonic of the fundamental it would be on a uniform string of no width.
+pub(crate) fn modes_test(chord: &Chord) {
+    let modes = chord.mode_frequencies(MODES_TESTED);
+    println!("modes of {} dots, the last {} times as heavy as the first, under {:?}:",
+             chord.dots().len(), chord.taper(), chord.integrator());
+    for (m, &f) in modes.iter().enumerate() {
+        let ratio = f/modes[0];
+        println!("  mode {}: {:.6e} cycles per unit time, {:.4} times the fundamental, {:+.1} cents from harmonic",
+                 m + 1, f, ratio, 1200.*(ratio/(m + 1) as f64).log2());
+    }
+}
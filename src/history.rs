@@ -0,0 +1,74 @@
+// Undo and redo for what's done to the string by hand. Before each pluck,
+// fret, touch or change of a setting the window keeps a copy of the string,
+// and of the other string when comparing two, so Ctrl+Z can put it back
+// just as it was the moment before, clock and all. Ticking is never kept:
+// undoing goes back past however long the string has run since. Only the
+// latest LIMIT copies are kept.
+use std::collections::VecDeque;
+
+use Chord;
+use pair::Pair;
+
+pub const LIMIT: usize = 32;
+
+#[derive(Clone)]
+struct Moment {
+    chord: Chord,
+    other: Option<Chord>,
+}
+
+impl Moment {
+    fn of(chord: &Chord, pair: &Option<Pair>) -> Moment {
+        Moment {chord: chord.clone(), other: pair.as_ref().map(|p| p.other.clone())}
+    }
+    fn restore(self, chord: &mut Chord, pair: &mut Option<Pair>) {
+        *chord = self.chord;
+        if let (Some(p), Some(other)) = (pair, self.other) {
+            p.other = other;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct History {
+    // Oldest first.
+    undo: VecDeque<Moment>,
+    redo: Vec<Moment>,
+}
+
+impl History {
+    // Keeps the strings as they are, before something is done to them,
+    // which leaves nothing to redo.
+    pub fn push(&mut self, chord: &Chord, pair: &Option<Pair>) {
+        if self.undo.len() == LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(Moment::of(chord, pair));
+        self.redo.clear();
+    }
+    // Puts the strings back as they were before the last thing done to
+    // them, keeping them as they are for redo. False if there's nothing to
+    // undo.
+    pub fn undo(&mut self, chord: &mut Chord, pair: &mut Option<Pair>) -> bool {
+        match self.undo.pop_back() {
+            Some(moment) => {
+                self.redo.push(Moment::of(chord, pair));
+                moment.restore(chord, pair);
+                true
+            },
+            None => false,
+        }
+    }
+    // Takes back the last undo, putting the strings back as they were when
+    // it was made.
+    pub fn redo(&mut self, chord: &mut Chord, pair: &mut Option<Pair>) -> bool {
+        match self.redo.pop() {
+            Some(moment) => {
+                self.undo.push_back(Moment::of(chord, pair));
+                moment.restore(chord, pair);
+                true
+            },
+            None => false,
+        }
+    }
+}
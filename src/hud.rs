@@ -0,0 +1,92 @@
+// The panel of settings that can be changed while the string runs: Tab
+// moves the focus down the list and then off it, hiding the panel, and Up
+// and Down step the focused one. Settings that span orders of magnitude
+// step by a constant factor, the rest by a constant amount, and each stays
+// within bounds.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Param {
+    K,
+    Damping,
+    Dt,
+    // Multiple of the usual height P plucks to.
+    PluckScale,
+    // Fraction of the way along the string P plucks at.
+    PluckPosition,
+}
+
+pub const PARAMS: [Param; 5] = [Param::K, Param::Damping, Param::Dt, Param::PluckScale, Param::PluckPosition];
+
+// Factors the logarithmic settings step by, so four steps double k or dt
+// and two double the damping, and the smallest damping above none, which a
+// step down from goes to none.
+const K_STEP: f64 = 1.189_207_115_002_721;
+const DAMPING_STEP: f64 = std::f64::consts::SQRT_2;
+const DT_STEP: f64 = K_STEP;
+const MIN_DAMPING: f64 = 1./65536.;
+
+impl Param {
+    pub fn name(self) -> &'static str {
+        match self {
+            Param::K => "k",
+            Param::Damping => "damping",
+            Param::Dt => "dt",
+            Param::PluckScale => "pluck",
+            Param::PluckPosition => "pluck at",
+        }
+    }
+    // Smallest and largest value the setting can be stepped to.
+    fn bounds(self) -> (f64, f64) {
+        match self {
+            Param::K => (1./(1 << 20) as f64, 4.),
+            Param::Damping => (0., 1./16.),
+            Param::Dt => (1./64., 16.),
+            Param::PluckScale => (0.25, 4.),
+            Param::PluckPosition => (0.05, 0.95),
+        }
+    }
+    // The value a step up or down from value, within bounds.
+    pub fn step(self, value: f64, up: bool) -> f64 {
+        let stepped = match self {
+            Param::K => if up {value*K_STEP} else {value/K_STEP},
+            Param::Damping if value < MIN_DAMPING => if up {MIN_DAMPING} else {0.},
+            Param::Damping if !up && value < MIN_DAMPING*DAMPING_STEP => 0.,
+            Param::Damping => if up {value*DAMPING_STEP} else {value/DAMPING_STEP},
+            Param::Dt => if up {value*DT_STEP} else {value/DT_STEP},
+            Param::PluckScale => value + if up {0.25} else {-0.25},
+            Param::PluckPosition => value + if up {0.05} else {-0.05},
+        };
+        let (low, high) = self.bounds();
+        stepped.clamp(low, high)
+    }
+    // The value as the panel shows it.
+    pub fn show(self, value: f64) -> String {
+        match self {
+            Param::K | Param::Damping if value == 0. => format!("{} 0", self.name()),
+            Param::K | Param::Damping => format!("{} {:.3e} (1/{:.0})", self.name(), value, 1./value),
+            Param::Dt => format!("{} {:.3}", self.name(), value),
+            Param::PluckScale => format!("{} x{:.2}", self.name(), value),
+            Param::PluckPosition => format!("{} {:.2}", self.name(), value),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Hud {
+    focus: Option<usize>,
+}
+
+impl Hud {
+    // Moves the focus to the next setting, or off the last one.
+    pub fn cycle(&mut self) {
+        self.focus = match self.focus {
+            None => Some(0),
+            Some(i) if i + 1 < PARAMS.len() => Some(i + 1),
+            Some(_) => None,
+        };
+    }
+    // The setting Up and Down change, while the panel is showing.
+    pub fn focus(&self) -> Option<Param> {
+        self.focus.map(|i| PARAMS[i])
+    }
+}
@@ -0,0 +1,117 @@
+// The Karplus-Strong plucked string, as a yardstick for the mass-spring
+// one: a delay line whose output is averaged with the sample after it and
+// fed back in. The averaging is a low-pass filter, so the high partials die
+// first, much as on a real string, and the loop sounds at the line's
+// sample rate over its length plus half a sample for the filter.
+//
+// The filter only does its work once a trip, so a line run at the string's
+// own tick rate, thousands of samples long, would loop its noise burst
+// almost untouched. The line runs at an audio rate instead, moving on one
+// sample every stride ticks, and what's heard in between is interpolated.
+use model::StringModel;
+
+pub struct KarplusStrong {
+    line: Vec<f64>,
+    // Index of the sample leaving the line next.
+    head: usize,
+    // Ticks per step of the line, and ticks since the last step.
+    stride: u32,
+    phase: u32,
+    // The last two samples to leave the line, heard in between.
+    previous: f64,
+    output: f64,
+    // Gain on each trip round the loop, 1 for no loss beyond the filter's.
+    feedback: f64,
+    dt: f64,
+    // State of the noise generator for plucks.
+    noise: u64,
+}
+
+impl KarplusStrong {
+    // A silent line of length samples, ticking dt at a time and stepping
+    // once every stride ticks.
+    pub fn new(length: usize, stride: u32, dt: f64) -> KarplusStrong {
+        KarplusStrong {
+            line: vec![0.; length.max(2)],
+            head: 0,
+            stride: stride.max(1),
+            phase: 0,
+            previous: 0.,
+            output: 0.,
+            feedback: 1.,
+            dt,
+            noise: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+    // A line run at about rate samples per unit of simulated time, of the
+    // length that sounds closest to frequency cycles per unit, as
+    // Chord::linear_fundamental gives them. The length is a whole number of
+    // samples, so the tuning is out by up to half a sample in the length.
+    pub fn tuned(frequency: f64, dt: f64, rate: f64) -> KarplusStrong {
+        let stride = (1./(rate*dt)).round().clamp(1., u32::MAX as f64);
+        let length = (1./(frequency*dt*stride) - 0.5).round().max(2.);
+        KarplusStrong::new(length as usize, stride as u32, dt)
+    }
+    pub fn len(&self) -> usize {
+        self.line.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.line.is_empty()
+    }
+    // Frequency the loop sounds at, in cycles per unit of simulated time.
+    pub fn frequency(&self) -> f64 {
+        1./((self.line.len() as f64 + 0.5)*self.stride as f64*self.dt)
+    }
+    // Sets the gain on each trip round the loop, from 0 to 1.
+    pub fn set_feedback(&mut self, feedback: f64) {
+        self.feedback = feedback.clamp(0., 1.);
+    }
+    // The classic excitation: a burst of white noise filling the line,
+    // from -amplitude to amplitude. Its mean is taken off, since nothing in
+    // the loop would ever get rid of it.
+    pub fn pluck_noise(&mut self, amplitude: f64) {
+        let burst: Vec<f64> = (0..self.line.len()).map(|_| (self.next_noise()*2. - 1.)*amplitude).collect();
+        let mean = burst.iter().sum::<f64>()/burst.len() as f64;
+        for (s, b) in self.line.iter_mut().zip(burst) {
+            *s += b - mean;
+        }
+    }
+    // Uniform on [0, 1), from a xorshift generator.
+    fn next_noise(&mut self) -> f64 {
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 7;
+        self.noise ^= self.noise << 17;
+        (self.noise >> 11) as f64/(1u64 << 53) as f64
+    }
+}
+
+impl StringModel for KarplusStrong {
+    fn tick(&mut self) {
+        self.phase += 1;
+        if self.phase < self.stride {
+            return;
+        }
+        self.phase = 0;
+        self.previous = self.output;
+        let n = self.line.len();
+        let next = (self.head + 1) % n;
+        self.output = self.line[self.head];
+        self.line[self.head] = self.feedback*0.5*(self.output + self.line[next]);
+        self.head = next;
+    }
+    // There are no places along the line to listen at, just what comes out
+    // of it.
+    fn pickup(&self, _: f64) -> f64 {
+        self.previous + (self.output - self.previous)*self.phase as f64/self.stride as f64
+    }
+    // Adds shape along the line from the sample about to leave it.
+    fn excite(&mut self, shape: &dyn Fn(f64) -> f64) {
+        let n = self.line.len();
+        for i in 0..n {
+            self.line[(self.head + i) % n] += shape(i as f64/(n - 1) as f64);
+        }
+    }
+    fn dt(&self) -> f64 {
+        self.dt
+    }
+}
@@ -0,0 +1,81 @@
+// The simulation: strings of masses and springs and the instruments built
+// from them, and the window, sound and files that show and save them.
+extern crate num_traits;
+extern crate rayon;
+extern crate serde;
+extern crate serde_json;
+extern crate gif;
+extern crate toml_edit;
+#[cfg(feature = "window")]
+extern crate sdl2;
+#[cfg(feature = "pixels")]
+extern crate pixels;
+#[cfg(feature = "pixels")]
+extern crate winit;
+#[cfg(feature = "audio")]
+extern crate cpal;
+#[cfg(feature = "python")]
+extern crate pyo3;
+// pyo3's macros name ::core, which in this edition is looked for here.
+#[cfg(feature = "python")]
+extern crate core;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+
+pub mod app;
+#[cfg(feature = "audio")]
+mod audio;
+mod bmp;
+mod capture;
+pub mod cli;
+mod config;
+mod csv;
+mod decay;
+mod envelope;
+mod font;
+mod gamepad;
+pub mod geometry;
+pub mod golden;
+pub mod guitar;
+mod headless;
+mod history;
+mod hud;
+pub mod karplus;
+pub mod membrane;
+mod midi;
+pub mod model;
+mod npy;
+mod pair;
+mod physics;
+#[cfg(feature = "pixels")]
+mod pixels_window;
+mod pitch;
+mod pulse;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod render;
+pub mod scalar;
+pub mod screen;
+mod script;
+mod serve;
+mod setup;
+pub mod spectrum;
+mod stats;
+pub mod string;
+mod trajectory;
+pub mod view;
+mod wav;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "window")]
+mod window;
+
+pub use geometry::Vect;
+pub use guitar::Guitar;
+pub use karplus::KarplusStrong;
+pub use membrane::Membrane;
+pub use model::StringModel;
+pub use scalar::Scalar;
+pub use string::{Boundary, Chord, ChordOf, ChordStatus, Dot, DotOf, Integrator, Layout};
@@ -0,0 +1,202 @@
+// A drum head: a grid of dots joined by springs to their neighbours across
+// and down (and along the diagonals too when asked), pinned all round the
+// edge. It lies in the x-y plane, centred on the origin, and vibrates in z.
+use geometry::Vect;
+use string::{Dot, REST_FRACTION};
+
+// First zero of J0, where the lowest mode of a round drum meets its rim.
+const J0_ZERO: f64 = 2.404825557695773;
+
+#[derive(Clone)]
+pub struct Membrane {
+    // Row by row, cols dots to a row.
+    dots: Vec<Dot>,
+    initial: Vec<Dot>,
+    cols: usize,
+    rows: usize,
+    k: f64,
+    dt: f64,
+    rest_length: f64,
+    // Whether each dot also has springs to its four diagonal neighbours.
+    diagonals: bool,
+    time: f64,
+    forces: Vec<Vect>,
+}
+
+impl Membrane {
+    // Flat membrane of cols by rows dots, the outermost ring of them fixed.
+    pub fn new(cols: usize, rows: usize, k: f64) -> Membrane {
+        Membrane::from_shape(cols, rows, k, |_, _| 0.)
+    }
+    // Membrane whose dot at (x, y) starts shape(x, y) out of the plane, with
+    // x and y in dot spacings from the middle.
+    pub fn from_shape(cols: usize, rows: usize, k: f64, shape: impl Fn(f64, f64) -> f64) -> Membrane {
+        assert!(cols >= 3 && rows >= 3, "a membrane needs at least 3 by 3 dots");
+        let (cx, cy) = ((cols-1) as f64/2., (rows-1) as f64/2.);
+        let mut dots = Vec::with_capacity(cols*rows);
+        for j in 0..rows {
+            for i in 0..cols {
+                let edge = i == 0 || j == 0 || i == cols-1 || j == rows-1;
+                let (x, y) = (i as f64 - cx, j as f64 - cy);
+                let mut dot = Dot::new(x, y, edge);
+                if !edge {
+                    dot.set_pos(Vect {x, y, z: shape(x, y)});
+                }
+                dots.push(dot);
+            }
+        }
+        Membrane {
+            initial: dots.clone(),
+            dots,
+            cols,
+            rows,
+            k,
+            dt: 1.,
+            rest_length: REST_FRACTION,
+            diagonals: false,
+            time: 0.,
+            forces: Vec::new(),
+        }
+    }
+    // Drumstick hit in the middle: the membrane starts flat and the dots
+    // within radius dot spacings of the centre get a raised-cosine kick of
+    // peak velocity.
+    pub fn new_strike(cols: usize, rows: usize, k: f64, radius: f64, velocity: f64) -> Membrane {
+        let mut membrane = Membrane::new(cols, rows, k);
+        membrane.strike(radius, velocity);
+        membrane.initial = membrane.dots.clone();
+        membrane
+    }
+    // The lowest mode of a round drum, J0 on the circle inscribed in the
+    // grid and flat outside it. The grid's corners and squareness make it
+    // only roughly a mode, so it slowly picks up others.
+    pub fn new_circular(cols: usize, rows: usize, k: f64, amplitude: f64) -> Membrane {
+        let radius = (cols.min(rows)-1) as f64/2.;
+        Membrane::from_shape(cols, rows, k, |x, y| {
+            let r = (x*x + y*y).sqrt()/radius;
+            if r < 1. {amplitude*bessel_j0(J0_ZERO*r)} else {0.}
+        })
+    }
+    // Hits the membrane where it is, as new_strike does.
+    pub fn strike(&mut self, radius: f64, velocity: f64) {
+        for dot in self.dots.iter_mut() {
+            if dot.is_fixed() {continue;}
+            let (x, y) = (dot.pos().x, dot.pos().y);
+            let r = (x*x + y*y).sqrt();
+            if r < radius {
+                let kick = velocity*0.5*(1. + (std::f64::consts::PI*r/radius).cos());
+                dot.set_vel(dot.vel() + Vect {x: 0., y: 0., z: kick});
+            }
+        }
+    }
+    pub fn reset(&mut self) {
+        self.dots = self.initial.clone();
+        self.time = 0.;
+    }
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    // Every dot, row by row.
+    pub fn dots(&self) -> &[Dot] {
+        &self.dots
+    }
+    // Dot i of row j.
+    pub fn dot(&self, i: usize, j: usize) -> &Dot {
+        &self.dots[j*self.cols + i]
+    }
+    pub fn k(&self) -> f64 {
+        self.k
+    }
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+    pub fn set_dt(&mut self, dt: f64) {
+        self.dt = dt;
+    }
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    pub fn has_diagonals(&self) -> bool {
+        self.diagonals
+    }
+    // Adds or removes the springs to the four diagonal neighbours, which
+    // rest at √2 times the spacing the others do.
+    pub fn set_diagonals(&mut self, on: bool) {
+        self.diagonals = on;
+    }
+    // Kinetic energy plus the energy stored in the springs.
+    pub fn total_energy(&self) -> f64 {
+        let kinetic: f64 = self.dots.iter().map(|d| 0.5*d.mass()*d.vel().length_squared()).sum();
+        let mut potential = 0.;
+        let mut stretch = |a: &Dot, b: &Dot, rest: f64| {
+            let s = (b.pos() - a.pos()).size() - rest;
+            potential += 0.5*self.k*s*s;
+        };
+        for j in 0..self.rows {
+            for i in 0..self.cols {
+                let here = self.dot(i, j);
+                if i+1 < self.cols {stretch(here, self.dot(i+1, j), self.rest_length);}
+                if j+1 < self.rows {stretch(here, self.dot(i, j+1), self.rest_length);}
+                if self.diagonals && i+1 < self.cols && j+1 < self.rows {
+                    let rest = self.rest_length*std::f64::consts::SQRT_2;
+                    stretch(here, self.dot(i+1, j+1), rest);
+                    stretch(self.dot(i+1, j), self.dot(i, j+1), rest);
+                }
+            }
+        }
+        kinetic + potential
+    }
+    // Semi-implicit Euler, the same step Chord takes by default.
+    pub fn tick(&mut self) {
+        self.compute_forces();
+        let dt = self.dt;
+        for (dot, &force) in self.dots.iter_mut().zip(&self.forces) {
+            if dot.is_fixed() {continue;}
+            dot.set_force(force);
+            dot.accelerate(dt);
+            dot.move_it(dt);
+        }
+        self.time += dt;
+    }
+    // Spring force on every dot. Opposite neighbours are added in pairs, so
+    // a shape with the grid's mirror symmetries keeps them to the bit.
+    fn compute_forces(&mut self) {
+        let mut forces = std::mem::take(&mut self.forces);
+        forces.clear();
+        forces.resize(self.dots.len(), Vect::zero());
+        let diagonal = self.rest_length*std::f64::consts::SQRT_2;
+        for j in 1..self.rows-1 {
+            for i in 1..self.cols-1 {
+                let here = self.dot(i, j);
+                if here.is_fixed() {continue;}
+                let pull = |di: isize, dj: isize, rest: f64| {
+                    let there = self.dot((i as isize + di) as usize, (j as isize + dj) as usize);
+                    here.get_force(there.pos(), rest)
+                };
+                let mut force = (pull(-1, 0, self.rest_length) + pull(1, 0, self.rest_length))
+                    + (pull(0, -1, self.rest_length) + pull(0, 1, self.rest_length));
+                if self.diagonals {
+                    force += (pull(-1, -1, diagonal) + pull(1, 1, diagonal))
+                        + (pull(1, -1, diagonal) + pull(-1, 1, diagonal));
+                }
+                forces[j*self.cols + i] = force*self.k;
+            }
+        }
+        self.forces = forces;
+    }
+}
+
+// Bessel function J0, from its power series, which is plenty for the
+// arguments up to its first zero used here.
+fn bessel_j0(x: f64) -> f64 {
+    let q = -(x/2.)*(x/2.);
+    let (mut term, mut sum) = (1., 1.);
+    for k in 1..30 {
+        term *= q/(k*k) as f64;
+        sum += term;
+    }
+    sum
+}
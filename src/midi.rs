@@ -0,0 +1,125 @@
+// MIDI from a raw MIDI device file, such as ALSA's /dev/snd/midiC1D0, read
+// on a thread of its own so a quiet keyboard never holds anything up. Only
+// the messages the string has a use for come through. A device that's
+// unplugged ends the thread with a last message saying why, rather than
+// taking the program down with it.
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Message {
+    // Velocity from 1 to 127; a Note On of velocity 0 comes through as a
+    // Note Off, as the MIDI spec has it.
+    NoteOn {note: u8, velocity: u8},
+    NoteOff {note: u8},
+    // Bend from -1 to just under 1, centred on 0.
+    PitchBend(f64),
+}
+
+pub struct Midi {
+    messages: Receiver<Result<Message, String>>,
+}
+
+impl Midi {
+    pub fn open(path: &Path) -> io::Result<Midi> {
+        let device = File::open(path)?;
+        let (sender, messages) = mpsc::channel();
+        thread::spawn(move || read(device, &sender));
+        Ok(Midi {messages})
+    }
+    // Messages received since the last call, oldest first, ending in an
+    // error if the device has stopped.
+    pub fn messages(&self) -> Vec<Result<Message, String>> {
+        self.messages.try_iter().collect()
+    }
+}
+
+// Frequency in Hz of a MIDI note number, with A4 at 440.
+pub fn note_frequency(note: u8) -> f64 {
+    440.*2f64.powf((note as f64 - 69.)/12.)
+}
+
+fn read(mut device: File, sender: &Sender<Result<Message, String>>) {
+    let mut parser = Parser::default();
+    let mut buffer = [0; 256];
+    loop {
+        let n = match device.read(&mut buffer) {
+            Ok(0) => {
+                let _ = sender.send(Err("the device closed".to_string()));
+                return;
+            },
+            Ok(n) => n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                let _ = sender.send(Err(err.to_string()));
+                return;
+            },
+        };
+        for &byte in &buffer[..n] {
+            if let Some(message) = parser.feed(byte) {
+                if sender.send(Ok(message)).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+// Turns the byte stream into messages, keeping the running status that
+// lets a sender leave out repeated status bytes.
+#[derive(Default)]
+pub struct Parser {
+    status: Option<u8>,
+    data: Vec<u8>,
+    in_sysex: bool,
+}
+
+impl Parser {
+    pub fn feed(&mut self, byte: u8) -> Option<Message> {
+        match byte {
+            // Real-time messages can turn up between any two bytes, and
+            // leave everything else as it was.
+            0xf8..=0xff => return None,
+            0xf0 => {
+                self.in_sysex = true;
+                self.status = None;
+                return None;
+            },
+            // System common messages, the end of a system exclusive among
+            // them, cancel the running status. Their data bytes are then
+            // skipped along with the rest of the system exclusive.
+            0xf1..=0xf7 => {
+                self.in_sysex = byte != 0xf7;
+                self.status = None;
+                return None;
+            },
+            0x80..=0xef => {
+                self.in_sysex = false;
+                self.status = Some(byte);
+                self.data.clear();
+                return None;
+            },
+            _ => {},
+        }
+        let status = self.status.filter(|_| !self.in_sysex)?;
+        self.data.push(byte);
+        let length = match status >> 4 {
+            0xc | 0xd => 1,
+            _ => 2,
+        };
+        if self.data.len() < length {
+            return None;
+        }
+        let data: Vec<u8> = self.data.drain(..).collect();
+        match status >> 4 {
+            0x8 => Some(Message::NoteOff {note: data[0]}),
+            0x9 if data[1] == 0 => Some(Message::NoteOff {note: data[0]}),
+            0x9 => Some(Message::NoteOn {note: data[0], velocity: data[1]}),
+            0xe => Some(Message::PitchBend(((data[1] as i32) << 7 | data[0] as i32) as f64/8192. - 1.)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// What the window, the audio output and the recorder need from anything
+// that sounds like a string, so they can drive one without caring how it
+// works inside.
+use guitar::Guitar;
+use string::Chord;
+
+pub trait StringModel {
+    // Advances the model by one tick of dt.
+    fn tick(&mut self);
+    // Signal heard at the fraction position along the string. Models with
+    // no sense of position can ignore it.
+    fn pickup(&self, position: f64) -> f64;
+    // Plucks the model, shape(u) giving the displacement to add at the
+    // fraction u along it.
+    fn excite(&mut self, shape: &dyn Fn(f64) -> f64);
+    // Simulated time per tick.
+    fn dt(&self) -> f64;
+}
+
+impl StringModel for Chord {
+    fn tick(&mut self) {
+        Chord::tick(self);
+    }
+    fn pickup(&self, position: f64) -> f64 {
+        Chord::pickup(self, position)
+    }
+    fn excite(&mut self, shape: &dyn Fn(f64) -> f64) {
+        Chord::excite(self, shape);
+    }
+    fn dt(&self) -> f64 {
+        Chord::dt(self)
+    }
+}
+
+impl StringModel for Guitar {
+    fn tick(&mut self) {
+        Guitar::tick(self);
+    }
+    fn pickup(&self, position: f64) -> f64 {
+        Guitar::pickup(self, position)
+    }
+    // Every string at once, as a strum with no spread.
+    fn excite(&mut self, shape: &dyn Fn(f64) -> f64) {
+        for string in self.strings_mut() {
+            string.excite(shape);
+        }
+    }
+    fn dt(&self) -> f64 {
+        self.strings()[0].dt()
+    }
+}
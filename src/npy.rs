@@ -0,0 +1,32 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Writes rows of f64s, all the same length, as a 2-D little-endian .npy
+// array that numpy.load reads back exactly.
+pub fn write_npy(path: &Path, rows: &[Vec<f64>]) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    encode_npy(&mut out, rows)?;
+    out.flush()
+}
+
+pub fn encode_npy<W: Write>(out: &mut W, rows: &[Vec<f64>]) -> io::Result<()> {
+    let columns = rows.first().map_or(0, |r| r.len());
+    if rows.iter().any(|r| r.len() != columns) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "rows of different lengths"));
+    }
+    let mut header = format!("{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}", rows.len(), columns);
+    // The magic, version and length take 10 bytes, and the data has to
+    // start on a multiple of 64 after the header's closing newline.
+    while !(10 + header.len() + 1).is_multiple_of(64) {
+        header.push(' ');
+    }
+    header.push('\n');
+    out.write_all(b"\x93NUMPY\x01\x00")?;
+    out.write_all(&(header.len() as u16).to_le_bytes())?;
+    out.write_all(header.as_bytes())?;
+    for &v in rows.iter().flatten() {
+        out.write_all(&v.to_le_bytes())?;
+    }
+    Ok(())
+}
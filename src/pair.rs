@@ -0,0 +1,70 @@
+// Two strings run side by side from the same start, differing only in what
+// the two sides of --compare set, so whatever the difference does to the
+// motion shows. Everything done to one string is done to the other, and
+// each side's own settings are held on its string through it all, so a
+// preset or Q can't quietly make the two the same.
+use std::collections::VecDeque;
+
+use Chord;
+use cli::Side;
+
+// Frames of the difference between the strings kept for the strip.
+pub const HISTORY: usize = 96;
+
+pub struct Pair {
+    // The second string; the first is the one the window runs as usual.
+    pub other: Chord,
+    sides: (Side, Side),
+    // How far apart the strings are at each dot, a frame at a time, newest
+    // last.
+    history: VecDeque<Vec<f64>>,
+}
+
+impl Pair {
+    pub fn new(other: Chord, sides: (Side, Side)) -> Pair {
+        Pair {other, sides, history: VecDeque::new()}
+    }
+    // What each string is labelled with.
+    pub fn names(&self) -> (&str, &str) {
+        (&self.sides.0.name, &self.sides.1.name)
+    }
+    // Puts back whatever each side sets on its string that something done
+    // to both has changed.
+    pub fn hold(&mut self, chord: &mut Chord) {
+        hold(&self.sides.0, chord);
+        hold(&self.sides.1, &mut self.other);
+    }
+    // Notes how far apart the dots of chord and the other string are now.
+    pub fn record(&mut self, chord: &Chord) {
+        let apart = chord.dots().iter().zip(self.other.dots())
+            .map(|(a, b)| (a.pos() - b.pos()).size())
+            .collect();
+        self.history.push_back(apart);
+        while self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+    }
+    pub fn history(&self) -> &VecDeque<Vec<f64>> {
+        &self.history
+    }
+}
+
+// chord and the other string of pair, if there is a pair, for doing the
+// same thing to both.
+pub fn both<'a>(chord: &'a mut Chord, pair: &'a mut Option<Pair>) -> impl Iterator<Item = &'a mut Chord> + 'a {
+    std::iter::once(chord).chain(pair.as_mut().map(|p| &mut p.other))
+}
+
+// Settings are only touched when they're off, so a glide already heading
+// for the side's k isn't started over every frame.
+fn hold(side: &Side, chord: &mut Chord) {
+    if let Some(integrator) = side.integrator.filter(|&i| i != chord.integrator()) {
+        chord.set_integrator(integrator);
+    }
+    if let Some(k) = side.k.filter(|&k| k != chord.target_k()) {
+        chord.set_target_k(k);
+    }
+    if let Some(damping) = side.damping.filter(|&d| d != chord.target_damping()) {
+        chord.set_damping(damping);
+    }
+}
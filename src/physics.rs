@@ -0,0 +1,159 @@
+// Runs the string on a thread of its own, so drawing a big string and
+// ticking it don't hold each other up. The thread ticks in small batches at
+// a fixed rate and every so often sends the window a snapshot of the
+// string. Snapshots the window hasn't got round to are dropped rather than
+// queued, but the pickup signal is never lost: samples ride along with the
+// next snapshot that gets through.
+use std::mem;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use Chord;
+use guitar::pluck_shape_at;
+
+// Time between batches of ticks, the most ticks a batch may run before the
+// thread gives up catching up, and snapshots that can wait for the window.
+const BATCH_PERIOD: Duration = Duration::from_millis(1);
+const MAX_BATCH: u32 = 200;
+const QUEUE: usize = 2;
+
+pub enum Command {
+    Pause(bool),
+    // One tick while paused.
+    Step,
+    // Multiple of the normal rate to run at.
+    Speed(f64),
+    // Plucks the string with pluck_shape_at the fraction given second,
+    // scaled by the amount given first.
+    Pluck(f64, f64),
+    SetK(f64),
+    Quit,
+}
+
+pub struct Snapshot {
+    pub chord: Arc<Chord>,
+    // Pickup signal of every tick since the last snapshot that got through,
+    // and the wall-clock time those ticks took.
+    pub samples: Vec<f64>,
+    // The same ticks heard at the second pickup, when there is one.
+    pub second: Vec<f64>,
+    pub ticking: Duration,
+}
+
+pub struct Physics {
+    commands: Sender<Command>,
+    snapshots: Receiver<Snapshot>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Physics {
+    // Starts ticking chord at rate units of simulated time per second,
+    // listening at the fractions pickups along it, with snapshot_rate
+    // snapshots a second.
+    pub fn start(chord: Arc<Mutex<Chord>>, rate: f64, snapshot_rate: f64, pickups: (f64, Option<f64>)) -> Physics {
+        let (commands, received) = mpsc::channel();
+        let (sender, snapshots) = mpsc::sync_channel(QUEUE);
+        let interval = Duration::from_secs_f64(1./snapshot_rate);
+        let thread = thread::spawn(move || {
+            run(&chord, &received, &sender, rate, interval, pickups);
+        });
+        Physics {commands, snapshots, thread: Some(thread)}
+    }
+    pub fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+    // Snapshots sent since the last call, oldest first.
+    pub fn snapshots(&self) -> Vec<Snapshot> {
+        self.snapshots.try_iter().collect()
+    }
+    // Stops the thread and waits for it to finish its batch.
+    pub fn stop(mut self) {
+        self.shut_down();
+    }
+    fn shut_down(&mut self) {
+        self.send(Command::Quit);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Physics {
+    fn drop(&mut self) {
+        self.shut_down();
+    }
+}
+
+fn run(chord: &Mutex<Chord>, commands: &Receiver<Command>, snapshots: &SyncSender<Snapshot>,
+       rate: f64, interval: Duration, (pickup, second_pickup): (f64, Option<f64>)) {
+    let mut paused = false;
+    let mut step = false;
+    let mut speed = 1.;
+    let mut lag = 0.;
+    let mut last = Instant::now();
+    let mut sent = last;
+    let mut samples = Vec::new();
+    let mut second = Vec::new();
+    let mut ticking = Duration::ZERO;
+    loop {
+        loop {
+            match commands.try_recv() {
+                Ok(Command::Pause(p)) => {
+                    paused = p;
+                    lag = 0.;
+                },
+                Ok(Command::Step) => step = true,
+                Ok(Command::Speed(s)) => speed = s,
+                Ok(Command::Pluck(scale, position)) => {
+                    chord.lock().unwrap().excite(|u| pluck_shape_at(u, position)*scale)
+                },
+                Ok(Command::SetK(k)) => chord.lock().unwrap().set_target_k(k),
+                Ok(Command::Quit) | Err(TryRecvError::Disconnected) => return,
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+        let now = Instant::now();
+        if !paused {
+            lag += (now - last).as_secs_f64()*rate*speed;
+        }
+        last = now;
+        {
+            let mut chord = chord.lock().unwrap();
+            if step {
+                lag = chord.dt();
+                step = false;
+            }
+            let mut steps = 0;
+            let batch = Instant::now();
+            while lag >= chord.dt() && steps < MAX_BATCH {
+                chord.tick();
+                samples.push(chord.pickup(pickup));
+                if let Some(u) = second_pickup {
+                    second.push(chord.pickup(u));
+                }
+                lag -= chord.dt();
+                steps += 1;
+            }
+            ticking += batch.elapsed();
+            if steps == MAX_BATCH {
+                lag = 0.;
+            }
+            if now - sent >= interval {
+                let snapshot = Snapshot {chord: Arc::new(chord.clone()), samples: mem::take(&mut samples), second: mem::take(&mut second),
+                                         ticking: mem::take(&mut ticking)};
+                match snapshots.try_send(snapshot) {
+                    Ok(()) => sent = now,
+                    Err(TrySendError::Full(snapshot)) => {
+                        samples = snapshot.samples;
+                        second = snapshot.second;
+                        ticking = snapshot.ticking;
+                    },
+                    Err(TrySendError::Disconnected(_)) => return,
+                }
+            }
+        }
+        thread::sleep(BATCH_PERIOD);
+    }
+}
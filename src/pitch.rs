@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+
+// A crossing only counts once the signal has swung past this fraction of its
+// recent peak on both sides, so noise around zero can't fake one.
+const HYSTERESIS: f64 = 0.2;
+// Per-sample decay of the tracked peak and of the running mean used to
+// take out any offset.
+const PEAK_DECAY: f64 = 0.9999;
+const MEAN_DECAY: f64 = 0.9999;
+// Peak below which the string counts as silent.
+const SILENCE: f64 = 1e-3;
+
+// Estimates the fundamental of a signal from the spacing of its upward zero
+// crossings over the last window samples.
+pub struct PitchTracker {
+    window: u64,
+    count: u64,
+    mean: f64,
+    peak: f64,
+    below: bool,
+    previous: f64,
+    crossings: VecDeque<f64>,
+}
+
+impl PitchTracker {
+    pub fn new(window: u64) -> PitchTracker {
+        PitchTracker {
+            window,
+            count: 0,
+            mean: 0.,
+            peak: 0.,
+            below: false,
+            previous: 0.,
+            crossings: VecDeque::new(),
+        }
+    }
+    pub fn feed(&mut self, sample: f64) {
+        self.mean = self.mean*MEAN_DECAY + sample*(1. - MEAN_DECAY);
+        let s = sample - self.mean;
+        self.peak = (self.peak*PEAK_DECAY).max(s.abs());
+        let h = self.peak*HYSTERESIS;
+        if s < -h {
+            self.below = true;
+        } else if self.below && s > h {
+            self.below = false;
+            // Place the crossing between this sample and the one before it.
+            let t = if s != self.previous {s/(s - self.previous)} else {0.};
+            self.crossings.push_back(self.count as f64 - t);
+        }
+        self.previous = s;
+        self.count += 1;
+        let oldest = self.count as f64 - self.window as f64;
+        while self.crossings.front().is_some_and(|&c| c < oldest) {
+            self.crossings.pop_front();
+        }
+    }
+    // Frequency in cycles per second for a signal sampled at sample_rate,
+    // or None while the signal is silent or hasn't crossed often enough.
+    pub fn frequency(&self, sample_rate: f64) -> Option<f64> {
+        if self.peak < SILENCE || self.crossings.len() < 2 {
+            return None;
+        }
+        let first = self.crossings[0];
+        let last = self.crossings[self.crossings.len()-1];
+        let period = (last - first)/(self.crossings.len()-1) as f64;
+        Some(sample_rate/period)
+    }
+}
+
+const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+// Nearest equal-tempered note to a frequency, with A4 = 440 Hz, e.g. "A2".
+pub fn note_name(frequency: f64) -> String {
+    let midi = (12.*(frequency/440.).log2()).round() as i64 + 69;
+    format!("{}{}", NAMES[midi.rem_euclid(12) as usize], midi.div_euclid(12) - 1)
+}
@@ -0,0 +1,249 @@
+// A window opened with winit and drawn through pixels, as a Renderer: the
+// frame is drawn into a Framebuffer and copied to the GPU to be shown.
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use pixels::{Pixels, SurfaceTexture};
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, Event, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event_loop::EventLoop;
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+use winit::window::{Fullscreen, Window, WindowBuilder};
+
+use render::{Framebuffer, InputState, Key, PixelFormat, Plot, Renderer};
+
+#[derive(Debug)]
+pub enum PixelsError {
+    Init {reason: String},
+    Window {reason: String},
+    Surface {reason: String},
+}
+
+impl fmt::Display for PixelsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PixelsError::Init {ref reason} => write!(f, "couldn't start winit: {}", reason),
+            PixelsError::Window {ref reason} => write!(f, "couldn't open the window: {}", reason),
+            PixelsError::Surface {ref reason} => write!(f, "couldn't draw in the window: {}", reason),
+        }
+    }
+}
+
+impl Error for PixelsError {}
+
+pub struct PixelsWindow {
+    frame: Framebuffer,
+    pixels: Pixels<'static>,
+    window: Arc<Window>,
+    events: EventLoop<()>,
+    // Where the pointer last was; winit only says when it moves.
+    mouse: (u16, u16),
+}
+
+impl PixelsWindow {
+    pub fn open(w: isize, h: isize, title: &str) -> Result<PixelsWindow, PixelsError> {
+        let events = EventLoop::new().map_err(|err| PixelsError::Init {reason: err.to_string()})?;
+        let window = WindowBuilder::new()
+            .with_title(title)
+            .with_inner_size(PhysicalSize::new(w as u32, h as u32))
+            .build(&events)
+            .map_err(|err| PixelsError::Window {reason: err.to_string()})?;
+        let window = Arc::new(window);
+        let size = window.inner_size();
+        let (w, h) = (size.width.max(1), size.height.max(1));
+        let pixels = Pixels::new(w, h, SurfaceTexture::new(w, h, window.clone()))
+            .map_err(|err| PixelsError::Surface {reason: err.to_string()})?;
+        Ok(PixelsWindow {frame: Framebuffer::new(w as usize, h as usize), pixels, window, events, mouse: (0, 0)})
+    }
+}
+
+impl Renderer for PixelsWindow {
+    fn size(&self) -> (isize, isize) {
+        self.frame.size()
+    }
+    fn format(&self) -> PixelFormat {
+        self.frame.format()
+    }
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: (u8, u8, u8)) {
+        self.frame.fill_rect(x, y, w, h, rgb);
+    }
+    fn draw_point(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        self.frame.draw_point(x, y, rgb);
+    }
+    fn blend(&mut self, plots: &[Plot]) {
+        self.frame.blend(plots);
+    }
+    fn with_pixels(&mut self, f: &mut dyn FnMut(&mut [u8], usize)) -> Result<(), String> {
+        self.frame.with_pixels(f)
+    }
+    fn present(&mut self) {
+        let format = self.frame.format();
+        for (rgba, &p) in self.pixels.frame_mut().chunks_exact_mut(4).zip(self.frame.pixels()) {
+            let (r, g, b) = format.unmap(p.to_ne_bytes());
+            rgba.copy_from_slice(&[r, g, b, 0xff]);
+        }
+        // A frame that can't be shown goes without.
+        let _ = self.pixels.render();
+    }
+    fn poll_input(&mut self) -> InputState {
+        let mut input = InputState::default();
+        let mouse = &mut self.mouse;
+        let status = self.events.pump_events(Some(Duration::ZERO), |event, _| {
+            let event = match event {
+                Event::WindowEvent {event, ..} => event,
+                _ => return,
+            };
+            match event {
+                WindowEvent::CloseRequested => input.quit = true,
+                // A held key repeats; only its first press counts.
+                WindowEvent::KeyboardInput {event: KeyEvent {physical_key: PhysicalKey::Code(code), state, repeat: false, ..}, ..} => {
+                    if let Some(k) = key(code) {
+                        match state {
+                            ElementState::Pressed => input.pressed.push(k),
+                            ElementState::Released => input.released.push(k),
+                        }
+                    }
+                },
+                WindowEvent::Resized(size) => input.resized = Some((size.width as isize, size.height as isize)),
+                WindowEvent::CursorMoved {position, ..} => {
+                    *mouse = (position.x.max(0.).min(u16::MAX as f64) as u16, position.y.max(0.).min(u16::MAX as f64) as u16);
+                    input.mouse = Some(*mouse);
+                },
+                WindowEvent::MouseInput {state, button, ..} => {
+                    input.mouse = Some(*mouse);
+                    let down = state == ElementState::Pressed;
+                    match button {
+                        MouseButton::Left => if down {input.clicked = true;} else {input.unclicked = true;},
+                        MouseButton::Middle => if down {input.middle_clicked = true;} else {input.middle_unclicked = true;},
+                        _ => {}
+                    }
+                },
+                // Touchpads scroll by the pixel; a notch is taken as a line
+                // of about 20 of them.
+                WindowEvent::MouseWheel {delta, ..} => {
+                    input.mouse = Some(*mouse);
+                    input.wheel += match delta {
+                        MouseScrollDelta::LineDelta(_, y) => y.round() as i32,
+                        MouseScrollDelta::PixelDelta(p) => (p.y/20.).round() as i32,
+                    };
+                },
+                _ => {}
+            }
+        });
+        if let PumpStatus::Exit(_) = status {
+            input.quit = true;
+        }
+        input
+    }
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+    // The window only asks for the new size; winit says later what it got,
+    // and the frame follows that as a resize.
+    fn set_mode(&mut self, w: isize, h: isize, fullscreen: bool) -> Result<(), String> {
+        if fullscreen != self.window.fullscreen().is_some() {
+            self.window.set_fullscreen(if fullscreen {Some(Fullscreen::Borderless(None))} else {None});
+        }
+        if !fullscreen {
+            let _ = self.window.request_inner_size(PhysicalSize::new(w as u32, h as u32));
+        }
+        let (width, height) = (w.max(1) as u32, h.max(1) as u32);
+        self.pixels.resize_surface(width, height).map_err(|err| err.to_string())?;
+        self.pixels.resize_buffer(width, height).map_err(|err| err.to_string())?;
+        self.frame = Framebuffer::new(width as usize, height as usize);
+        Ok(())
+    }
+}
+
+fn key(code: KeyCode) -> Option<Key> {
+    Some(match code {
+        KeyCode::KeyA => Key::A,
+        KeyCode::KeyB => Key::B,
+        KeyCode::KeyC => Key::C,
+        KeyCode::KeyD => Key::D,
+        KeyCode::KeyE => Key::E,
+        KeyCode::KeyF => Key::F,
+        KeyCode::KeyG => Key::G,
+        KeyCode::KeyH => Key::H,
+        KeyCode::KeyI => Key::I,
+        KeyCode::KeyJ => Key::J,
+        KeyCode::KeyK => Key::K,
+        KeyCode::KeyL => Key::L,
+        KeyCode::KeyM => Key::M,
+        KeyCode::KeyN => Key::N,
+        KeyCode::KeyO => Key::O,
+        KeyCode::KeyP => Key::P,
+        KeyCode::KeyQ => Key::Q,
+        KeyCode::KeyR => Key::R,
+        KeyCode::KeyS => Key::S,
+        KeyCode::KeyT => Key::T,
+        KeyCode::KeyU => Key::U,
+        KeyCode::KeyV => Key::V,
+        KeyCode::KeyW => Key::W,
+        KeyCode::KeyX => Key::X,
+        KeyCode::KeyY => Key::Y,
+        KeyCode::KeyZ => Key::Z,
+        KeyCode::Digit0 => Key::Num0,
+        KeyCode::Digit1 => Key::Num1,
+        KeyCode::Digit2 => Key::Num2,
+        KeyCode::Digit3 => Key::Num3,
+        KeyCode::Digit4 => Key::Num4,
+        KeyCode::Digit5 => Key::Num5,
+        KeyCode::Digit6 => Key::Num6,
+        KeyCode::Digit7 => Key::Num7,
+        KeyCode::Digit8 => Key::Num8,
+        KeyCode::Digit9 => Key::Num9,
+        KeyCode::Numpad0 => Key::Kp0,
+        KeyCode::Numpad1 => Key::Kp1,
+        KeyCode::Numpad2 => Key::Kp2,
+        KeyCode::Numpad3 => Key::Kp3,
+        KeyCode::Numpad4 => Key::Kp4,
+        KeyCode::Numpad5 => Key::Kp5,
+        KeyCode::Numpad6 => Key::Kp6,
+        KeyCode::Numpad7 => Key::Kp7,
+        KeyCode::Numpad8 => Key::Kp8,
+        KeyCode::Numpad9 => Key::Kp9,
+        KeyCode::NumpadAdd => Key::KpPlus,
+        KeyCode::NumpadSubtract => Key::KpMinus,
+        KeyCode::F1 => Key::F1,
+        KeyCode::F2 => Key::F2,
+        KeyCode::F3 => Key::F3,
+        KeyCode::F4 => Key::F4,
+        KeyCode::F5 => Key::F5,
+        KeyCode::F6 => Key::F6,
+        KeyCode::F7 => Key::F7,
+        KeyCode::F8 => Key::F8,
+        KeyCode::F9 => Key::F9,
+        KeyCode::F10 => Key::F10,
+        KeyCode::F11 => Key::F11,
+        KeyCode::F12 => Key::F12,
+        KeyCode::ArrowUp => Key::Up,
+        KeyCode::ArrowDown => Key::Down,
+        KeyCode::ArrowLeft => Key::Left,
+        KeyCode::ArrowRight => Key::Right,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Space => Key::Space,
+        KeyCode::Enter => Key::Return,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Escape => Key::Escape,
+        KeyCode::Comma => Key::Comma,
+        KeyCode::Period => Key::Period,
+        KeyCode::Slash => Key::Slash,
+        KeyCode::Minus => Key::Minus,
+        KeyCode::Equal => Key::Equals,
+        KeyCode::BracketLeft => Key::LeftBracket,
+        KeyCode::BracketRight => Key::RightBracket,
+        KeyCode::Semicolon => Key::Semicolon,
+        KeyCode::Quote => Key::Quote,
+        KeyCode::Backslash => Key::Backslash,
+        KeyCode::ControlLeft => Key::LCtrl,
+        KeyCode::ControlRight => Key::RCtrl,
+        _ => return None,
+    })
+}
@@ -0,0 +1,94 @@
+// Follows a travelling pulse down a string and back, timing it across the
+// middle of the string each way to measure how fast waves go, and noting
+// which way up it comes back from the far end. The middle keeps the timing
+// clear of the ends, where the pulse is half reflected and its peak is hard
+// to place.
+use Chord;
+
+// Stretch of the string, as fractions of its length, the pulse is timed
+// over, and how near the far end it must get before it counts as reflected.
+const TIMED_FROM: f64 = 0.3;
+const TIMED_TO: f64 = 0.7;
+const TURN_AT: f64 = 0.9;
+
+// The pulse's speed along the string over one leg, negative coming back,
+// and its height at the peak, negative when upside down.
+pub struct Leg {
+    pub speed: f64,
+    pub height: f64,
+}
+
+pub struct Report {
+    pub out: Leg,
+    pub back: Leg,
+}
+
+// Where along the string the largest displacement is, found to within a
+// fraction of a dot by fitting a parabola through it and its neighbours,
+// and the displacement there.
+pub fn peak(chord: &Chord) -> (f64, f64) {
+    let dots = chord.dots();
+    let (i, height) = dots.iter().map(|d| d.pos().y).enumerate()
+        .fold((0, 0f64), |best, (i, y)| if y.abs() > best.1.abs() {(i, y)} else {best});
+    let x = dots[i].pos().x;
+    if i == 0 || i == dots.len()-1 {
+        return (x, height);
+    }
+    let (a, b, c) = (dots[i-1].pos().y.abs(), height.abs(), dots[i+1].pos().y.abs());
+    let curve = a - 2.*b + c;
+    let shift = if curve < 0. {0.5*(a - c)/curve} else {0.};
+    (x + shift*chord.spacing(), height)
+}
+
+// Ticks chord until a pulse heading for the far end has got there and back
+// across the middle. None if it doesn't within twice the time the wave
+// speed says that takes.
+pub fn follow(chord: &mut Chord) -> Option<Report> {
+    let dots = chord.dots();
+    let (start, length) = (dots[0].pos().x, dots[dots.len()-1].pos().x - dots[0].pos().x);
+    let limit = 4.*length/(chord.wave_speed()*chord.dt());
+    let (mut out, mut back) = (Vec::new(), Vec::new());
+    let (mut out_height, mut back_height) = (0f64, 0f64);
+    let mut turned = false;
+    for _ in 0..limit as u64 {
+        chord.tick();
+        let (x, height) = peak(chord);
+        let u = (x - start)/length;
+        if u >= TURN_AT {
+            turned = true;
+        }
+        let timed = (TIMED_FROM..=TIMED_TO).contains(&u);
+        if timed && !turned {
+            out.push((chord.time(), x));
+            if height.abs() > out_height.abs() {
+                out_height = height;
+            }
+        } else if timed {
+            back.push((chord.time(), x));
+            if height.abs() > back_height.abs() {
+                back_height = height;
+            }
+        } else if turned && u < TIMED_FROM {
+            return Some(Report {
+                out: Leg {speed: slope(&out)?, height: out_height},
+                back: Leg {speed: slope(&back)?, height: back_height},
+            });
+        }
+    }
+    None
+}
+
+// Least-squares slope of the points, None for fewer than two.
+fn slope(points: &[(f64, f64)]) -> Option<f64> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let (mt, mx) = points.iter().fold((0., 0.), |(t, x), p| (t + p.0/n, x + p.1/n));
+    let (mut tx, mut tt) = (0., 0.);
+    for &(t, x) in points {
+        tx += (t - mt)*(x - mx);
+        tt += (t - mt)*(t - mt);
+    }
+    Some(tx/tt)
+}
@@ -0,0 +1,165 @@
+// The string as the Python module guitar_string, built with the python
+// feature by maturin from python/pyproject.toml. Its Chord owns a Chord and
+// hands its positions and velocities out as array.array('d'), written into
+// through the buffer protocol.
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use {Chord, Dot};
+
+fn inside(u: f64) -> bool {
+    u > 0. && u < 1.
+}
+
+// Checked the same way the command line checks its options, for anything
+// the string's own constructors would refuse.
+fn checked(n: u16, k: f64, fits: bool, chord: impl FnOnce() -> Chord) -> PyResult<PyChord> {
+    if n < 2 || !(k > 0. && k.is_finite()) || !fits {
+        return Err(PyValueError::new_err(format!("can't make a string of {} segments with k={} from those arguments", n, k)));
+    }
+    Ok(PyChord {chord: chord()})
+}
+
+/// A string of n segments with spring constant k, fixed at both ends.
+///
+/// Positions and velocities come back as array.array('d'), which supports
+/// the buffer protocol, so numpy.asarray and matplotlib take them without
+/// a copy.
+#[pyclass(name = "Chord", module = "guitar_string")]
+pub struct PyChord {
+    chord: Chord,
+}
+
+#[pymethods]
+impl PyChord {
+    #[new]
+    fn new(n: u16, k: f64) -> PyResult<PyChord> {
+        checked(n, k, true, || Chord::new(n, k))
+    }
+
+    /// The triangle the window starts with.
+    #[staticmethod]
+    fn triangle(n: u16, k: f64) -> PyResult<PyChord> {
+        PyChord::new(n, k)
+    }
+
+    /// The string's first mode.
+    #[staticmethod]
+    fn sine(n: u16, k: f64) -> PyResult<PyChord> {
+        checked(n, k, true, || Chord::new_sine(n, k))
+    }
+
+    /// Held at amplitude at the fraction position along and let go.
+    #[staticmethod]
+    fn pluck(n: u16, k: f64, position: f64, amplitude: f64) -> PyResult<PyChord> {
+        checked(n, k, inside(position) && amplitude.is_finite(), || Chord::new_pluck(n, k, position, amplitude))
+    }
+
+    /// Flat, with a bump of velocity speed and fractional width at position.
+    #[staticmethod]
+    fn strike(n: u16, k: f64, position: f64, width: f64, speed: f64) -> PyResult<PyChord> {
+        let fits = inside(position) && width > 0. && width.is_finite() && speed.is_finite();
+        checked(n, k, fits, || Chord::new_strike(n, k, position, width, speed))
+    }
+
+    fn __len__(&self) -> usize {
+        self.chord.dots().len()
+    }
+
+    fn tick(&mut self) {
+        self.chord.tick();
+    }
+
+    // The GIL is let go of meanwhile, as a long run takes a while.
+    fn run(&mut self, py: Python, ticks: u64) {
+        let chord = &mut self.chord;
+        py.allow_threads(|| chord.run(ticks));
+    }
+
+    /// Back to how the string started.
+    fn reset(&mut self) {
+        self.chord.reset();
+    }
+
+    /// Adds a triangle of height amplitude peaking at the fraction position.
+    fn excite(&mut self, position: f64, amplitude: f64) -> PyResult<()> {
+        if !inside(position) || !amplitude.is_finite() {
+            return Err(PyValueError::new_err(format!("can't pluck at {} by {}", position, amplitude)));
+        }
+        self.chord.excite(|u| if u <= position {u/position} else {(1. - u)/(1. - position)}*amplitude);
+        Ok(())
+    }
+
+    fn set_damping(&mut self, damping: f64) -> PyResult<()> {
+        if !(damping >= 0. && damping.is_finite()) {
+            return Err(PyValueError::new_err(format!("can't damp the string by {}", damping)));
+        }
+        self.chord.set_damping(damping);
+        Ok(())
+    }
+
+    #[getter]
+    fn time(&self) -> f64 {
+        self.chord.time()
+    }
+
+    /// The displacement heard at the fraction position along the string.
+    fn pickup(&self, position: f64) -> f64 {
+        self.chord.pickup(position.clamp(0., 1.))
+    }
+
+    fn kinetic_energy(&self) -> f64 {
+        self.chord.kinetic_energy()
+    }
+
+    fn potential_energy(&self) -> f64 {
+        self.chord.potential_energy()
+    }
+
+    fn total_energy(&self) -> f64 {
+        self.chord.total_energy()
+    }
+
+    fn positions_x<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.pos().x)
+    }
+
+    fn positions_y<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.pos().y)
+    }
+
+    fn positions_z<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.pos().z)
+    }
+
+    fn velocities_x<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.vel().x)
+    }
+
+    fn velocities_y<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.vel().y)
+    }
+
+    fn velocities_z<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.copy(py, |d| d.vel().z)
+    }
+}
+
+impl PyChord {
+    // A zeroed array.array('d') of one double a dot, filled in through its
+    // buffer rather than one float object at a time.
+    fn copy<'py>(&self, py: Python<'py>, each: impl Fn(&Dot) -> f64) -> PyResult<Bound<'py, PyAny>> {
+        let values: Vec<f64> = self.chord.dots().iter().map(each).collect();
+        let zeros = PyBytes::new(py, &vec![0; values.len()*std::mem::size_of::<f64>()]);
+        let out = py.import("array")?.getattr("array")?.call1(("d", zeros))?;
+        PyBuffer::<f64>::get(&out)?.copy_from_slice(py, &values)?;
+        Ok(out)
+    }
+}
+
+#[pymodule]
+fn guitar_string(module: &Bound<PyModule>) -> PyResult<()> {
+    module.add_class::<PyChord>()
+}
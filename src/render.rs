@@ -0,0 +1,206 @@
+// What Screen draws with. A renderer owns the pixels, shows them, and
+// reports input; everything about where strings go and what colour they are
+// stays in Screen, so the simulation never knows which backend is behind it.
+
+// The keys the window responds to, or might, whatever library reads them.
+// A backend passes on the ones it knows and drops the rest.
+#[cfg_attr(not(any(feature = "window", feature = "pixels")), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+    Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpPlus, KpMinus,
+    F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Up, Down, Left, Right, Home, End, PageUp, PageDown,
+    Space, Return, Tab, Escape,
+    Comma, Period, Slash, Minus, Equals, LeftBracket, RightBracket, Semicolon, Quote, Backslash,
+    LCtrl, RCtrl,
+}
+
+// Where each 8 bit channel sits in a 32 bit pixel.
+#[derive(Clone, Copy)]
+pub struct PixelFormat {
+    pub r_shift: u8,
+    pub g_shift: u8,
+    pub b_shift: u8,
+}
+
+impl PixelFormat {
+    // Bytes of a pixel of the given colour, in memory order.
+    pub fn map(&self, (r,g,b): (u8, u8, u8)) -> [u8; 4] {
+        let p = ((r as u32) << self.r_shift) | ((g as u32) << self.g_shift) | ((b as u32) << self.b_shift);
+        p.to_ne_bytes()
+    }
+    pub fn unmap(&self, bytes: [u8; 4]) -> (u8, u8, u8) {
+        let p = u32::from_ne_bytes(bytes);
+        ((p >> self.r_shift) as u8, (p >> self.g_shift) as u8, (p >> self.b_shift) as u8)
+    }
+}
+
+// A pixel to mix a colour into, and how much of the pixel it covers.
+#[derive(Clone, Copy)]
+pub struct Plot {
+    pub x: i32,
+    pub y: i32,
+    pub coverage: f32,
+    pub rgb: (u8, u8, u8),
+}
+
+// The colour coverage of the way from under to over.
+pub fn mix(under: (u8, u8, u8), over: (u8, u8, u8), coverage: f32) -> (u8, u8, u8) {
+    let a = coverage.clamp(0., 1.);
+    let channel = |u: u8, o: u8| (u as f32 + (o as f32 - u as f32)*a).round() as u8;
+    (channel(under.0, over.0), channel(under.1, over.1), channel(under.2, over.2))
+}
+
+// Everything that happened since the last poll.
+#[derive(Default)]
+pub struct InputState {
+    pub quit: bool,
+    pub pressed: Vec<Key>,
+    pub released: Vec<Key>,
+    // Latest pointer position, if it moved, and whether the left button
+    // went down or up.
+    pub mouse: Option<(u16, u16)>,
+    pub clicked: bool,
+    pub unclicked: bool,
+    // The same for the middle button, and notches the wheel turned, away
+    // from the user counting up.
+    pub middle_clicked: bool,
+    pub middle_unclicked: bool,
+    pub wheel: i32,
+    // Size the window was dragged to, if it was.
+    pub resized: Option<(isize, isize)>,
+}
+
+// Drawing calls take coordinates already clipped to the window.
+pub trait Renderer {
+    fn size(&self) -> (isize, isize);
+    fn format(&self) -> PixelFormat;
+    // Starts a frame by filling the window with the background.
+    fn begin_frame(&mut self, background: (u8, u8, u8)) {
+        let (w, h) = self.size();
+        self.fill_rect(0, 0, w as usize, h as usize, background);
+    }
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: (u8, u8, u8));
+    fn draw_point(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        self.fill_rect(x, y, 1, 1, rgb);
+    }
+    // Bresenham line between two pixels, ends included.
+    fn draw_line(&mut self, from: (usize, usize), to: (usize, usize), rgb: (u8, u8, u8)) {
+        let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+        let (x1, y1) = (to.0 as i64, to.1 as i64);
+        let (dx, dy) = ((x1 - x).abs(), -(y1 - y).abs());
+        let (sx, sy) = (if x < x1 {1} else {-1}, if y < y1 {1} else {-1});
+        let mut err = dx + dy;
+        loop {
+            self.draw_point(x as usize, y as usize, rgb);
+            if x == x1 && y == y1 {break;}
+            let e2 = 2*err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+    // Mixes each plot's colour into its pixel by its coverage, over
+    // whatever colour is there already, in order. Pixels outside the window
+    // are left out, so plots needn't be clipped.
+    fn blend(&mut self, plots: &[Plot]) {
+        let format = self.format();
+        let (w, h) = self.size();
+        // A frame whose pixels can't be reached goes without.
+        let _ = self.with_pixels(&mut |pixels, pitch| {
+            for p in plots {
+                if p.x < 0 || p.y < 0 || p.x as isize >= w || p.y as isize >= h {
+                    continue;
+                }
+                let i = p.y as usize*pitch + p.x as usize*4;
+                let under = format.unmap([pixels[i], pixels[i+1], pixels[i+2], pixels[i+3]]);
+                pixels[i..i+4].copy_from_slice(&format.map(mix(under, p.rgb, p.coverage)));
+            }
+        });
+    }
+    // Hands f the raw 32 bit pixels of the frame being drawn, with the
+    // number of bytes from one row to the next.
+    fn with_pixels(&mut self, f: &mut dyn FnMut(&mut [u8], usize)) -> Result<(), String>;
+    fn present(&mut self);
+    fn poll_input(&mut self) -> InputState;
+    fn set_title(&mut self, title: &str);
+    fn set_mode(&mut self, w: isize, h: isize, fullscreen: bool) -> Result<(), String>;
+}
+
+// Pixels in memory with no window, in the 0RGB layout of most pure Rust
+// window libraries. Nothing ever presses a key on it.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<u32>,
+}
+
+#[allow(dead_code)]
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        let (width, height) = (width.max(1), height.max(1));
+        Framebuffer {width, height, pixels: vec![0; width*height]}
+    }
+    pub fn pixels(&self) -> &[u32] {
+        &self.pixels
+    }
+}
+
+const FRAMEBUFFER_FORMAT: PixelFormat = PixelFormat {r_shift: 16, g_shift: 8, b_shift: 0};
+
+impl Renderer for Framebuffer {
+    fn size(&self) -> (isize, isize) {
+        (self.width as isize, self.height as isize)
+    }
+    fn format(&self) -> PixelFormat {
+        FRAMEBUFFER_FORMAT
+    }
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: (u8, u8, u8)) {
+        let p = u32::from_ne_bytes(FRAMEBUFFER_FORMAT.map(rgb));
+        for row in self.pixels.chunks_mut(self.width).skip(y).take(h) {
+            for q in row.iter_mut().skip(x).take(w) {
+                *q = p;
+            }
+        }
+    }
+    fn draw_point(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        if x < self.width && y < self.height {
+            self.pixels[y*self.width + x] = u32::from_ne_bytes(FRAMEBUFFER_FORMAT.map(rgb));
+        }
+    }
+    // Straight into the pixels, rather than through a copy of them all.
+    fn blend(&mut self, plots: &[Plot]) {
+        for p in plots {
+            if p.x < 0 || p.y < 0 || p.x as usize >= self.width || p.y as usize >= self.height {
+                continue;
+            }
+            let q = &mut self.pixels[p.y as usize*self.width + p.x as usize];
+            let under = FRAMEBUFFER_FORMAT.unmap(q.to_ne_bytes());
+            *q = u32::from_ne_bytes(FRAMEBUFFER_FORMAT.map(mix(under, p.rgb, p.coverage)));
+        }
+    }
+    fn with_pixels(&mut self, f: &mut dyn FnMut(&mut [u8], usize)) -> Result<(), String> {
+        let mut bytes: Vec<u8> = self.pixels.iter().flat_map(|p| p.to_ne_bytes()).collect();
+        f(&mut bytes, self.width*4);
+        for (p, b) in self.pixels.iter_mut().zip(bytes.chunks_exact(4)) {
+            *p = u32::from_ne_bytes([b[0], b[1], b[2], b[3]]);
+        }
+        Ok(())
+    }
+    fn present(&mut self) {}
+    fn poll_input(&mut self) -> InputState {
+        InputState::default()
+    }
+    fn set_title(&mut self, _title: &str) {}
+    fn set_mode(&mut self, w: isize, h: isize, _fullscreen: bool) -> Result<(), String> {
+        *self = Framebuffer::new(w.max(1) as usize, h.max(1) as usize);
+        Ok(())
+    }
+}
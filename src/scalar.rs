@@ -0,0 +1,39 @@
+// The number type a string is simulated in: f64 unless asked otherwise, or
+// f32 where speed and memory matter more than the last few digits.
+use std::fmt::{Debug, Display};
+use std::iter::Sum;
+use std::ops::{AddAssign, DivAssign, MulAssign, SubAssign};
+
+use num_traits::{Float, FloatConst};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub trait Scalar: Float + FloatConst + Sum + AddAssign + SubAssign + MulAssign + DivAssign
+    + Default + Debug + Display + Send + Sync + Serialize + DeserializeOwned + 'static {
+    // An f64, such as one of the physics' constants, in this type.
+    fn of(value: f64) -> Self;
+    fn as_f64(self) -> f64;
+    // Remainder of division by rhs that's never negative, as f64's is.
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+        if r < Self::zero() {r + rhs.abs()} else {r}
+    }
+}
+
+impl Scalar for f32 {
+    fn of(value: f64) -> f32 {
+        value as f32
+    }
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Scalar for f64 {
+    fn of(value: f64) -> f64 {
+        value
+    }
+    fn as_f64(self) -> f64 {
+        self
+    }
+}
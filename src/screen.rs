@@ -0,0 +1,999 @@
+// The window's view of the strings: what's drawn and how, and the input that
+// came in since the last frame, over whichever Renderer it draws with.
+use std::f64;
+use std::error::Error;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+use {Chord, Guitar, Membrane, Vect};
+use capture;
+use bmp;
+use cli;
+use font;
+use geometry::catmull_rom;
+use pair;
+use render::{Key, Plot, Renderer};
+use view::{Camera, Transform, Viewport};
+#[cfg(feature = "pixels")]
+use pixels_window;
+#[cfg(feature = "window")]
+use window;
+
+// What draw_chord puts on screen for each string.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum DrawMode {
+    Dots,
+    Lines,
+    Both,
+}
+
+impl DrawMode {
+    pub(crate) fn next(self) -> DrawMode {
+        match self {
+            DrawMode::Dots => DrawMode::Lines,
+            DrawMode::Lines => DrawMode::Both,
+            DrawMode::Both => DrawMode::Dots,
+        }
+    }
+}
+
+// Colours everything on screen is drawn in.
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub(crate) name: &'static str,
+    pub(crate) background: (u8, u8, u8),
+    pub(crate) string: (u8, u8, u8),
+    // Dots held still: the ends, frets and anything grabbed.
+    pub(crate) fixed: (u8, u8, u8),
+    // Text, mode bars, the oscilloscope trace and marks on nodes and
+    // antinodes.
+    pub(crate) accent: (u8, u8, u8),
+    // Guide lines such as the fretboard and panel edges.
+    pub(crate) guide: (u8, u8, u8),
+}
+
+pub const THEMES: [Theme; 3] = [
+    Theme {name: "dark", background: (0, 0, 0), string: (255, 255, 255),
+        fixed: (255, 96, 64), accent: (0, 200, 80), guide: (96, 96, 96)},
+    Theme {name: "light", background: (240, 240, 232), string: (24, 24, 32),
+        fixed: (200, 32, 0), accent: (0, 96, 192), guide: (176, 176, 168)},
+    Theme {name: "high contrast", background: (0, 0, 0), string: (255, 255, 0),
+        fixed: (0, 255, 255), accent: (255, 255, 255), guide: (255, 255, 255)},
+];
+
+// A signal for the oscilloscope and the colour to draw it in.
+type Trace<'a> = (&'a [f64], (u8, u8, u8));
+
+// How much of earlier frames stays on screen: none, a fading trail, or the
+// brightest each pixel has been (the envelope of the motion).
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum TrailMode {
+    Off,
+    Trails,
+    Envelope,
+}
+
+impl TrailMode {
+    pub(crate) fn next(self) -> TrailMode {
+        match self {
+            TrailMode::Off => TrailMode::Trails,
+            TrailMode::Trails => TrailMode::Envelope,
+            TrailMode::Envelope => TrailMode::Off,
+        }
+    }
+}
+
+// Part of the segment from a to b inside the box from (0, 0) to (w, h), if
+// any of it is (Liang-Barsky).
+fn clip_segment(a: Vect, b: Vect, w: f64, h: f64) -> Option<(Vect, Vect)> {
+    let d = b - a;
+    let (mut t0, mut t1) = (0., 1.);
+    for &(p, q) in &[(-d.x, a.x), (d.x, w - a.x), (-d.y, a.y), (d.y, h - a.y)] {
+        if p == 0. {
+            if q < 0. {return None;}
+            continue;
+        }
+        let t = q/p;
+        if p < 0. {
+            if t > t1 {return None;}
+            if t > t0 {t0 = t;}
+        } else {
+            if t < t0 {return None;}
+            if t < t1 {t1 = t;}
+        }
+    }
+    let at = |t: f64| a + d*t;
+    Some((at(t0), at(t1)))
+}
+
+// Pixels of an antialiased line from a to b in rgb, added to plots, with
+// how much of each it covers (Xiaolin Wu's), pixel centres at whole
+// coordinates.
+fn wu_line(mut a: Vect, mut b: Vect, rgb: (u8, u8, u8), plots: &mut Vec<Plot>) {
+    let steep = (b.y - a.y).abs() > (b.x - a.x).abs();
+    if steep {
+        a = Vect::flat(a.y, a.x);
+        b = Vect::flat(b.y, b.x);
+    }
+    if a.x > b.x {
+        std::mem::swap(&mut a, &mut b);
+    }
+    let dx = b.x - a.x;
+    let gradient = if dx == 0. {1.} else {(b.y - a.y)/dx};
+    let mut plot = |x: f64, y: f64, coverage: f64| {
+        let (x, y) = if steep {(y, x)} else {(x, y)};
+        plots.push(Plot {x: x as i32, y: y as i32, coverage: coverage as f32, rgb});
+    };
+    let fract = |v: f64| v - v.floor();
+    // Each end covers as much of its column as the line reaches into.
+    let mut end = |p: Vect, gap: f64| {
+        let x = (p.x + 0.5).floor();
+        let y = p.y + gradient*(x - p.x);
+        plot(x, y.floor(), (1. - fract(y))*gap);
+        plot(x, y.floor() + 1., fract(y)*gap);
+        (x, y)
+    };
+    let (first, y) = end(a, 1. - fract(a.x + 0.5));
+    let (last, _) = end(b, fract(b.x + 0.5));
+    let mut y = y + gradient;
+    let mut x = first + 1.;
+    while x < last {
+        plot(x, y.floor(), 1. - fract(y));
+        plot(x, y.floor() + 1., fract(y));
+        y += gradient;
+        x += 1.;
+    }
+}
+
+// Pixels of a filled circle of radius about centre in rgb, added to plots,
+// its edge shaded by how much of each pixel it covers. Nothing is added if
+// it's wholly outside the w by h window.
+fn disc(centre: Vect, radius: f64, rgb: (u8, u8, u8), (w, h): (f64, f64), plots: &mut Vec<Plot>) {
+    let reach = (radius + 1.).ceil() as i32;
+    let beyond = reach as f64;
+    if !(centre.x > -beyond && centre.y > -beyond && centre.x < w + beyond && centre.y < h + beyond) {
+        return;
+    }
+    let (cx, cy) = (centre.x.round() as i32, centre.y.round() as i32);
+    for y in cy - reach..=cy + reach {
+        for x in cx - reach..=cx + reach {
+            let d = (x as f64 - centre.x).hypot(y as f64 - centre.y);
+            let coverage = (radius + 0.5 - d).clamp(0., 1.);
+            if coverage > 0. {
+                plots.push(Plot {x, y, coverage: coverage as f32, rgb});
+            }
+        }
+    }
+}
+
+// Visible part of the w by h rectangle at (x, y) in a width by height
+// window, as (x, y, w, h), or None if none of it is.
+fn clip_rect(x: i32, y: i32, w: i32, h: i32, width: i32, height: i32) -> Option<(i16, i16, u16, u16)> {
+    let (left, top) = (x.max(0), y.max(0));
+    let (right, bottom) = (x.saturating_add(w).min(width), y.saturating_add(h).min(height));
+    if left >= right || top >= bottom {
+        return None;
+    }
+    Some((left as i16, top as i16, (right - left) as u16, (bottom - top) as u16))
+}
+
+// Colour the fraction t of the way along evenly spaced gradient stops.
+fn gradient(stops: &[(u8, u8, u8)], t: f64) -> (u8, u8, u8) {
+    let last = stops.len()-1;
+    let u = t.clamp(0., 1.)*last as f64;
+    let i = (u.floor() as usize).min(last.saturating_sub(1));
+    let f = u - i as f64;
+    let (a, b) = (stops[i], stops[(i+1).min(last)]);
+    let mix = |p: u8, q: u8| (p as f64 + (q as f64 - p as f64)*f).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+// Points along a Catmull-Rom curve through points, SPLINE_STEPS to each gap
+// between them.
+fn smooth_curve(points: &[Vect]) -> Vec<Vect> {
+    if points.len() < 2 {
+        return points.to_vec();
+    }
+    let last = points.len()-1;
+    let mut curve = Vec::with_capacity(last*SPLINE_STEPS + 1);
+    for i in 0..last {
+        let p0 = points[i.saturating_sub(1)];
+        let p3 = points[(i+2).min(last)];
+        for step in 0..SPLINE_STEPS {
+            let t = step as f64/SPLINE_STEPS as f64;
+            curve.push(catmull_rom(p0, points[i], points[i+1], p3, t));
+        }
+    }
+    curve.push(points[last]);
+    curve
+}
+
+pub struct Screen {
+    pub(crate) width: isize,
+    pub(crate) height: isize,
+    pub(crate) renderer: Box<dyn Renderer>,
+    // Whether the window fills the display, and its size to go back to.
+    pub(crate) fullscreen: bool,
+    pub(crate) windowed: (isize, isize),
+    // Pixels per unit of displacement for a lone string, zoomed with +/-.
+    pub(crate) y_scale: f64,
+    pub(crate) draw_mode: DrawMode,
+    pub(crate) theme: Theme,
+    // Whether lines follow a smooth curve through the dots.
+    pub(crate) smooth: bool,
+    // Whether a crosshair marks the string's centre of mass, with 8.
+    pub(crate) show_com: bool,
+    // Whether dots are drawn as shaded circles and lines with Wu's
+    // algorithm, blended over what's there, rather than as hard squares
+    // and Bresenham lines; A switches.
+    pub(crate) antialias: bool,
+    // Whether the view is turned a quarter round the string with Z, looking
+    // down on it so the swing out of the plane shows as up and down.
+    pub(crate) from_above: bool,
+    // Whether dots are coloured by speed, and the recent top speed that
+    // maps to SPEED_HOT.
+    pub(crate) colour_by_speed: bool,
+    pub(crate) top_speed: f64,
+    // Recent biggest displacement of the membrane, which maps to the ends of
+    // its heat map.
+    pub(crate) top_height: f64,
+    // Text drawn in the top left corner over every frame.
+    pub(crate) overlay: String,
+    // Latest of the second pickup's signal, overlaid on the oscilloscope in
+    // the fixed dots' colour when there's a second pickup.
+    pub(crate) second_trace: Vec<f64>,
+    // Lines of the settings panel drawn in the top right corner, each in
+    // its own colour, while Tab has it showing.
+    pub(crate) panel: Vec<(String, (u8, u8, u8))>,
+    // Whether the bottom of the window shows the pickup signal.
+    pub(crate) show_scope: bool,
+    // Persistence of earlier frames: the mode, the fraction of its
+    // brightness a trail keeps each frame, and the faded image itself, one
+    // value per byte of the surface.
+    pub(crate) trail_mode: TrailMode,
+    pub(crate) trail_decay: f64,
+    pub(crate) trail: Vec<f32>,
+    // Where to save the next frame drawn, if it's wanted.
+    pub(crate) screenshot: Option<PathBuf>,
+    // Recording of the frames being shown, while F9 has one going.
+    pub(crate) recording: Option<capture::Capture>,
+    // Mode amplitudes drawn as bars under the string, when there are any.
+    pub(crate) modes: Vec<f64>,
+    // Each dot's recent largest displacement, when nodes and antinodes are
+    // being marked.
+    pub(crate) envelope: Vec<f64>,
+    pub(crate) should_end: bool,
+    pub(crate) pressed: Vec<Key>,
+    pub(crate) released: Vec<Key>,
+    // Keys that are down right now.
+    pub(crate) held: Vec<Key>,
+    // Where the pointer is, and whether the left button went down or up
+    // since the last tick.
+    pub(crate) mouse: (u16, u16),
+    pub(crate) clicked: bool,
+    pub(crate) unclicked: bool,
+    // Where the lone string's view has been zoomed and panned to, if it
+    // has; Home puts it back.
+    pub(crate) camera: Option<Camera>,
+    // Notches the wheel turned since the last tick, and where the pointer
+    // last was while the middle button is held.
+    pub(crate) wheel: i32,
+    pub(crate) panning: Option<(u16, u16)>,
+}
+
+impl Screen {
+    // Opens a w by h window to draw in, with whichever library backend says.
+    pub fn new(w: isize, h: isize, backend: cli::Backend) -> Result<Screen, Box<dyn Error>> {
+        let renderer: Result<Box<dyn Renderer>, Box<dyn Error>> = match backend {
+            #[cfg(feature = "window")]
+            cli::Backend::Sdl => Ok(Box::new(window::SdlWindow::open(w, h, "String")?)),
+            #[cfg(feature = "pixels")]
+            cli::Backend::Pixels => Ok(Box::new(pixels_window::PixelsWindow::open(w, h, "String")?)),
+            #[allow(unreachable_patterns)]
+            _ => Err(format!("can't open a {}x{} window without the {:?} backend", w, h, backend).into()),
+        };
+        renderer.map(Screen::with_renderer)
+    }
+    pub fn with_renderer(renderer: Box<dyn Renderer>) -> Screen {
+        let (w, h) = renderer.size();
+        Screen {width: w, height: h, renderer, fullscreen: false, windowed: (w, h), y_scale: Y_SCALE, draw_mode: DrawMode::Both, theme: THEMES[0], smooth: false, show_com: false, antialias: false, from_above: false,
+            colour_by_speed: false, top_speed: 0., top_height: 0., overlay: String::new(), second_trace: Vec::new(), panel: Vec::new(),
+            show_scope: true, trail_mode: TrailMode::Off, trail_decay: TRAIL_DECAY,
+            trail: Vec::new(), screenshot: None, recording: None, modes: Vec::new(), envelope: Vec::new(), should_end: false,
+            pressed: Vec::new(), released: Vec::new(), held: Vec::new(),
+            mouse: (0, 0), clicked: false, unclicked: false, camera: None, wheel: 0, panning: None}
+    }
+    pub(crate) fn tick(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.clicked = false;
+        self.unclicked = false;
+        let input = self.renderer.poll_input();
+        if input.quit {
+            self.should_end = true;
+        }
+        for k in input.pressed {
+            if k == Key::Escape {
+                self.should_end = true;
+                continue;
+            }
+            self.pressed.push(k);
+            if !self.held.contains(&k) {
+                self.held.push(k);
+            }
+        }
+        for k in input.released {
+            self.released.push(k);
+            self.held.retain(|&h| h != k);
+        }
+        if let Some((w, h)) = input.resized {
+            let fullscreen = self.fullscreen;
+            if let Err(err) = self.set_mode(w, h, fullscreen) {
+                eprintln!("failed to resize to {}x{}: {}", w, h, err);
+            }
+        }
+        if let Some(mouse) = input.mouse {
+            self.mouse = mouse;
+        }
+        self.clicked = input.clicked;
+        self.unclicked = input.unclicked;
+        self.wheel = input.wheel;
+        if input.middle_clicked {
+            self.panning = Some(self.mouse);
+        }
+        if input.middle_unclicked {
+            self.panning = None;
+        }
+    }
+    // Opens the window again at w by h, keeping the old one if that fails.
+    pub(crate) fn set_mode(&mut self, w: isize, h: isize, fullscreen: bool) -> Result<(), String> {
+        self.renderer.set_mode(w.max(1), h.max(1), fullscreen)?;
+        let (w, h) = self.renderer.size();
+        self.width = w;
+        self.height = h;
+        self.fullscreen = fullscreen;
+        Ok(())
+    }
+    pub(crate) fn toggle_fullscreen(&mut self) {
+        let result = if self.fullscreen {
+            let (w, h) = self.windowed;
+            self.set_mode(w, h, false)
+        } else {
+            self.windowed = (self.width, self.height);
+            let (w, h) = (self.width, self.height);
+            self.set_mode(w, h, true)
+        };
+        if let Err(err) = result {
+            eprintln!("failed to switch fullscreen: {}", err);
+        }
+    }
+    pub(crate) fn is_held(&self, k: Key) -> bool {
+        self.held.contains(&k)
+    }
+    // Fills a w pixel square with its top left corner at (x, y), drawing
+    // whatever part of it falls inside the window.
+    pub(crate) fn draw_square(&mut self, x: i32, y: i32, w: i32, rgb: (u8, u8, u8)) {
+        self.draw_rect(x, y, w, w, rgb);
+    }
+    pub(crate) fn draw_rect(&mut self, x: i32, y: i32, w: i32, h: i32, rgb: (u8, u8, u8)) {
+        if let Some((x, y, w, h)) = clip_rect(x, y, w, h, self.width as i32, self.height as i32) {
+            self.renderer.fill_rect(x as usize, y as usize, w as usize, h as usize, rgb);
+        }
+    }
+    // Draws the string, and under it the latest of the pickup signal.
+    pub(crate) fn draw(&mut self, chord: &Chord, signal: &[f64]) {
+        self.clear();
+        self.track_speed(&[chord]);
+        let transform = self.string_transform(chord);
+        self.draw_chord(chord, &transform);
+        self.draw_modes();
+        self.persist();
+        self.draw_scope(signal);
+        self.finish();
+    }
+    // The string in the top half of the window and the signal of the
+    // Karplus-Strong line it's being compared with in the bottom half, on
+    // the same time scale as the string's own oscilloscope.
+    pub(crate) fn draw_comparison(&mut self, chord: &Chord, compared: &[f64]) {
+        self.clear();
+        self.track_speed(&[chord]);
+        let (w, h) = (self.width as f64, self.height as f64);
+        let top = Viewport {x: 0., y: 0., w, h: h/2.};
+        let transform = Transform::fit(chord, top, h/4., self.y_scale);
+        self.draw_chord(chord, &transform);
+        self.persist();
+        self.draw_trace(Viewport {x: 0., y: h/2., w, h: h/2.}, compared);
+        self.finish();
+    }
+    // The two strings of a comparison, the first above the second, each
+    // labelled with its side, and along the bottom a strip of how far apart
+    // their dots have been lately.
+    pub(crate) fn draw_pair(&mut self, chord: &Chord, pair: &pair::Pair) {
+        self.clear();
+        self.track_speed(&[chord, &pair.other]);
+        let (w, h) = (self.width as f64, self.height as f64);
+        let strip = (h*STRIP_SHARE).round();
+        let half = (h - strip)/2.;
+        let views = [Viewport {x: 0., y: 0., w, h: half}, Viewport {x: 0., y: half, w, h: half}];
+        let transform = Transform::fit(chord, views[0], half/2., self.y_scale);
+        self.draw_chord(chord, &transform);
+        let other = Transform::fit(&pair.other, views[1], half*1.5, self.y_scale);
+        self.draw_chord(&pair.other, &other);
+        self.persist();
+        let (first, second) = pair.names();
+        for (view, name) in views.iter().zip(&[first, second]) {
+            let y = (view.y + view.h) as i32 - OVERLAY_MARGIN - 5*OVERLAY_PIXEL;
+            self.draw_text(OVERLAY_MARGIN, y, name, OVERLAY_PIXEL, self.theme.accent);
+        }
+        self.draw_apart(Viewport {x: 0., y: h - strip, w, h: strip}, chord, &transform, pair.history());
+        self.finish();
+    }
+    // How far apart the strings have been at each dot, a row a frame with
+    // the newest at the top, under each dot of chord as transform draws it
+    // and brighter the further apart, up to the most in the strip.
+    pub(crate) fn draw_apart(&mut self, view: Viewport, chord: &Chord, transform: &Transform, history: &VecDeque<Vec<f64>>) {
+        self.draw_rect(view.x as i32, view.y as i32, view.w as i32, 1, self.theme.guide);
+        let most = history.iter().flatten().fold(0., |m: f64, &a| m.max(a));
+        let row = (view.h - 1.)/pair::HISTORY as f64;
+        let column = transform.x_scale.ceil() as i32;
+        let (background, accent) = (self.theme.background, self.theme.accent);
+        for (j, apart) in history.iter().rev().enumerate() {
+            let top = (view.y + 1. + j as f64*row).round() as i32;
+            let bottom = (view.y + 1. + (j + 1) as f64*row).round() as i32;
+            for (d, &a) in chord.dots().iter().zip(apart) {
+                if a == 0. {
+                    continue;
+                }
+                let x = transform.apply(d.pos()).x + 2. - transform.x_scale/2.;
+                self.draw_rect(x.round() as i32, top, column, bottom - top, gradient(&[background, accent], a/most));
+            }
+        }
+        let text = format!("apart by up to {:.3e}", most);
+        let width = text.chars().count() as i32*(font::WIDTH + 1)*OVERLAY_PIXEL;
+        self.draw_text(self.width as i32 - OVERLAY_MARGIN - width, view.y as i32 + OVERLAY_MARGIN, &text, OVERLAY_PIXEL, self.theme.string);
+    }
+    // One bar per mode along the bottom of the string's viewport, as tall
+    // as the mode's amplitude at the string's scale.
+    pub(crate) fn draw_modes(&mut self) {
+        if self.modes.is_empty() {
+            return;
+        }
+        let view = self.string_viewport();
+        let slot = (view.w - 2.*view.margin_x())/self.modes.len() as f64;
+        let bottom = view.y + view.h - MODE_MARGIN;
+        for (m, a) in self.modes.clone().iter().enumerate() {
+            let h = (a.abs()*self.y_scale).min(view.h/2.).round() as i32;
+            let x = view.x + view.margin_x() + slot*m as f64 + slot*0.2;
+            self.draw_rect(x.round() as i32, bottom as i32 - h, (slot*0.6).round() as i32, h, self.theme.accent);
+        }
+    }
+    // Mixes what's been drawn this frame with the faded earlier frames and
+    // puts the result on screen. Each byte of colour keeps whichever of its
+    // new value and its faded old one stands out more from the background,
+    // so this works whatever order the surface stores its channels in.
+    pub(crate) fn persist(&mut self) {
+        let decay = match self.trail_mode {
+            TrailMode::Off => return,
+            TrailMode::Trails => self.trail_decay as f32,
+            TrailMode::Envelope => 1.,
+        };
+        let background = self.renderer.format().map(self.theme.background);
+        let trail = &mut self.trail;
+        // A frame whose pixels can't be reached just goes without trails.
+        let _ = self.renderer.with_pixels(&mut |pixels, _| {
+            if trail.len() != pixels.len() {
+                *trail = vec![0.; pixels.len()];
+            }
+            for (i, (p, t)) in pixels.iter_mut().zip(trail.iter_mut()).enumerate() {
+                let bg = background[i % 4] as f32;
+                *t = (*t*decay).max((*p as f32 - bg).abs());
+                // Ink runs away from the background, down on light ones.
+                let ink = if bg > 127. {bg - *t} else {bg + *t};
+                *p = ink.round().clamp(0., 255.) as u8;
+            }
+        });
+    }
+    // Starts a frame on the theme's background.
+    pub(crate) fn clear(&mut self) {
+        self.renderer.begin_frame(self.theme.background);
+    }
+    pub(crate) fn cycle_theme(&mut self) {
+        let i = THEMES.iter().position(|t| t.name == self.theme.name).unwrap_or(0);
+        self.theme = THEMES[(i + 1) % THEMES.len()];
+        self.trail.clear();
+        println!("{} theme", self.theme.name);
+    }
+    // Moves to the next trail mode, starting it from a clean slate.
+    pub(crate) fn cycle_trails(&mut self) {
+        self.trail_mode = self.trail_mode.next();
+        self.trail.clear();
+    }
+    // Makes trails last longer (factor above 1) or shorter, by scaling how
+    // much brightness they lose each frame.
+    pub(crate) fn stretch_trails(&mut self, factor: f64) {
+        let loss = ((1. - self.trail_decay)/factor).clamp(1. - MAX_TRAIL_DECAY, 1. - MIN_TRAIL_DECAY);
+        self.trail_decay = 1. - loss;
+        println!("trail decay {:.4}", self.trail_decay);
+    }
+    // Part of the window the strings get: all of it, or the top two thirds
+    // when the oscilloscope is showing.
+    pub(crate) fn string_viewport(&self) -> Viewport {
+        let (w, h) = (self.width as f64, self.height as f64);
+        let h = if self.show_scope {h*(1. - SCOPE_SHARE)} else {h};
+        Viewport {x: 0., y: 0., w, h}
+    }
+    pub(crate) fn scope_viewport(&self) -> Viewport {
+        let (w, h) = (self.width as f64, self.height as f64);
+        Viewport {x: 0., y: h*(1. - SCOPE_SHARE), w, h: h*SCOPE_SHARE}
+    }
+    // Draws the oscilloscope of signal, if it's showing, with the second
+    // pickup's over it on the same scale, so what each pickup hears of the
+    // string can be told apart.
+    pub(crate) fn draw_scope(&mut self, signal: &[f64]) {
+        if self.show_scope {
+            let view = self.scope_viewport();
+            let second = std::mem::take(&mut self.second_trace);
+            let mut traces = vec![(signal, self.theme.accent)];
+            if !second.is_empty() {
+                traces.push((&second[..], self.theme.fixed));
+            }
+            self.draw_traces(view, &traces);
+            self.second_trace = second;
+        }
+    }
+    pub(crate) fn draw_trace(&mut self, view: Viewport, signal: &[f64]) {
+        let accent = self.theme.accent;
+        self.draw_traces(view, &[(signal, accent)]);
+    }
+    // Last SCOPE_SAMPLES of each signal as a trace across view in its
+    // colour, under a rule along its top and scaled so the biggest swing of
+    // any of them just fits.
+    pub(crate) fn draw_traces(&mut self, view: Viewport, signals: &[Trace]) {
+        self.draw_rect(view.x as i32, view.y as i32, view.w as i32, 1, self.theme.guide);
+        let shown: Vec<_> = signals.iter().map(|&(signal, rgb)| (&signal[signal.len().saturating_sub(SCOPE_SAMPLES)..], rgb)).collect();
+        let peak = shown.iter().flat_map(|&(s, _)| s).fold(0., |m: f64, s| m.max(s.abs()));
+        let half = view.h/2. - SCOPE_MARGIN;
+        let gain = if peak > 0. {half/peak} else {0.};
+        let centre = view.y + view.h/2.;
+        let step = (view.w - 2.*SCOPE_MARGIN)/(SCOPE_SAMPLES-1) as f64;
+        // About one point per pixel is all the trace can show.
+        let stride = ((1./step).floor() as usize).max(1);
+        for (shown, rgb) in shown {
+            if shown.len() < 2 {
+                continue;
+            }
+            let start = view.x + SCOPE_MARGIN + (SCOPE_SAMPLES - shown.len()) as f64*step;
+            let points: Vec<Vect> = shown.iter().enumerate().step_by(stride)
+                .map(|(i, s)| Vect::flat(start + i as f64*step, centre - s*gain))
+                .collect();
+            self.draw_path(&points, rgb);
+        }
+    }
+    // The window's pixels as rows of RGB bytes from the top left.
+    pub(crate) fn capture(&mut self) -> Result<Vec<u8>, String> {
+        let format = self.renderer.format();
+        let (w, h) = (self.width as usize, self.height as usize);
+        let mut rgb = Vec::with_capacity(w*h*3);
+        self.renderer.with_pixels(&mut |pixels, pitch| {
+            for y in 0..h {
+                for x in 0..w {
+                    let i = y*pitch + x*4;
+                    let (r, g, b) = format.unmap([pixels[i], pixels[i+1], pixels[i+2], pixels[i+3]]);
+                    rgb.extend_from_slice(&[r, g, b]);
+                }
+            }
+        })?;
+        Ok(rgb)
+    }
+    // Saves what's on screen as a BMP file.
+    pub(crate) fn save_screenshot(&mut self, path: &Path) -> Result<(), String> {
+        let rgb = self.capture()?;
+        bmp::write_bmp(path, self.width as usize, self.height as usize, &rgb)
+            .map_err(|err| err.to_string())
+    }
+    // Starts recording every'th frame to a GIF at path, or stops the
+    // recording under way and finishes writing it.
+    pub(crate) fn toggle_recording(&mut self, path: &Path, every: u64, fps: f64) {
+        if let Some(recording) = self.recording.take() {
+            match recording.finish() {
+                Ok((written, dropped)) => println!("recorded {} frames, dropped {}", written, dropped),
+                Err(err) => eprintln!("failed to finish the recording: {}", err),
+            }
+            return;
+        }
+        let delay = (every as f64/fps*100.).round().clamp(1., u16::MAX as f64) as u16;
+        match capture::Capture::start(path, self.width as u16, self.height as u16, every, delay) {
+            Ok(recording) => {
+                println!("recording to {}", path.display());
+                self.recording = Some(recording);
+            },
+            Err(err) => eprintln!("failed to start recording {}: {}", path.display(), err),
+        }
+    }
+    // Puts the overlay on top of what's been drawn, saves a screenshot if
+    // one was asked for, and shows the frame.
+    pub(crate) fn finish(&mut self) {
+        let overlay = self.overlay.clone();
+        let accent = self.theme.accent;
+        self.draw_text(OVERLAY_MARGIN, OVERLAY_MARGIN, &overlay, OVERLAY_PIXEL, accent);
+        let panel = self.panel.clone();
+        for (i, (text, rgb)) in panel.iter().enumerate() {
+            let width = text.chars().count() as i32*(font::WIDTH + 1)*OVERLAY_PIXEL;
+            let y = OVERLAY_MARGIN + i as i32*PANEL_LINE;
+            self.draw_text(self.width as i32 - OVERLAY_MARGIN - width, y, text, OVERLAY_PIXEL, *rgb);
+        }
+        if let Some(path) = self.screenshot.take() {
+            match self.save_screenshot(&path) {
+                Ok(()) => println!("wrote {}", path.display()),
+                Err(err) => eprintln!("failed to write {}: {}", path.display(), err),
+            }
+        }
+        if let Some(mut recording) = self.recording.take() {
+            if recording.wants_frame() {
+                match self.capture() {
+                    Ok(rgb) => recording.push(rgb),
+                    Err(err) => eprintln!("failed to record a frame: {}", err),
+                }
+            }
+            self.recording = Some(recording);
+        }
+        self.renderer.present();
+    }
+    // Writes text in the built-in font with its top left corner at (x, y),
+    // each font pixel drawn pixel screen pixels across.
+    pub(crate) fn draw_text(&mut self, x: i32, y: i32, text: &str, pixel: i32, rgb: (u8, u8, u8)) {
+        for (i, c) in text.chars().enumerate() {
+            let left = x + i as i32*(font::WIDTH + 1)*pixel;
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for col in 0..font::WIDTH {
+                    if bits & (1 << (font::WIDTH - 1 - col)) != 0 {
+                        self.draw_square(left + col*pixel, y + row as i32*pixel, pixel, rgb);
+                    }
+                }
+            }
+        }
+    }
+    // Where the lone string is drawn: fitted to its part of the window and
+    // then seen through the camera, if it's been moved.
+    pub(crate) fn string_transform(&self, chord: &Chord) -> Transform {
+        let view = self.string_viewport();
+        let fit = Transform::fit(chord, view, view.y + view.h/2., self.y_scale);
+        match self.camera {
+            Some(camera) => camera.through(&fit, view),
+            None => fit,
+        }
+    }
+    // Zooms the lone string's view toward the pointer by the notches the
+    // wheel turned this frame, and pans it by how far the pointer went
+    // with the middle button held.
+    pub(crate) fn steer_camera(&mut self, chord: &Chord) {
+        let view = self.string_viewport();
+        let fit = Transform::fit(chord, view, view.y + view.h/2., self.y_scale);
+        let camera = self.camera.unwrap_or_else(|| Camera::fitted(&fit, view));
+        let mouse = Vect::flat(self.mouse.0 as f64, self.mouse.1 as f64);
+        if self.wheel != 0 {
+            let factor = CAMERA_STEP.powi(self.wheel);
+            self.camera = Some(camera.zoomed(&fit, view, factor, (MIN_CAMERA_ZOOM, MAX_CAMERA_ZOOM), mouse));
+        }
+        if let Some(from) = self.panning.filter(|&from| from != self.mouse) {
+            let camera = self.camera.unwrap_or(camera);
+            self.camera = Some(camera.panned(&fit, mouse - Vect::flat(from.0 as f64, from.1 as f64)));
+            self.panning = Some(self.mouse);
+        }
+    }
+    // Follows the fastest dot on screen, letting the top speed sag slowly
+    // so colours stay spread out as the strings die down.
+    pub(crate) fn track_speed(&mut self, chords: &[&Chord]) {
+        let fastest = chords.iter()
+            .flat_map(|c| c.dots().iter())
+            .fold(0., |m: f64, d| m.max(d.vel().size()));
+        self.top_speed = (self.top_speed*TOP_SPEED_DECAY).max(fastest);
+    }
+    // Scales the vertical exaggeration by factor, within sensible limits.
+    pub(crate) fn zoom(&mut self, factor: f64) {
+        self.y_scale = (self.y_scale*factor).clamp(MIN_Y_SCALE, MAX_Y_SCALE);
+    }
+    // The free dot of a lone string drawn nearest the pointer, if it's
+    // within GRAB_RADIUS pixels. Dragging pulls dots up and down in the
+    // window's plane, so looking from above there's nothing to grab.
+    pub(crate) fn dot_under_mouse(&self, chord: &Chord) -> Option<usize> {
+        if self.from_above {
+            return None;
+        }
+        let mouse = Vect::flat(self.mouse.0 as f64, self.mouse.1 as f64);
+        let transform = self.string_transform(chord);
+        chord.dots().iter().enumerate()
+            .filter(|&(_, d)| !d.is_fixed())
+            .map(|(i, d)| {
+                let p = transform.apply(d.pos());
+                (i, (Vect::flat(p.x, p.y) - mouse).size())
+            })
+            .filter(|&(_, dist)| dist <= GRAB_RADIUS)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+    // Height on a lone string under the pointer.
+    pub(crate) fn mouse_height(&self, chord: &Chord) -> f64 {
+        let mouse = Vect::flat(self.mouse.0 as f64, self.mouse.1 as f64);
+        self.string_transform(chord).invert(mouse).y
+    }
+    // Fraction of the way along a lone string the pointer is, from 0 at the
+    // left end to 1 at the right.
+    pub(crate) fn mouse_fraction(&self, chord: &Chord) -> f64 {
+        let mouse = Vect::flat(self.mouse.0 as f64, self.mouse.1 as f64);
+        let x = self.string_transform(chord).invert(mouse).x;
+        let dots = chord.dots();
+        let (left, right) = (dots[0].pos().x, dots[dots.len()-1].pos().x);
+        ((x - left)/(right - left)).clamp(0., 1.)
+    }
+    // The membrane seen from above as a heat map, one square per dot, going
+    // from SPEED_COLD below the plane through the background to SPEED_HOT
+    // above it.
+    pub(crate) fn draw_membrane(&mut self, membrane: &Membrane) {
+        self.clear();
+        let highest = membrane.dots().iter().fold(0., |m: f64, d| m.max(d.pos().z.abs()));
+        self.top_height = (self.top_height*TOP_SPEED_DECAY).max(highest);
+        let (cols, rows) = (membrane.cols() as i32, membrane.rows() as i32);
+        let (w, h) = (self.width as i32, self.height as i32);
+        let cell = (w/cols).min(h/rows).max(1);
+        let (left, top) = ((w - cell*cols)/2, (h - cell*rows)/2);
+        for j in 0..rows {
+            for i in 0..cols {
+                let d = membrane.dot(i as usize, j as usize);
+                let colour = if d.is_fixed() {
+                    self.theme.guide
+                } else if self.top_height > 0. {
+                    let t = 0.5 + 0.5*d.pos().z/self.top_height;
+                    gradient(&[SPEED_COLD, self.theme.background, SPEED_HOT], t)
+                } else {
+                    self.theme.background
+                };
+                self.draw_square(left + i*cell, top + j*cell, cell, colour);
+            }
+        }
+        self.persist();
+        self.finish();
+    }
+    // Strings stacked top to bottom in lanes of equal height, each drawn at
+    // GUITAR_SCALE times the lone string's vertical scale.
+    pub(crate) fn draw_guitar(&mut self, guitar: &Guitar, signal: &[f64]) {
+        self.clear();
+        let strings: Vec<&Chord> = guitar.strings().iter().collect();
+        self.track_speed(&strings);
+        let view = self.string_viewport();
+        let lane = (view.h - 2.*view.margin_y())/guitar.strings().len() as f64;
+        for (i, string) in guitar.strings().iter().enumerate() {
+            let centre = view.y + view.margin_y() + lane*(i as f64 + 0.5);
+            let transform = Transform::fit(string, view, centre, self.y_scale*GUITAR_SCALE);
+            self.draw_chord(string, &transform);
+        }
+        self.persist();
+        self.draw_scope(signal);
+        self.finish();
+    }
+    // One pixel wide lines joining window positions in turn, clipped to
+    // the window. Antialiased, the whole path is blended in at once.
+    pub(crate) fn draw_path(&mut self, points: &[Vect], rgb: (u8, u8, u8)) {
+        let (w, h) = ((self.width-1) as f64, (self.height-1) as f64);
+        let mut plots = Vec::new();
+        for pair in points.windows(2) {
+            let (start, end) = match clip_segment(pair[0], pair[1], w, h) {
+                Some(ends) => ends,
+                None => continue,
+            };
+            if self.antialias {
+                wu_line(start, end, rgb, &mut plots);
+            } else {
+                let pixel = |p: Vect| (p.x.round() as usize, p.y.round() as usize);
+                self.renderer.draw_line(pixel(start), pixel(end), rgb);
+            }
+        }
+        if !plots.is_empty() {
+            self.renderer.blend(&plots);
+        }
+    }
+    // A position as the view has it: turned a quarter round the string when
+    // looking from above, with z towards the viewer either way.
+    pub(crate) fn seen(&self, pos: Vect) -> Vect {
+        if self.from_above {Vect {x: pos.x, y: pos.z, z: -pos.y}} else {pos}
+    }
+    pub(crate) fn draw_chord(&mut self, chord: &Chord, transform: &Transform) {
+        if let Some(barrier) = chord.barrier() {
+            let y = transform.apply(Vect::flat(0., barrier)).y;
+            self.draw_rect(0, y.round() as i32 + 4, self.width as i32, 1, self.theme.guide);
+        }
+        // A small block where the magnet sits.
+        if let Some(m) = chord.magnet() {
+            let p = transform.apply(self.seen(m));
+            self.draw_rect(p.x.round() as i32 - MAGNET_MARK, p.y.round() as i32 - MAGNET_MARK/2,
+                           2*MAGNET_MARK, MAGNET_MARK, self.theme.fixed);
+        }
+        // A short upright mark where a finger touches the string.
+        if let Some(i) = chord.touched() {
+            let p = transform.apply(Vect::flat(chord.dots()[i].pos().x, 0.));
+            self.draw_rect(p.x.round() as i32 + 1, p.y.round() as i32 - TOUCH_MARK, 2, 2*TOUCH_MARK + 4, self.theme.guide);
+        }
+        // A crosshair on the centre of mass, through the middle of where a
+        // dot there would be drawn.
+        if self.show_com {
+            let p = transform.apply(self.seen(chord.center_of_mass()));
+            let (x, y) = (p.x.round() as i32 + 2, p.y.round() as i32 + 2);
+            self.draw_rect(x - COM_MARK, y, 2*COM_MARK + 1, 1, self.theme.accent);
+            self.draw_rect(x, y - COM_MARK, 1, 2*COM_MARK + 1, self.theme.accent);
+        }
+        let points: Vec<Vect> = chord.dots().iter().map(|c| transform.apply(self.seen(c.pos()))).collect();
+        if self.draw_mode != DrawMode::Dots {
+            // Through the middle of each dot's square, or circle.
+            let middle = if self.antialias {Vect::flat(1.5, 1.5)} else {Vect::flat(2., 2.)};
+            let curve: Vec<Vect> = if self.smooth {smooth_curve(&points)} else {points.clone()}.into_iter().map(|p| p + middle).collect();
+            self.draw_path(&curve, self.theme.string);
+        }
+        // Nodes, where the envelope stays under NODE_FRACTION of its
+        // largest, and antinodes, where it peaks.
+        let envelope = if self.envelope.len() == chord.dots().len() {self.envelope.clone()} else {Vec::new()};
+        let loudest = envelope.iter().cloned().fold(0., f64::max);
+        let node = |i: usize| loudest > 0. && envelope[i] < NODE_FRACTION*loudest;
+        if loudest > 0. {
+            for i in 1..envelope.len()-1 {
+                if node(i) || envelope[i] < envelope[i-1] || envelope[i] <= envelope[i+1] {
+                    continue;
+                }
+                let x = chord.dots()[i].pos().x;
+                for &y in &[envelope[i], -envelope[i]] {
+                    let p = transform.apply(self.seen(Vect {x, y, z: y}));
+                    self.draw_rect(p.x.round() as i32 - ANTINODE_MARK + 2, p.y.round() as i32 + 1, 2*ANTINODE_MARK, 2, self.theme.accent);
+                }
+            }
+        }
+        if self.draw_mode != DrawMode::Lines {
+            let mut plots = Vec::new();
+            for (i, (p, c)) in points.iter().zip(chord.dots()).enumerate() {
+                let colour = if c.is_fixed() {
+                    self.theme.fixed
+                } else if node(i) {
+                    self.theme.accent
+                } else if self.colour_by_speed && self.top_speed > 0. {
+                    gradient(&[SPEED_COLD, self.theme.string, SPEED_HOT], c.vel().size()/self.top_speed)
+                } else {
+                    self.theme.string
+                };
+                // Nearer dots are drawn bigger, around the same middle.
+                let across = 4. + 2.*(p.z/DEPTH_PIXELS).clamp(-1., 1.);
+                if self.antialias {
+                    // The middle of the square's middle pixels.
+                    disc(*p + Vect::flat(1.5, 1.5), across/2., colour, (self.width as f64, self.height as f64), &mut plots);
+                    continue;
+                }
+                let size = across.round() as i32;
+                self.draw_square(
+                    p.x.round() as i32 + (4 - size)/2,
+                    p.y.round() as i32 + (4 - size)/2,
+                    size, colour
+                );
+            }
+            if !plots.is_empty() {
+                self.renderer.blend(&plots);
+            }
+        }
+    }
+}
+
+// Share of the window the strip under a comparison takes.
+const STRIP_SHARE: f64 = 1./6.;
+
+// Sine modes shown as bars with N, how often they're worked out again, and
+// the gap under them in pixels.
+pub(crate) const MODE_COUNT: usize = 16;
+pub(crate) const MODES_EVERY: u64 = 5;
+const MODE_MARGIN: f64 = 10.;
+// Fraction of the window's height the oscilloscope takes, the gap kept
+// around its trace in pixels, and how many pickup samples it spans.
+const SCOPE_SHARE: f64 = 1./3.;
+const SCOPE_MARGIN: f64 = 10.;
+pub(crate) const SCOPE_SAMPLES: usize = 1<<14;
+// Brightness a trail keeps from one frame to the next, its limits, and how
+// much 9 and 0 change the fraction it loses.
+const TRAIL_DECAY: f64 = 0.97;
+const MIN_TRAIL_DECAY: f64 = 0.5;
+const MAX_TRAIL_DECAY: f64 = 0.999;
+pub(crate) const TRAIL_STEP: f64 = 1.5;
+// Frames kept by an F9 recording: one in RECORD_EVERY, saved as
+// string-<timestamp>.gif in RECORD_DIR.
+pub(crate) const RECORD_EVERY: u64 = 4;
+pub(crate) const RECORD_DIR: &str = ".";
+// Gap between the overlay text and the window's corner, and the size of
+// one font pixel, in screen pixels.
+const OVERLAY_MARGIN: i32 = 8;
+const OVERLAY_PIXEL: i32 = 2;
+// Pixels from the top of one line of the settings panel to the next.
+const PANEL_LINE: i32 = 7*OVERLAY_PIXEL;
+// Pixels towards or away from the viewer at which a dot is drawn at its
+// biggest or smallest.
+const DEPTH_PIXELS: f64 = 100.;
+// Pieces each gap between dots is split into for a smooth curve.
+const SPLINE_STEPS: usize = 8;
+// Zoom each notch of the wheel gives the lone string's view, and the least
+// and most it can be zoomed from the fitted view.
+const CAMERA_STEP: f64 = 1.25;
+const MIN_CAMERA_ZOOM: f64 = 0.25;
+const MAX_CAMERA_ZOOM: f64 = 256.;
+// Pixels per unit of displacement a lone string starts drawn at, how far
+// +/- can zoom that, and how much each press changes it.
+const Y_SCALE: f64 = 6.;
+const MIN_Y_SCALE: f64 = 0.1;
+const MAX_Y_SCALE: f64 = 1000.;
+pub(crate) const ZOOM_STEP: f64 = 1.25;
+// Vertical scale of each of the six strings sharing the window, relative
+// to a lone string's.
+const GUITAR_SCALE: f64 = 0.5;
+// Colours of still and of the fastest dots when colouring by speed, with
+// the theme's string colour halfway, and how much of the top speed is kept
+// from one frame to the next.
+const SPEED_COLD: (u8, u8, u8) = (32, 64, 255);
+const SPEED_HOT: (u8, u8, u8) = (255, 32, 0);
+const TOP_SPEED_DECAY: f64 = 0.995;
+// Fraction of the largest envelope under which a dot counts as a node, and
+// half the width in pixels of the marks at an antinode's extremes.
+const NODE_FRACTION: f64 = 0.05;
+const ANTINODE_MARK: i32 = 6;
+// Periods of the pitch heard the envelope spans, unless it's set.
+pub(crate) const ENVELOPE_PERIODS: f64 = 2.;
+// Half the width in pixels of the block drawn for the magnet.
+const MAGNET_MARK: i32 = 6;
+// Half the height in pixels of the mark where the string is touched.
+const TOUCH_MARK: i32 = 12;
+// Half the width in pixels of the crosshair on the centre of mass.
+const COM_MARK: i32 = 8;
+// Farthest in pixels a click can be from a dot and still grab it.
+const GRAB_RADIUS: f64 = 20.;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clip_rect_drops_rects_off_screen() {
+        assert_eq!(clip_rect(-20, 10, 10, 10, 100, 50), None);
+        assert_eq!(clip_rect(100, 10, 10, 10, 100, 50), None);
+        assert_eq!(clip_rect(10, -10, 10, 10, 100, 50), None);
+        assert_eq!(clip_rect(10, 50, 10, 10, 100, 50), None);
+        assert_eq!(clip_rect(10, 10, 0, 10, 100, 50), None);
+    }
+
+    #[test]
+    fn clip_rect_trims_rects_partly_on_screen() {
+        assert_eq!(clip_rect(-5, -5, 10, 10, 100, 50), Some((0, 0, 5, 5)));
+        assert_eq!(clip_rect(95, 45, 10, 10, 100, 50), Some((95, 45, 5, 5)));
+        assert_eq!(clip_rect(10, 20, 30, 5, 100, 50), Some((10, 20, 30, 5)));
+    }
+
+    #[test]
+    fn clip_rect_fits_oversize_rects_to_the_window() {
+        assert_eq!(clip_rect(-1000, -1000, 5000, 5000, 100, 50), Some((0, 0, 100, 50)));
+        assert_eq!(clip_rect(i32::MAX - 1, 0, i32::MAX, 10, 100, 50), None);
+        assert_eq!(clip_rect(i32::MIN, i32::MIN, i32::MAX, i32::MAX, 100, 50), None);
+        assert_eq!(clip_rect(0, 0, i32::MAX, i32::MAX, 100, 50), Some((0, 0, 100, 50)));
+    }
+
+    const STOPS: [(u8, u8, u8); 3] = [(0, 0, 0), (200, 100, 50), (255, 255, 255)];
+
+    #[test]
+    fn gradient_ends_on_its_stops() {
+        assert_eq!(gradient(&STOPS, 0.), STOPS[0]);
+        assert_eq!(gradient(&STOPS, 1.), STOPS[2]);
+        assert_eq!(gradient(&STOPS, 0.5), STOPS[1]);
+    }
+
+    #[test]
+    fn gradient_mixes_between_stops() {
+        assert_eq!(gradient(&STOPS, 0.25), (100, 50, 25));
+        assert_eq!(gradient(&[(0, 0, 0), (255, 255, 255)], 0.5), (128, 128, 128));
+    }
+
+    #[test]
+    fn gradient_holds_out_of_range_t_at_the_ends() {
+        assert_eq!(gradient(&STOPS, -3.), STOPS[0]);
+        assert_eq!(gradient(&STOPS, 7.), STOPS[2]);
+        assert_eq!(gradient(&[(9, 8, 7)], 0.6), (9, 8, 7));
+    }
+}
@@ -0,0 +1,223 @@
+// Events read from a script file and played into the string as its clock
+// passes them, one to a line:
+//
+//     # a pluck, a fret and then a palm on the string
+//     at 0 pluck pos=0.3 amp=4
+//     at 25000 fret index=40
+//     at 50000 set_damping 0.001
+//     at 90000 quit
+//
+// Times are in the string's own units, as Chord::time counts them. Events
+// at the same time go in the order the file gives them.
+use std::fs;
+use std::path::Path;
+
+use Chord;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    // Adds a triangle of height amplitude peaking at the fraction position
+    // along the string to its displacement.
+    Pluck {position: f64, amplitude: f64},
+    // Adds a triangle of velocity the same way, as a hammer would.
+    Strike {position: f64, speed: f64},
+    Fret(usize),
+    Unfret(usize),
+    SetDamping(f64),
+    // Glides k to the new value, as the whammy bar does.
+    SetK(f64),
+    Reset,
+    Quit,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub at: f64,
+    pub action: Action,
+}
+
+// The events still to come, soonest first.
+pub struct Script {
+    events: Vec<Event>,
+    next: usize,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Script, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Script::parse(&text).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+    pub fn parse(text: &str) -> Result<Script, String> {
+        let mut events = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            events.push(parse_event(line).map_err(|err| format!("line {}: {}", i + 1, err))?);
+        }
+        // A stable sort, so ties keep the file's order.
+        events.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Ok(Script {events, next: 0})
+    }
+    // Time of the next event, if there's one left.
+    pub fn next_at(&self) -> Option<f64> {
+        self.events.get(self.next).map(|e| e.at)
+    }
+    // Takes the actions of every event at or before time.
+    pub fn due(&mut self, time: f64) -> Vec<Action> {
+        let start = self.next;
+        while self.next_at().is_some_and(|at| at <= time) {
+            self.next += 1;
+        }
+        self.events[start..self.next].iter().map(|e| e.action.clone()).collect()
+    }
+}
+
+impl Action {
+    // Does the action to chord. Quit is left to the caller.
+    pub fn apply(&self, chord: &mut Chord) {
+        match *self {
+            Action::Pluck {position, amplitude} => chord.excite(|u| triangle(u, position)*amplitude),
+            Action::Strike {position, speed} => chord.excite_velocity(|u| triangle(u, position)*speed),
+            Action::Fret(index) => chord.fret(index),
+            Action::Unfret(index) => chord.unfret(index),
+            Action::SetDamping(damping) => chord.set_damping(damping),
+            Action::SetK(k) => chord.set_target_k(k),
+            Action::Reset => chord.reset(),
+            Action::Quit => {},
+        }
+    }
+}
+
+// 0 at both ends of the string and 1 at the fraction apex along it.
+fn triangle(u: f64, apex: f64) -> f64 {
+    if u <= apex {u/apex} else {(1. - u)/(1. - apex)}
+}
+
+fn parse_event(line: &str) -> Result<Event, String> {
+    let mut words = line.split_whitespace();
+    if words.next() != Some("at") {
+        return Err("expected \"at TIME COMMAND\"".to_string());
+    }
+    let at = match words.next().map(|w| w.parse::<f64>()) {
+        Some(Ok(at)) if at >= 0. && at.is_finite() => at,
+        Some(_) => return Err("the time must be a number, zero or more".to_string()),
+        None => return Err("expected a time after \"at\"".to_string()),
+    };
+    let command = words.next().ok_or("expected a command after the time")?;
+    let args: Vec<&str> = words.collect();
+    let action = match command {
+        "pluck" => {
+            let position = named(&args, "pos", fraction)?;
+            Action::Pluck {position, amplitude: named(&args, "amp", real)?}
+        },
+        "strike" => {
+            let position = named(&args, "pos", fraction)?;
+            Action::Strike {position, speed: named(&args, "speed", real)?}
+        },
+        "fret" => Action::Fret(named(&args, "index", index)?),
+        "unfret" => Action::Unfret(named(&args, "index", index)?),
+        "set_damping" => Action::SetDamping(only(&args, "damping", non_negative)?),
+        "set_k" => Action::SetK(only(&args, "k", positive)?),
+        "reset" => {
+            none(&args)?;
+            Action::Reset
+        },
+        "quit" => {
+            none(&args)?;
+            Action::Quit
+        },
+        _ => return Err(format!("unknown command {}, expected one of pluck, strike, fret, unfret, \
+                                 set_damping, set_k, reset, quit", command)),
+    };
+    let known: &[&str] = match action {
+        Action::Pluck {..} => &["pos", "amp"],
+        Action::Strike {..} => &["pos", "speed"],
+        Action::Fret(_) | Action::Unfret(_) => &["index"],
+        _ => &[],
+    };
+    let stray = args.iter().find(|a| a.split_once('=').is_none_or(|(name, _)| !known.contains(&name)));
+    if let (false, Some(arg)) = (known.is_empty(), stray) {
+        return Err(format!("{} doesn't take {}", command, arg));
+    }
+    Ok(Event {at, action})
+}
+
+// The value of the name=value argument, read with parse.
+fn named<T>(args: &[&str], name: &str, parse: fn(&str) -> Result<T, String>) -> Result<T, String> {
+    let value = args.iter().find_map(|a| a.split_once('=').filter(|&(n, _)| n == name).map(|(_, v)| v))
+        .ok_or_else(|| format!("expected {}=", name))?;
+    parse(value).map_err(|err| format!("{} {}", name, err))
+}
+
+// The one bare argument of a command like set_damping.
+fn only<T>(args: &[&str], what: &str, parse: fn(&str) -> Result<T, String>) -> Result<T, String> {
+    match *args {
+        [value] if !value.contains('=') => parse(value).map_err(|err| format!("{} {}", what, err)),
+        _ => Err(format!("expected just the {}", what)),
+    }
+}
+
+fn none(args: &[&str]) -> Result<(), String> {
+    match args.first() {
+        Some(arg) => Err(format!("unexpected {}", arg)),
+        None => Ok(()),
+    }
+}
+
+fn real(value: &str) -> Result<f64, String> {
+    value.parse::<f64>().ok().filter(|v| v.is_finite()).ok_or_else(|| format!("must be a number, not {}", value))
+}
+
+fn fraction(value: &str) -> Result<f64, String> {
+    real(value).and_then(|v| if v > 0. && v < 1. {Ok(v)} else {Err(format!("must be strictly between 0 and 1, not {}", v))})
+}
+
+fn non_negative(value: &str) -> Result<f64, String> {
+    real(value).and_then(|v| if v >= 0. {Ok(v)} else {Err(format!("must be zero or more, not {}", v))})
+}
+
+fn positive(value: &str) -> Result<f64, String> {
+    real(value).and_then(|v| if v > 0. {Ok(v)} else {Err(format!("must be positive, not {}", v))})
+}
+
+fn index(value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("must be a whole number, not {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> String {
+        Script::parse(text).err().expect("the script was read")
+    }
+
+    #[test]
+    fn an_unknown_command_is_refused_with_its_line() {
+        let err = error("# a pluck and then something else\nat 0 pluck pos=0.3 amp=4\n\nat 100 twang pos=0.5\n");
+        assert!(err.starts_with("line 4: unknown command twang"), "{}", err);
+    }
+
+    #[test]
+    fn a_time_that_isn_t_one_is_refused() {
+        for time in ["soon", "-5", "inf", "NaN"] {
+            let err = error(&format!("at 0 reset\nat {} quit", time));
+            assert!(err.starts_with("line 2: the time must be a number"), "at {}: {}", time, err);
+        }
+        assert!(error("at").starts_with("line 1: expected a time"));
+    }
+
+    #[test]
+    fn events_come_soonest_first_and_ties_in_the_file_s_order() {
+        let mut script = Script::parse("at 300 quit\nat 100 fret index=4\nat 0 reset\nat 100 unfret index=4\n").unwrap();
+        let at: Vec<f64> = script.events.iter().map(|e| e.at).collect();
+        assert_eq!(at, [0., 100., 100., 300.]);
+        assert_eq!(script.due(50.), [Action::Reset]);
+        assert_eq!(script.due(100.), [Action::Fret(4), Action::Unfret(4)]);
+        assert_eq!(script.next_at(), Some(300.));
+        assert_eq!(script.due(1e9), [Action::Quit]);
+        assert_eq!(script.next_at(), None);
+    }
+}
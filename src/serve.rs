@@ -0,0 +1,70 @@
+// Streams the string to anyone who connects over TCP, as one line of JSON
+// a frame: {"t": time, "positions": [[x, y], ...]}. Each client has a
+// writer thread of its own behind a short queue, and frames that find the
+// queue full are dropped, so a slow or stalled client only misses frames
+// and never holds up the string.
+use std::io::{self, BufWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use Chord;
+use serde_json;
+
+// Frames a client may fall behind by before it starts missing them.
+const QUEUE: usize = 4;
+
+type Clients = Arc<Mutex<Vec<SyncSender<Arc<String>>>>>;
+
+pub struct Server {
+    clients: Clients,
+    interval: Duration,
+    last: Option<Instant>,
+}
+
+impl Server {
+    // Listens on port of this machine alone, sending at most rate frames a
+    // second.
+    pub fn start(port: u16, rate: f64) -> io::Result<Server> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        let clients = Clients::default();
+        let accepted = clients.clone();
+        // The thread blocks in accept for as long as the program runs, so
+        // it's left to end with it.
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let (sender, frames) = mpsc::sync_channel(QUEUE);
+                thread::spawn(move || send(stream, &frames));
+                accepted.lock().unwrap().push(sender);
+            }
+        });
+        Ok(Server {clients, interval: Duration::from_secs_f64(1./rate), last: None})
+    }
+    // Sends chord to every client, unless a frame went out too recently.
+    pub fn offer(&mut self, chord: &Chord) {
+        let now = Instant::now();
+        if self.last.is_some_and(|last| now - last < self.interval) {
+            return;
+        }
+        self.last = Some(now);
+        let mut clients = self.clients.lock().unwrap();
+        if clients.is_empty() {
+            return;
+        }
+        let positions: Vec<[f64; 2]> = chord.dots().iter().map(|d| [d.pos().x, d.pos().y]).collect();
+        let frame = Arc::new(serde_json::json!({"t": chord.time(), "positions": positions}).to_string());
+        // A client that's gone has dropped its end of the queue.
+        clients.retain(|c| !matches!(c.try_send(frame.clone()), Err(TrySendError::Disconnected(_))));
+    }
+}
+
+fn send(stream: TcpStream, frames: &Receiver<Arc<String>>) {
+    let mut out = BufWriter::new(stream);
+    for frame in frames {
+        if writeln!(out, "{}", frame).and_then(|_| out.flush()).is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,133 @@
+// Building the strings the command line asks for, and looking them over
+// as they run, for the window and headless runs alike.
+use std::f64;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use {Chord, ChordStatus};
+use cli;
+use pair;
+
+// Where the pickup sits along the string.
+pub(crate) const PICKUP_POS: f64 = 0.8;
+// Ticks the decay meter takes the pickup's RMS over, about a period of the
+// default string.
+pub(crate) const DECAY_WINDOW: usize = 1<<14;
+// Periods of the fundamental a string under gravity is given to settle.
+const RELAX_PERIODS: f64 = 10.;
+// Width, as a fraction of the string's length, and height of the pulse
+// preset, and where along the string keypad 5 and --wave-speed-test start
+// it.
+const PULSE_WIDTH: f64 = 0.03;
+const PULSE_AMPLITUDE: f64 = 2.;
+const PULSE_START: f64 = 0.25;
+pub(crate) const PULSE_PRESET: usize = 4;
+
+// New string of n segments in the preset shape, picking up the timing,
+// damping and integrator of the one it replaces.
+pub(crate) fn preset(index: usize, old: &Chord) -> Chord {
+    let n = (old.dots().len()-1) as u16;
+    let mut chord = match index {
+        0 => Chord::new(n, old.k()),
+        1 => Chord::new_sine(n, old.k()),
+        2 => Chord::new_harmonic(n, old.k(), 2, 5.),
+        3 => Chord::new_strike(n, old.k(), 0.2, 0.05, 0.05),
+        _ => Chord::new_pulse(n, old.k(), PULSE_START, PULSE_WIDTH, PULSE_AMPLITUDE),
+    };
+    chord.set_dt(old.dt());
+    chord.set_damping(old.damping());
+    chord.set_damping2(old.damping2());
+    if let Some((stiffness, resistance)) = old.termination() {
+        chord.set_termination(stiffness, resistance);
+    }
+    chord.set_integrator(old.integrator());
+    chord.set_gravity(old.gravity());
+    if old.taper() != 1. {
+        chord.set_taper(old.taper());
+    }
+    if let Some((amplitude, seed)) = old.noise() {
+        chord.set_noise(amplitude, seed);
+    }
+    settle(&mut chord);
+    chord
+}
+
+// The string the command line asks for.
+fn initial_chord(options: &cli::Options) -> Chord {
+    let (n, k, u) = (options.segments, options.k, options.position);
+    let mut chord = match options.preset {
+        cli::Preset::Triangle => Chord::new(n, k),
+        cli::Preset::Sine => Chord::new_sine(n, k),
+        cli::Preset::Pluck => Chord::new_pluck_angled(n, k, u, n as f64*0.375/2., options.angle.to_radians()),
+        cli::Preset::Strike => Chord::new_strike(n, k, u, 0.05, 0.05),
+        cli::Preset::Pulse => Chord::new_pulse(n, k, u, PULSE_WIDTH, PULSE_AMPLITUDE),
+    };
+    chord.set_dt(options.dt);
+    chord.set_damping(options.damping);
+    chord.set_damping2(options.damping2);
+    if let Some((stiffness, resistance)) = options.bridge {
+        chord.set_termination(stiffness, resistance);
+    }
+    chord.set_integrator(options.integrator);
+    chord.set_gravity(options.gravity);
+    if options.taper != 1. {
+        chord.set_taper(options.taper);
+    }
+    settle(&mut chord);
+    chord.set_noise(options.noise, options.seed);
+    chord
+}
+
+// The string the command line asks for, and the one it's compared with
+// beside it if it asks for a comparison. The two are built the same way
+// but for what their sides set.
+pub(crate) fn initial_strings(options: &cli::Options) -> (Chord, Option<pair::Pair>) {
+    match options.compare {
+        Some((ref first, ref second)) => {
+            let other = initial_chord(&second.applied(options));
+            (initial_chord(&first.applied(options)), Some(pair::Pair::new(other, (first.clone(), second.clone()))))
+        },
+        None => (initial_chord(options), None),
+    }
+}
+
+// Lets a string under gravity settle into its sag. The damping is critical
+// for the fundamental, so the sag is found in a few periods.
+fn settle(chord: &mut Chord) {
+    if chord.gravity() == 0. {
+        return;
+    }
+    let frequency = chord.linear_fundamental();
+    let damping = 4.*f64::consts::PI*chord.mean_mass()*frequency;
+    chord.relax((RELAX_PERIODS/(frequency*chord.dt())) as u32, damping);
+}
+
+// Seconds since the epoch, for naming output files.
+pub(crate) fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Warns when the string's k is past what its integrator can take at its dt.
+pub(crate) fn warn_if_unstable(chord: &Chord) {
+    let limit = chord.max_stable_k(chord.dt());
+    if chord.k() > limit {
+        eprintln!("warning: k = {} is above {}, the largest the {:?} integrator is stable \
+                   for at dt = {}; the string will blow up", chord.k(), limit, chord.integrator(), chord.dt());
+    }
+}
+
+// What went wrong, for a string that's diverged.
+pub(crate) fn divergence(chord: &Chord) -> Option<String> {
+    match chord.status() {
+        ChordStatus::Stable => None,
+        ChordStatus::Diverged {ticks, time, displacement} => Some(format!(
+            "the string diverged by tick {} (time {}), reaching a displacement of {:e}, \
+             with k = {}, dt = {}, damping = {} and the {:?} integrator",
+            ticks, time, displacement, chord.k(), chord.dt(), chord.damping(), chord.integrator())),
+    }
+}
+
+// Largest displacement of any dot from the rest line.
+pub(crate) fn max_amplitude(chord: &Chord) -> f64 {
+    chord.dots().iter().fold(0., |m: f64, d| m.max(d.pos().y.abs()))
+}
+
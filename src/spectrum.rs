@@ -0,0 +1,92 @@
+use std::f64::consts::PI;
+
+// Magnitude spectrum of a signal as (frequency, magnitude) pairs from DC up
+// to Nyquist. Frequencies are in cycles per sample. The signal is Hann
+// windowed and zero-padded to a power of two; magnitudes are scaled so a
+// sinusoid of amplitude a peaks at about a.
+pub fn spectrum(samples: &[f64]) -> Vec<(f64, f64)> {
+    if samples.is_empty() {
+        return Vec::new();
+    }
+    let n = samples.len().next_power_of_two();
+    let mut re = vec![0.; n];
+    let mut im = vec![0.; n];
+    let mut weight = 0.;
+    for (i, &s) in samples.iter().enumerate() {
+        let w = hann(i, samples.len());
+        re[i] = s*w;
+        weight += w;
+    }
+    fft(&mut re, &mut im);
+    let scale = if weight > 0. {2./weight} else {0.};
+    (0..n/2 + 1).map(|k| {
+        (k as f64/n as f64, (re[k]*re[k] + im[k]*im[k]).sqrt()*scale)
+    }).collect()
+}
+
+// Frequency of the strongest non-DC peak, refined between bins by fitting a
+// parabola through it and its neighbours.
+pub fn peak_frequency(spectrum: &[(f64, f64)]) -> Option<f64> {
+    if spectrum.len() < 3 {
+        return None;
+    }
+    let mut best = 1;
+    for k in 2..spectrum.len() {
+        if spectrum[k].1 > spectrum[best].1 {
+            best = k;
+        }
+    }
+    if spectrum[best].1 == 0. {
+        return None;
+    }
+    let bin = spectrum[1].0 - spectrum[0].0;
+    if best == spectrum.len()-1 {
+        return Some(spectrum[best].0);
+    }
+    let (a, b, c) = (spectrum[best-1].1, spectrum[best].1, spectrum[best+1].1);
+    let denom = a - 2.*b + c;
+    let shift = if denom != 0. {0.5*(a - c)/denom} else {0.};
+    Some(spectrum[best].0 + shift*bin)
+}
+
+fn hann(i: usize, len: usize) -> f64 {
+    if len < 2 {
+        return 1.;
+    }
+    0.5 - 0.5*(2.*PI*i as f64/(len-1) as f64).cos()
+}
+
+// In-place iterative radix-2 FFT; the length must be a power of two.
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.*PI/len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len/2 {
+                let (wr, wi) = ((angle*k as f64).cos(), (angle*k as f64).sin());
+                let (a, b) = (start + k, start + k + len/2);
+                let tr = re[b]*wr - im[b]*wi;
+                let ti = re[b]*wi + im[b]*wr;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,76 @@
+// How a running string is doing, written to a CSV file a row at a time
+// once another so many ticks have gone by: its clock, energy, largest
+// displacement and pickup signal, where its centre of mass is and its total
+// momentum in the window's plane, and the wall-clock time that went on
+// ticking it and on drawing it since the last row. The times are only
+// added up between rows, so logging costs a file write a row and nothing a
+// tick.
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::time::Duration;
+
+use Chord;
+use csv::CsvWriter;
+
+const COLUMNS: [&str; 11] = ["time", "energy", "max_displacement", "pickup", "com_x", "com_y", "momentum_x", "momentum_y",
+                             "tick_ms", "draw_ms", "frames"];
+
+pub struct StatsLog {
+    csv: CsvWriter<BufWriter<File>>,
+    every: u64,
+    pickup: f64,
+    // Tick the next row is due at.
+    next: u64,
+    // Wall-clock time spent ticking and drawing, and frames drawn, since
+    // the last row.
+    ticking: Duration,
+    drawing: Duration,
+    frames: u64,
+}
+
+impl StatsLog {
+    // A log writing a row every every ticks to path, hearing the string at
+    // the fraction pickup along it.
+    pub fn create(path: &Path, every: u64, pickup: f64) -> io::Result<StatsLog> {
+        let columns: Vec<String> = COLUMNS.iter().map(|c| c.to_string()).collect();
+        let csv = CsvWriter::new(BufWriter::new(File::create(path)?), &columns)?;
+        Ok(StatsLog {csv, every: every.max(1), pickup, next: 0, ticking: Duration::ZERO, drawing: Duration::ZERO, frames: 0})
+    }
+    pub fn ticked(&mut self, spent: Duration) {
+        self.ticking += spent;
+    }
+    pub fn drew(&mut self, spent: Duration) {
+        self.drawing += spent;
+        self.frames += 1;
+    }
+    // Ticks until the next row is due from tick, for runs that shouldn't
+    // go past it.
+    pub fn due_in(&self, tick: u64) -> u64 {
+        self.next.saturating_sub(tick).max(1)
+    }
+    // Writes a row for chord if it's due. A string that has gone back in
+    // time, by a reset or a load, starts the count again.
+    pub fn log(&mut self, chord: &Chord) -> io::Result<()> {
+        let ticks = chord.ticks();
+        if ticks + self.every <= self.next {
+            self.next = ticks;
+        }
+        if ticks < self.next {
+            return Ok(());
+        }
+        self.next = ticks + self.every;
+        let displacement = chord.dots().iter().fold(0., |m: f64, d| m.max(d.pos().y.hypot(d.pos().z)));
+        let ms = |d: Duration| d.as_secs_f64()*1e3;
+        let (com, momentum) = (chord.center_of_mass(), chord.total_momentum());
+        let row = [chord.time(), chord.total_energy(), displacement, chord.pickup(self.pickup),
+                   com.x, com.y, momentum.x, momentum.y, ms(self.ticking), ms(self.drawing), self.frames as f64];
+        self.ticking = Duration::ZERO;
+        self.drawing = Duration::ZERO;
+        self.frames = 0;
+        self.csv.row(ticks, &row)
+    }
+    pub fn finish(self) -> io::Result<()> {
+        self.csv.finish().map(|_| ())
+    }
+}
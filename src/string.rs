@@ -0,0 +1,2099 @@
+// A string of point masses joined by springs, and everything that can be
+// done to it while it vibrates.
+use std::f64;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::Path;
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use geometry::Vect;
+use scalar::Scalar;
+use spectrum;
+
+// A dot in f64, which is what everything outside this file uses. DotOf<f32>
+// is the same dot in single precision.
+pub type Dot = DotOf<f64>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Scalar"))]
+pub struct DotOf<T> {
+    pos: Vect<T>,
+    vel: Vect<T>,
+    acc: Vect<T>,
+    prev_acc: Vect<T>,
+    mass: T,
+    fixed: bool,
+}
+
+impl<T: Scalar> DotOf<T> {
+    pub fn new(x: T, y: T, f: bool) -> DotOf<T> {
+        DotOf {
+            pos: Vect::flat(x, y),
+            vel: Vect::zero(),
+            acc: Vect::zero(),
+            prev_acc: Vect::zero(),
+            mass: T::one(),
+            fixed: f,
+        }
+    }
+    pub fn pos(&self) -> Vect<T> {
+        self.pos
+    }
+    pub fn vel(&self) -> Vect<T> {
+        self.vel
+    }
+    // Acceleration from the forces of the last tick.
+    pub fn acc(&self) -> Vect<T> {
+        self.acc
+    }
+    pub fn mass(&self) -> T {
+        self.mass
+    }
+    pub fn set_mass(&mut self, mass: T) {
+        self.mass = mass;
+    }
+    pub fn is_fixed(&self) -> bool {
+        self.fixed
+    }
+    pub(crate) fn set_pos(&mut self, pos: Vect<T>) {
+        self.pos = pos;
+    }
+    pub(crate) fn set_vel(&mut self, vel: Vect<T>) {
+        self.vel = vel;
+    }
+    pub(crate) fn move_it(&mut self, dt: T) {
+        self.pos += self.vel*dt;
+    }
+    pub(crate) fn accelerate(&mut self, dt: T) {
+        self.vel += self.acc*dt;
+    }
+    pub(crate) fn get_force(&self, pos: Vect<T>, rest_length: T) -> Vect<T> {
+        let d = pos - self.pos;
+        let len = d.size();
+        if len == T::zero() {
+            return Vect::zero();
+        }
+        d*((len - rest_length)/len)
+    }
+    pub(crate) fn set_force(&mut self, vect: Vect<T>) {
+        self.acc = vect/self.mass;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Integrator {
+    Euler,
+    Verlet,
+    Rk4,
+    // Position-based: an Euler step, then every segment put back to the
+    // length it was built with, so the string can't stretch.
+    Constrained,
+}
+
+impl Integrator {
+    pub fn next(self) -> Integrator {
+        match self {
+            Integrator::Euler => Integrator::Verlet,
+            Integrator::Verlet => Integrator::Rk4,
+            Integrator::Rk4 => Integrator::Constrained,
+            Integrator::Constrained => Integrator::Euler,
+        }
+    }
+}
+
+// How the two ends of the string are held. Free ends move and only feel
+// their single neighbour; periodic ends join up into a ring.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Boundary {
+    Fixed,
+    Free,
+    Periodic,
+}
+
+// How run keeps the dots while it ticks them. Outside run they're always
+// an array of Dots, as dots() hands them out.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layout {
+    Interleaved,
+    // One array per coordinate, so each pass streams through plain f64s.
+    Split,
+}
+
+// Whether a string is still fit to simulate.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChordStatus {
+    Stable,
+    // The dots ran off to a displacement of the given size (or to infinity
+    // or NaN) by the given tick count and time, as an explicit integrator
+    // does once k or dt is too large.
+    Diverged {ticks: u64, time: f64, displacement: f64},
+}
+
+// Periodic external force pushing one dot up and down.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Driver<T> {
+    index: usize,
+    amplitude: T,
+    frequency: T,
+}
+
+// Bow drawn across one dot. force is the most static friction the bow can
+// exert before the dot breaks away and slides.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bow<T> {
+    index: usize,
+    speed: T,
+    force: T,
+}
+
+// Extra damping on the dots from index from to the right end, as a palm
+// resting on the strings near the bridge.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Mute<T> {
+    from: usize,
+    damping: T,
+}
+
+// Extra damping on dot index alone, as a finger resting lightly on the
+// string to sound a harmonic.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Touch<T> {
+    index: usize,
+    damping: T,
+}
+
+// Point the dots are pulled toward with a force of strength over the square
+// of their distance, as an electric guitar's pickup magnet pulls on its
+// steel strings.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Scalar"))]
+pub struct Magnet<T> {
+    position: Vect<T>,
+    strength: T,
+}
+
+// Random push on every free dot across the string, new each tick, as the
+// knocks of the air's molecules give a real one. Each dot's push is drawn
+// from a normal distribution of standard deviation amplitude by hashing the
+// seed, the tick and the dot's index together, so the same seed always
+// gives the same pushes, every stage of a tick sees the same ones, and no
+// generator state has to be carried from tick to tick.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Noise<T> {
+    amplitude: T,
+    seed: u64,
+}
+
+// A setting on its way to target in a straight line, step nearer each
+// tick.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Glide<T> {
+    target: T,
+    step: T,
+}
+
+// Right-hand end held to its anchor by a spring and a dashpot in place of a
+// rigid pin, so the string's energy drains out through it as it does
+// through a guitar's bridge.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Scalar"))]
+pub struct Termination<T> {
+    anchor: Vect<T>,
+    stiffness: T,
+    resistance: T,
+}
+
+// Sinusoidal wobble of k by the fraction depth, rate times per unit of
+// simulated time. The phase is carried along rather than worked out from
+// the time so changing the rate doesn't make it jump.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Vibrato<T> {
+    depth: T,
+    rate: T,
+    phase: T,
+}
+
+// Largest bow-relative speed that still counts as sticking, and sliding
+// friction as a fraction of the static limit.
+const STICK_SPEED: f64 = 1e-4;
+const SLIDE_FRICTION: f64 = 0.4;
+
+// Ticks fundamental_frequency simulates, and where it listens. The probe
+// position avoids the nodes of the low modes.
+const PROBE_TICKS: usize = 1<<18;
+const PROBE_POS: f64 = 0.29;
+
+// Ticks between checks for divergence, and the displacement, as a multiple
+// of the string's length, past which it's taken to have diverged.
+const CHECK_EVERY: u64 = 64;
+const DIVERGED_SPANS: f64 = 1e3;
+
+// Strings with fewer dots than this are ticked on the calling thread alone;
+// below about this size handing the runs to the pool costs more than it
+// saves.
+pub const PARALLEL_THRESHOLD: usize = 20_000;
+
+// A string in f64. ChordOf<f32> ticks in single precision, which is faster
+// and halves the memory but drifts from the f64 trajectory over long runs.
+pub type Chord = ChordOf<f64>;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "T: Scalar"))]
+pub struct ChordOf<T> {
+    k: T,
+    // Spring constant k glides toward, a little every tick.
+    target_k: T,
+    dt: T,
+    // Simulated time elapsed since the string was built.
+    time: T,
+    damping: T,
+    // Where the damping is gliding to, when it's been told to glide.
+    #[serde(default)]
+    damping_glide: Option<Glide<T>>,
+    // Drag on the second difference of the velocity along the string, which
+    // falls harder on short waves than on long ones.
+    #[serde(default)]
+    damping2: T,
+    // Acceleration of every free dot down the screen, and how far below
+    // its ends the string hung at the deepest when relax last settled it.
+    #[serde(default)]
+    gravity: T,
+    #[serde(default)]
+    sag: T,
+    stiffness: T,
+    rest_length: T,
+    boundary: Boundary,
+    // Height of a rigid fretboard the string can slap against, if any.
+    barrier_y: Option<T>,
+    restitution: T,
+    driver: Option<Driver<T>>,
+    bow: Option<Bow<T>>,
+    mute: Option<Mute<T>>,
+    #[serde(default)]
+    touch: Option<Touch<T>>,
+    vibrato: Option<Vibrato<T>>,
+    #[serde(default)]
+    termination: Option<Termination<T>>,
+    #[serde(default)]
+    magnet: Option<Magnet<T>>,
+    #[serde(default)]
+    noise: Option<Noise<T>>,
+    // Multiple of k for each segment, the one to the right of each dot, the
+    // last used only to close a periodic ring; empty for a uniform string.
+    #[serde(default)]
+    k_profile: Vec<T>,
+    // Length of each segment as the string was built, indexed the same way,
+    // which the constrained integrator holds them to.
+    #[serde(default)]
+    lengths: Vec<T>,
+    // Offset from the last dot's position to the first one's image on the
+    // ring, used only by periodic strings.
+    period: Vect<T>,
+    integrator: Integrator,
+    // Dots from which a tick is split across the cores. It's a setting of
+    // this process rather than of the string, so it isn't saved.
+    #[serde(skip, default = "parallel_threshold")]
+    parallel_threshold: usize,
+    #[serde(skip, default = "interleaved")]
+    layout: Layout,
+    // Buffers a tick reuses rather than allocating afresh.
+    #[serde(skip)]
+    scratch: Scratch<T>,
+    // Ticks since the string was built or reset, and what the last check
+    // for divergence found.
+    #[serde(skip)]
+    ticks: u64,
+    #[serde(skip, default = "stable")]
+    status: ChordStatus,
+    chord: Vec<DotOf<T>>,
+    // The dots as the string was built, for reset.
+    initial: Vec<DotOf<T>>,
+}
+
+// Distance inside which a magnet's pull stops growing and falls away to
+// nothing at its centre, as inside a uniform ball, so a dot passing right
+// over it isn't flung off.
+const MAGNET_CORE: f64 = 1.;
+
+// Damping of a touch, as a multiple of the string's impedance. A dashpot on
+// one dot meets the waves from both sides, so twice the impedance is the
+// most it can soak up from a wave passing it.
+const TOUCH_DAMPING: f64 = 2.;
+
+// Ticks over which k covers most of the way to target_k, and how near it
+// must get, relative to the target, before it's snapped there.
+const K_SLEW_TICKS: f64 = 300.;
+const K_SNAP: f64 = 1e-9;
+
+// Passes of the constrained integrator over the segments each tick.
+const CONSTRAINT_PASSES: u32 = 8;
+
+// Springs rest at this fraction of the initial dot spacing. A rest length of
+// the full spacing would leave the straight string without tension, and so
+// without any linear restoring force for transverse motion.
+pub(crate) const REST_FRACTION: f64 = 0.5;
+
+impl<T: Scalar> ChordOf<T> {
+    fn from_dots(ds: Vec<DotOf<T>>, k: T) -> ChordOf<T> {
+        let spacing = (ds[ds.len()-1].pos.x - ds[0].pos.x)/T::of((ds.len()-1) as f64);
+        let period = Vect::flat(spacing*T::of(ds.len() as f64), T::zero());
+        ChordOf {
+            k,
+            target_k: k,
+            dt: T::one(),
+            time: T::zero(),
+            damping: T::zero(),
+            damping_glide: None,
+            damping2: T::zero(),
+            gravity: T::zero(),
+            sag: T::zero(),
+            stiffness: T::zero(),
+            rest_length: spacing*T::of(REST_FRACTION),
+            boundary: Boundary::Fixed,
+            barrier_y: None,
+            restitution: T::of(0.5),
+            driver: None,
+            bow: None,
+            mute: None,
+            touch: None,
+            vibrato: None,
+            termination: None,
+            magnet: None,
+            noise: None,
+            k_profile: Vec::new(),
+            lengths: segment_lengths(&ds, period),
+            period,
+            integrator: Integrator::Euler,
+            parallel_threshold: PARALLEL_THRESHOLD,
+            layout: Layout::Interleaved,
+            scratch: Scratch::default(),
+            ticks: 0,
+            status: ChordStatus::Stable,
+            initial: ds.clone(),
+            chord: ds,
+        }
+    }
+    pub fn new(n: u16, k: T) -> ChordOf<T> {
+        ChordOf::new_pluck(n, k, T::of(0.5), T::of(n as f64*0.375/2.))
+    }
+    // String of n segments whose free dots start at height shape(x), with x
+    // running from 0 to n along the string.
+    pub fn from_shape(n: u16, k: T, shape: impl Fn(T) -> T) -> ChordOf<T> {
+        let mut ds = Vec::with_capacity(n as usize + 1);
+        ds.push(DotOf::new(T::zero(), T::zero(), true));
+        for i in 1..n {
+            ds.push(DotOf::new(T::of(i as f64), shape(T::of(i as f64)), false));
+        }
+        ds.push(DotOf::new(T::of(n as f64), T::zero(), true));
+        ChordOf::from_dots(ds, k)
+    }
+    // String whose dot i starts at height samples[i], and optionally moving
+    // at velocities[i]. The ends are pinned at rest whatever the input says.
+    pub fn from_samples(samples: &[T], velocities: Option<&[T]>, k: T) -> ChordOf<T> {
+        assert!(samples.len() >= 3, "a string needs at least 3 samples");
+        if let Some(v) = velocities {
+            assert!(v.len() == samples.len(), "velocities must match samples in length");
+        }
+        let last = samples.len()-1;
+        let ds = samples.iter().enumerate().map(|(i, &y)| {
+            let fixed = i == 0 || i == last;
+            let mut dot = DotOf::new(T::of(i as f64), if fixed {T::zero()} else {y}, fixed);
+            if let (false, Some(v)) = (fixed, velocities) {
+                dot.vel.y = v[i];
+            }
+            dot
+        }).collect();
+        ChordOf::from_dots(ds, k)
+    }
+    // String at rest through the given dot positions, its first and last
+    // dots pinned where they are.
+    pub fn from_positions(positions: &[Vect<T>], k: T) -> ChordOf<T> {
+        assert!(positions.len() >= 3, "a string needs at least 3 dots");
+        let last = positions.len()-1;
+        let ds = positions.iter().enumerate().map(|(i, &p)| {
+            let mut dot = DotOf::new(p.x, p.y, i == 0 || i == last);
+            dot.pos.z = p.z;
+            dot
+        }).collect();
+        ChordOf::from_dots(ds, k)
+    }
+    // Triangular pluck with its apex at the fraction pluck_pos of the way
+    // along the string, raised to amplitude.
+    pub fn new_pluck(n: u16, k: T, pluck_pos: T, amplitude: T) -> ChordOf<T> {
+        assert!(pluck_pos > T::zero() && pluck_pos < T::one(), "pluck position must be in (0, 1)");
+        let apex = pluck_pos*T::of(n as f64);
+        ChordOf::from_shape(n, k, |x| {
+            if x <= apex {
+                amplitude*x/apex
+            } else {
+                amplitude*(T::of(n as f64) - x)/(T::of(n as f64) - apex)
+            }
+        })
+    }
+    // The same pluck pulled out at angle radians to the plane the window
+    // shows, so the string swings in both planes at once.
+    pub fn new_pluck_angled(n: u16, k: T, pluck_pos: T, amplitude: T, angle: T) -> ChordOf<T> {
+        let mut chord = ChordOf::new_pluck(n, k, pluck_pos, amplitude);
+        let (sin, cos) = angle.sin_cos();
+        for dot in chord.chord.iter_mut() {
+            let r = dot.pos.y;
+            dot.pos.y = r*cos;
+            dot.pos.z = r*sin;
+        }
+        chord.initial = chord.chord.clone();
+        chord
+    }
+    // The triangle of new, on a string whose dots get heavier from the
+    // first end to the other, mass_ratio times as heavy at the far end.
+    pub fn new_tapered(n: u16, k: T, mass_ratio: T) -> ChordOf<T> {
+        let mut chord = ChordOf::new(n, k);
+        chord.set_taper(mass_ratio);
+        chord
+    }
+    pub fn new_sine(n: u16, k: T) -> ChordOf<T> {
+        ChordOf::new_harmonic(n, k, 1, T::of(5.))
+    }
+    // Standing wave of the given mode, with mode - 1 nodes between the ends.
+    pub fn new_harmonic(n: u16, k: T, mode: u16, amplitude: T) -> ChordOf<T> {
+        ChordOf::from_shape(n, k, |x| {
+            (T::of(mode as f64)*T::PI()*x/T::of(n as f64)).sin()*amplitude
+        })
+    }
+    // Hammer blow: the string starts flat and the dots within width (as a
+    // fraction of the length) of strike_pos get a raised-cosine kick of
+    // peak velocity.
+    pub fn new_strike(n: u16, k: T, strike_pos: T, width: T, velocity: T) -> ChordOf<T> {
+        let mut chord = ChordOf::from_shape(n, k, |_| T::zero());
+        chord.excite_velocity(|u| {
+            let d = (u - strike_pos).abs();
+            if d < width {
+                velocity*T::of(0.5)*(T::one() + (T::PI()*d/width).cos())
+            } else {
+                T::zero()
+            }
+        });
+        chord.initial = chord.chord.clone();
+        chord
+    }
+    // Gaussian bump of peak amplitude, centred at the fraction center of
+    // the way along with a standard deviation of width times the length,
+    // already moving toward the far end at the wave speed. Let go from rest
+    // it would split into two of half the height, going either way.
+    pub fn new_pulse(n: u16, k: T, center: T, width: T, amplitude: T) -> ChordOf<T> {
+        let (c, w) = (center*T::of(n as f64), width*T::of(n as f64));
+        let mut chord = ChordOf::from_shape(n, k, |x| amplitude*(-(x - c)*(x - c)/(T::of(2.)*w*w)).exp());
+        let speed = chord.wave_speed();
+        // A shape y(x - vt) moves at -v·dy/dx.
+        for dot in chord.chord.iter_mut() {
+            if dot.fixed {continue;}
+            dot.vel.y = speed*(dot.pos.x - c)/(w*w)*dot.pos.y;
+        }
+        chord.initial = chord.chord.clone();
+        chord
+    }
+    pub fn dots(&self) -> &[DotOf<T>] {
+        &self.chord
+    }
+    pub fn k(&self) -> T {
+        self.k
+    }
+    pub fn target_k(&self) -> T {
+        self.target_k
+    }
+    pub fn dt(&self) -> T {
+        self.dt
+    }
+    pub fn time(&self) -> T {
+        self.time
+    }
+    pub fn damping(&self) -> T {
+        self.damping
+    }
+    // Damping the string is gliding to, or its damping when it isn't.
+    pub fn target_damping(&self) -> T {
+        self.damping_glide.as_ref().map_or(self.damping, |g| g.target)
+    }
+    pub fn damping2(&self) -> T {
+        self.damping2
+    }
+    pub fn gravity(&self) -> T {
+        self.gravity
+    }
+    pub fn sag(&self) -> T {
+        self.sag
+    }
+    pub fn stiffness(&self) -> T {
+        self.stiffness
+    }
+    pub fn rest_length(&self) -> T {
+        self.rest_length
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    // Fraction of its speed a dot keeps bouncing off the barrier.
+    pub fn restitution(&self) -> T {
+        self.restitution
+    }
+    // The dots as the string was built, which reset goes back to.
+    pub fn initial_dots(&self) -> &[DotOf<T>] {
+        &self.initial
+    }
+    // Height of the fretboard, if there is one.
+    pub fn barrier(&self) -> Option<T> {
+        self.barrier_y
+    }
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+    pub fn is_driven(&self) -> bool {
+        self.driver.is_some()
+    }
+    pub fn is_bowed(&self) -> bool {
+        self.bow.is_some()
+    }
+    // Stiffness and resistance of the lossy right-hand end, if it has one.
+    pub fn termination(&self) -> Option<(T, T)> {
+        self.termination.as_ref().map(|t| (t.stiffness, t.resistance))
+    }
+    pub fn parallel_threshold(&self) -> usize {
+        self.parallel_threshold
+    }
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+    pub fn status(&self) -> ChordStatus {
+        self.status
+    }
+    // Ticks since the string was built, loaded or reset.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    // Largest k at which ticks of dt stay stable. The stiffest motion is
+    // the highest longitudinal mode, where neighbouring dots swing against
+    // each other at ω = 2√(k/m): the leapfrog-type integrators need ω·dt
+    // under 2, and RK4 under 2√2. Damping, stiffness and large amplitudes
+    // shift the real limit a little, so stay well clear of it.
+    pub fn max_stable_k(&self, dt: T) -> T {
+        let mass = self.chord.iter().filter(|d| !d.fixed).map(|d| d.mass).fold(T::infinity(), T::min);
+        let limit = match self.integrator {
+            Integrator::Rk4 => T::of(2.)*T::SQRT_2(),
+            _ => T::of(2.),
+        };
+        // The stiffest segment goes first.
+        let stiffest = self.k_profile.iter().cloned().reduce(T::max).unwrap_or(T::one());
+        mass*(limit/(T::of(2.)*dt)).powi(2)/stiffest
+    }
+    pub fn is_muted(&self) -> bool {
+        self.mute.is_some()
+    }
+    // Number of dots from which ticks run on every core; 0 always splits
+    // them and usize::MAX never does. Either way the results are the same to
+    // the bit, since each dot's sums are done in the same order.
+    pub fn set_parallel_threshold(&mut self, dots: usize) {
+        self.parallel_threshold = dots;
+    }
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+    pub fn set_dt(&mut self, dt: T) {
+        self.dt = dt;
+    }
+    pub fn set_damping(&mut self, damping: T) {
+        self.damping = damping;
+        self.damping_glide = None;
+    }
+    // Has the damping glide to damping over K_SLEW_TICKS ticks rather than
+    // jump there.
+    pub fn set_target_damping(&mut self, damping: T) {
+        let step = (damping - self.damping)/T::of(K_SLEW_TICKS);
+        self.damping_glide = if step == T::zero() {None} else {Some(Glide {target: damping, step})};
+    }
+    // Adds damping2 times v[i-1] - 2v[i] + v[i+1] to the force on each dot,
+    // on top of the uniform damping. A sine mode of m half waves on N
+    // segments then dies as if it had 4·damping2·sin²(mπ/2N) more uniform
+    // damping, so the high partials go first.
+    pub fn set_damping2(&mut self, damping2: T) {
+        self.damping2 = damping2;
+    }
+    // Pulls every free dot down the screen with acceleration gravity, or up
+    // for a negative one. The string hangs in a shallow catenary under it,
+    // which relax finds.
+    pub fn set_gravity(&mut self, gravity: T) {
+        self.gravity = gravity;
+        self.refresh();
+    }
+    // Lets the string settle into the shape it hangs in, then puts back on
+    // top of that whatever displacement from the straight line between its
+    // ends, and whatever velocity, it had, so a plucked string is plucked
+    // about its sag rather than about the straight line. The settling runs
+    // iterations ticks from the straight line at rest with damping in place
+    // of the string's own. The settled string is the one reset returns to,
+    // and the clock starts again from it.
+    pub fn relax(&mut self, iterations: u32, damping: T) {
+        let line = self.straight_line();
+        let shape: Vec<(Vect<T>, Vect<T>)> = self.chord.iter().zip(&line)
+            .map(|(d, &p)| (d.pos - p, d.vel))
+            .collect();
+        for (dot, &p) in self.chord.iter_mut().zip(&line) {
+            if !dot.fixed {
+                dot.pos = p;
+                dot.vel = Vect::zero();
+            }
+        }
+        let own = self.damping;
+        self.damping = damping;
+        self.refresh();
+        for _ in 0..iterations {
+            self.tick();
+        }
+        self.damping = own;
+        self.sag = self.chord.iter().zip(&line)
+            .map(|(d, p)| d.pos.y - p.y)
+            .fold(T::zero(), |deepest, y| deepest.max(y));
+        for (dot, &(offset, vel)) in self.chord.iter_mut().zip(&shape) {
+            if !dot.fixed {
+                dot.pos += offset;
+                dot.vel = vel;
+            }
+        }
+        self.initial = self.chord.clone();
+        self.time = T::zero();
+        self.ticks = 0;
+        self.status = ChordStatus::Stable;
+        self.refresh();
+    }
+    // Where each dot would be on the straight line between the ends.
+    fn straight_line(&self) -> Vec<Vect<T>> {
+        let (left, right) = (self.chord[0].pos, self.chord[self.chord.len()-1].pos);
+        let last = T::of((self.chord.len()-1) as f64);
+        (0..self.chord.len()).map(|i| left + (right - left)*(T::of(i as f64)/last)).collect()
+    }
+    pub fn set_stiffness(&mut self, stiffness: T) {
+        self.stiffness = stiffness;
+    }
+    pub fn set_rest_length(&mut self, rest_length: T) {
+        self.rest_length = rest_length;
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.clear_termination();
+        let last = self.chord.len()-1;
+        let span = self.chord[last].pos.x - self.chord[0].pos.x;
+        self.boundary = boundary;
+        self.period = Vect::flat(span + span/T::of(last as f64), T::zero());
+        self.lengths[last] = (self.initial[0].pos + self.period - self.initial[last].pos).size();
+        // The ends stay as set across reset too.
+        for dots in [&mut self.chord, &mut self.initial] {
+            dots[0].fixed = boundary == Boundary::Fixed;
+            dots[last].fixed = boundary == Boundary::Fixed;
+        }
+    }
+    pub fn set_barrier(&mut self, barrier_y: Option<T>) {
+        self.barrier_y = barrier_y;
+    }
+    // Drives dot index with amplitude·sin(2π·frequency·t); frequency is in
+    // cycles per unit of simulated time. Out-of-range indices are ignored.
+    pub fn set_driver(&mut self, index: usize, amplitude: T, frequency: T) {
+        if index >= self.chord.len() {return;}
+        self.driver = Some(Driver {index, amplitude, frequency});
+    }
+    pub fn clear_driver(&mut self) {
+        self.driver = None;
+    }
+    // Bows the string at the fraction pos of its length, moving at speed.
+    pub fn set_bow(&mut self, pos: T, speed: T, force: T) {
+        let index = self.index_at(pos);
+        self.bow = Some(Bow {index, speed, force});
+    }
+    // Sets the spring constant k glides to over the next few hundred ticks,
+    // rather than jumping and jolting the string.
+    pub fn set_target_k(&mut self, k: T) {
+        self.target_k = k;
+    }
+    // Wobbles k by the fraction depth around its value, rate times per unit
+    // of simulated time, carrying on from the current phase. Depth 0 turns
+    // vibrato off. Rates well below the string's frequency leave its
+    // average energy steady even at depth 0.5, but wobbling near twice its
+    // frequency pumps energy in (parametric resonance) at any depth.
+    pub fn set_vibrato(&mut self, depth: T, rate: T) {
+        if depth == T::zero() {
+            self.vibrato = None;
+            return;
+        }
+        let phase = self.vibrato.as_ref().map_or(T::zero(), |v| v.phase);
+        self.vibrato = Some(Vibrato {depth, rate, phase});
+    }
+    pub fn clear_bow(&mut self) {
+        self.bow = None;
+    }
+    // Lets the right-hand end of a string with fixed ends go, holding it to
+    // where it was pinned by a spring of stiffness and a dashpot of
+    // resistance, in force per unit of displacement and of velocity. The end
+    // moves only across the string. With little stiffness, the more
+    // resistance the faster the string dies up to about its impedance, past
+    // which the dashpot holds the end nearly still and takes less; a stiffer
+    // spring holds it still too. An infinite stiffness keeps the end pinned
+    // exactly, as it was. Like the string's own
+    // springs, a stiffness past about 4·mass/dt² or a resistance past
+    // 2·mass/dt makes the explicit integrators blow up.
+    pub fn set_termination(&mut self, stiffness: T, resistance: T) {
+        self.clear_termination();
+        if self.boundary != Boundary::Fixed || stiffness.is_infinite() {return;}
+        let last = self.chord.len()-1;
+        let anchor = self.chord[last].pos;
+        self.termination = Some(Termination {anchor, stiffness, resistance});
+        self.chord[last].fixed = false;
+        self.refresh();
+    }
+    // Pins the right-hand end back at its anchor.
+    pub fn clear_termination(&mut self) {
+        if let Some(t) = self.termination.take() {
+            let last = self.chord.len()-1;
+            let end = &mut self.chord[last];
+            end.fixed = true;
+            end.pos = t.anchor;
+            end.vel = Vect::zero();
+            self.refresh();
+        }
+    }
+    pub fn set_restitution(&mut self, restitution: T) {
+        self.restitution = restitution;
+    }
+    // Damps the part of the string beyond the fraction from of its length
+    // with the extra damping given, until cleared.
+    pub fn set_mute(&mut self, from: T, damping: T) {
+        let from = self.index_at(from);
+        self.mute = Some(Mute {from, damping});
+    }
+    pub fn clear_mute(&mut self) {
+        self.mute = None;
+    }
+    // Rests a finger lightly on the dot nearest the fraction position along
+    // the string, damping that dot hard until released. Unlike a fret it
+    // leaves the dot free, so the modes with a node there, the harmonics of
+    // a touch at 1/2 or 1/3, ring on while the rest die away.
+    pub fn touch(&mut self, position: T) {
+        let index = self.index_at(position);
+        if index == 0 || index >= self.chord.len()-1 {
+            self.touch = None;
+            return;
+        }
+        let damping = T::of(TOUCH_DAMPING)*self.impedance();
+        self.touch = Some(Touch {index, damping});
+    }
+    // Puts a magnet offset across the string from the point the fraction
+    // position along it, pulling every dot toward it with strength over the
+    // square of the distance. A positive offset is below the string as the
+    // window draws it.
+    pub fn set_magnet(&mut self, position: T, offset: T, strength: T) {
+        let (left, right) = (self.chord[0].pos, self.chord[self.chord.len()-1].pos);
+        let along = left + (right - left)*position.clamp(T::zero(), T::one());
+        let position = Vect {x: along.x, y: along.y + offset, z: along.z};
+        self.magnet = Some(Magnet {position, strength});
+    }
+    pub fn clear_magnet(&mut self) {
+        self.magnet = None;
+    }
+    // Where the magnet is, if there is one.
+    pub fn magnet(&self) -> Option<Vect<T>> {
+        self.magnet.as_ref().map(|m| m.position)
+    }
+    // Pushes every free dot across the string at random each tick, the
+    // pushes standing amplitude apart and following from seed. An amplitude
+    // of 0 turns the noise off, leaving the string exactly as it was
+    // without.
+    pub fn set_noise(&mut self, amplitude: T, seed: u64) {
+        self.noise = if amplitude == T::zero() {None} else {Some(Noise {amplitude, seed})};
+    }
+    // The noise's amplitude and seed, if there is any.
+    pub fn noise(&self) -> Option<(T, u64)> {
+        self.noise.as_ref().map(|n| (n.amplitude, n.seed))
+    }
+    pub fn release_touch(&mut self) {
+        self.touch = None;
+    }
+    // Dot being touched, if any.
+    pub fn touched(&self) -> Option<usize> {
+        self.touch.as_ref().map(|t| t.index)
+    }
+    // Drag on dot i per unit of its velocity: the string's damping, plus a
+    // mute's beyond where it starts and a touch's on the dot touched.
+    fn damping_at(&self, i: usize) -> T {
+        let mut damping = match self.mute {
+            Some(ref m) if i >= m.from => self.damping + m.damping,
+            _ => self.damping,
+        };
+        if let Some(ref t) = self.touch {
+            if t.index == i {
+                damping += t.damping;
+            }
+        }
+        damping
+    }
+    // Makes the string stiffer or softer along its length, as a partly
+    // wound string or a splice is: each segment pulls with k times f of the
+    // fraction of the way along the string its middle is. f must be
+    // positive wherever it's asked. A straight string only stays put with
+    // the same tension in every segment, so the dots, now and as reset
+    // puts them, are moved along it until every segment has, the stiff
+    // ones ending up shorter; each keeps its displacement from the line.
+    pub fn set_k_profile(&mut self, f: impl Fn(T) -> T) {
+        let segments = T::of((self.chord.len()-1) as f64);
+        let profile = (0..self.chord.len()).map(|s| f((T::of(s as f64) + T::of(0.5))/segments)).collect();
+        self.lay_out(profile);
+    }
+    pub fn clear_k_profile(&mut self) {
+        self.lay_out(Vec::new());
+    }
+    // Switches to profile, moving the dots from where even tension has them
+    // under the old one to where it has them under the new.
+    fn lay_out(&mut self, profile: Vec<T>) {
+        let periodic = self.boundary == Boundary::Periodic;
+        let period = self.period;
+        let length = if periodic {period.size()} else {(self.chord[self.chord.len()-1].pos - self.chord[0].pos).size()};
+        let before = self.even_tension(&self.k_profile, length);
+        let after = self.even_tension(&profile, length);
+        self.k_profile = profile;
+        for dots in [&mut self.chord, &mut self.initial] {
+            let left = dots[0].pos;
+            let span = if periodic {period} else {dots[dots.len()-1].pos - left};
+            for (dot, (&b, &a)) in dots.iter_mut().zip(before.iter().zip(&after)) {
+                dot.pos += span*(a - b);
+            }
+        }
+        self.lengths = segment_lengths(&self.initial, period);
+        self.refresh();
+    }
+    // How far along a string of length, as a fraction, each dot is when
+    // every segment under profile carries the same tension.
+    fn even_tension(&self, profile: &[T], length: T) -> Vec<T> {
+        let n = self.chord.len();
+        let segments = if self.boundary == Boundary::Periodic {n} else {n-1};
+        let scale = |s: usize| if profile.is_empty() {T::one()} else {profile[s]};
+        if (0..segments).all(|s| scale(s) == scale(0)) {
+            return (0..n).map(|i| T::of(i as f64)/T::of(segments as f64)).collect();
+        }
+        // Each segment is the rest length plus tension/k, all adding up to
+        // the length of the string.
+        let give: T = (0..segments).map(|s| T::one()/scale(s)).sum();
+        let tension = (length - self.rest_length*T::of(segments as f64))/give;
+        let mut along = T::zero();
+        let mut fractions = Vec::with_capacity(n);
+        for i in 0..n {
+            fractions.push(along/length);
+            if i < segments {
+                along += self.rest_length + tension/scale(i);
+            }
+        }
+        fractions
+    }
+    // Multiple of k of each segment, empty if the string is uniform.
+    pub fn k_profile(&self) -> &[T] {
+        &self.k_profile
+    }
+    // Multiple of k of segment s, the one from dot s to dot s+1, wrapping
+    // round the ring the way neighbor does.
+    fn segment_scale(&self, s: isize) -> T {
+        if self.k_profile.is_empty() {
+            T::one()
+        } else {
+            self.k_profile[s.rem_euclid(self.k_profile.len() as isize) as usize]
+        }
+    }
+    // Gives dot i mass f(i), for reset as well.
+    pub fn set_mass_profile(&mut self, f: impl Fn(usize) -> T) {
+        for dots in [&mut self.chord, &mut self.initial] {
+            for (i, dot) in dots.iter_mut().enumerate() {
+                dot.mass = f(i);
+            }
+        }
+        self.refresh();
+    }
+    // Spreads the mass linearly along the string, from 1 at the first dot
+    // to mass_ratio at the last, for reset as well; a ratio of 1 is a
+    // uniform string.
+    pub fn set_taper(&mut self, mass_ratio: T) {
+        let last = T::of((self.chord.len()-1) as f64);
+        self.set_mass_profile(|i| T::one() + (mass_ratio - T::one())*T::of(i as f64)/last);
+    }
+    // The mass of the last dot over the first's, which set_taper sets.
+    pub fn taper(&self) -> T {
+        self.chord[self.chord.len()-1].mass/self.chord[0].mass
+    }
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        self.integrator = integrator;
+        self.refresh();
+    }
+    // Adds shape(u) to the displacement of every free dot, u running from 0
+    // at the first dot to 1 at the last.
+    pub fn excite(&mut self, shape: impl Fn(T) -> T) {
+        let last = T::of((self.chord.len()-1) as f64);
+        for (i, dot) in self.chord.iter_mut().enumerate() {
+            if dot.fixed {continue;}
+            dot.pos.y += shape(T::of(i as f64)/last);
+        }
+        self.refresh();
+    }
+    // Like excite, but for a struck string: adds shape(u) to the velocity
+    // and leaves the displacement alone.
+    pub fn excite_velocity(&mut self, shape: impl Fn(T) -> T) {
+        let last = T::of((self.chord.len()-1) as f64);
+        for (i, dot) in self.chord.iter_mut().enumerate() {
+            if dot.fixed {continue;}
+            dot.vel.y += shape(T::of(i as f64)/last);
+        }
+        self.refresh();
+    }
+    // Turns every dot's velocity round, so a time-reversible integrator
+    // runs the motion back the way it came. Verlet gets back to within
+    // rounding; the semi-implicit Euler step isn't reversible, and on the
+    // default string is billions of times further out after a thousand
+    // ticks each way.
+    pub fn reverse_velocities(&mut self) {
+        for dot in self.chord.iter_mut() {
+            dot.vel = -dot.vel;
+        }
+        self.refresh();
+    }
+    // Puts every dot back where and how it started and restarts the clock.
+    // Settings such as damping, the driver and the bow are kept.
+    pub fn reset(&mut self) {
+        self.chord = self.initial.clone();
+        if self.termination.is_some() {
+            let last = self.chord.len()-1;
+            self.chord[last].fixed = false;
+        }
+        self.time = T::zero();
+        self.ticks = 0;
+        self.status = ChordStatus::Stable;
+        self.refresh();
+    }
+    // Takes hold of a free dot so it stays wherever drag_to puts it instead
+    // of following the forces on it. Fails for fixed dots and bad indices.
+    pub fn grab(&mut self, index: usize) -> bool {
+        match self.chord.get_mut(index) {
+            Some(dot) if !dot.fixed => {
+                dot.fixed = true;
+                dot.vel = Vect::zero();
+                true
+            },
+            _ => false,
+        }
+    }
+    // Moves a grabbed dot to height y; it keeps its place along the string.
+    // Out-of-range indices are ignored.
+    pub fn drag_to(&mut self, index: usize, y: T) {
+        if let Some(dot) = self.chord.get_mut(index) {
+            dot.pos.y = y;
+        }
+    }
+    // Lets go of a grabbed dot from rest, so the string twangs from its
+    // dragged shape. Out-of-range indices are ignored.
+    pub fn release(&mut self, index: usize) {
+        if let Some(dot) = self.chord.get_mut(index) {
+            dot.fixed = false;
+            dot.vel = Vect::zero();
+            self.refresh();
+        }
+    }
+    // Pins an interior dot to the axis, shortening the vibrating length.
+    // Endpoints and out-of-range indices are ignored.
+    pub fn fret(&mut self, index: usize) {
+        if index == 0 || index >= self.chord.len()-1 {return;}
+        let dot = &mut self.chord[index];
+        dot.fixed = true;
+        dot.pos.y = T::zero();
+        dot.vel = Vect::zero();
+        self.refresh();
+    }
+    pub fn unfret(&mut self, index: usize) {
+        if index == 0 || index >= self.chord.len()-1 {return;}
+        self.chord[index].fixed = false;
+    }
+    // Displacement at the fraction position along the string, interpolated
+    // linearly between the two nearest dots.
+    pub fn pickup(&self, position: T) -> T {
+        let last = self.chord.len()-1;
+        let u = position.clamp(T::zero(), T::one())*T::of(last as f64);
+        let i = (u.floor().as_f64() as usize).min(last-1);
+        let t = u - T::of(i as f64);
+        self.chord[i].pos.y*(T::one()-t) + self.chord[i+1].pos.y*t
+    }
+    // Strongest frequency in the motion from the current state on, found by
+    // running a copy of the string. sample_rate is ticks per second and
+    // sets the unit of the result.
+    pub fn fundamental_frequency(&self, sample_rate: T) -> Option<T> {
+        let mut probe = self.clone();
+        let mut samples = Vec::with_capacity(PROBE_TICKS);
+        for _ in 0..PROBE_TICKS {
+            probe.tick();
+            samples.push(probe.pickup(T::of(PROBE_POS)).as_f64());
+        }
+        spectrum::peak_frequency(&spectrum::spectrum(&samples)).map(|f| T::of(f*sample_rate.as_f64()))
+    }
+    // Fundamental of the linearised string in cycles per unit of simulated
+    // time, from the dispersion relation of the discrete string. Damping,
+    // stiffness and the other extras are left out; so is the amplitude
+    // dependence of large plucks.
+    pub fn linear_fundamental(&self) -> T {
+        let two = T::of(2.);
+        let omega = (self.transverse_stiffness()/self.mean_mass()).sqrt()*two*self.mode_sine();
+        self.heard(omega)
+    }
+    // Cycles per unit of simulated time a mode of angular frequency omega
+    // rings at under the integrator.
+    fn heard(&self, omega: T) -> T {
+        let two = T::of(2.);
+        match self.integrator {
+            Integrator::Rk4 => omega/(two*T::PI()),
+            // Leapfrog-type steps sound at the ω' with sin(ω'dt/2) = ωdt/2.
+            _ => (omega*self.dt/two).min(T::one()).asin()*two/self.dt/(two*T::PI()),
+        }
+    }
+    // Frequencies of the first count modes of the linearised string, lowest
+    // first, in the same units as linear_fundamental, as it is between
+    // its two ends held still: the straight string's segments and masses,
+    // the k profile and the dots' own masses all included, the extras
+    // linear_fundamental leaves out left out here too. Unlike the sine
+    // modes, these are the string's own however its mass is spread, so a
+    // tapered string's overtones show how far off harmonic they go. Fewer
+    // than count if the string has fewer free dots.
+    pub fn mode_frequencies(&self, count: usize) -> Vec<T> {
+        let dots = &self.chord;
+        let n = dots.len();
+        let length = (dots[n-1].pos - dots[0].pos).size();
+        let along = self.even_tension(&self.k_profile, length);
+        let k = self.k;
+        // Force across the string per unit displacement of each segment.
+        let pull: Vec<T> = (0..n-1).map(|s| {
+            let len = length*(along[s+1] - along[s]);
+            k*self.segment_scale(s as isize)*(len - self.rest_length)/len
+        }).collect();
+        // The symmetric tridiagonal M^-1/2 K M^-1/2 over the inner dots,
+        // whose eigenvalues are the modes' ω².
+        let inner = n-2;
+        let diagonal: Vec<T> = (1..n-1).map(|i| (pull[i-1] + pull[i])/dots[i].mass).collect();
+        let off: Vec<T> = (1..n-2).map(|i| pull[i]/(dots[i].mass*dots[i+1].mass).sqrt()).collect();
+        // Eigenvalues below x, counted from the signs in the LDLᵀ
+        // factorisation of the matrix less x.
+        let below = |x: T| {
+            let mut count = 0;
+            let mut d = T::one();
+            for i in 0..inner {
+                let coupling = if i == 0 {T::zero()} else {off[i-1]*off[i-1]/d};
+                d = diagonal[i] - x - coupling;
+                if d == T::zero() {
+                    d = T::min_positive_value();
+                }
+                if d < T::zero() {
+                    count += 1;
+                }
+            }
+            count
+        };
+        // Every eigenvalue is within the largest row sum.
+        let top = (0..inner).map(|i| {
+            diagonal[i] + if i > 0 {off[i-1]} else {T::zero()} + if i + 1 < inner {off[i]} else {T::zero()}
+        }).fold(T::zero(), T::max);
+        (0..count.min(inner)).map(|m| {
+            let (mut lo, mut hi) = (T::zero(), top);
+            for _ in 0..100 {
+                let mid = (lo + hi)*T::of(0.5);
+                if below(mid) > m {hi = mid} else {lo = mid}
+            }
+            self.heard(((lo + hi)*T::of(0.5)).sqrt())
+        }).collect()
+    }
+    // Mean time between rising zero crossings of dot index over cycles
+    // cycles, timed on a copy of the string with the crossings found to
+    // within a tick by interpolation. None if the dot doesn't get through
+    // them within twice the time linear_fundamental says they take.
+    pub fn crossing_period(&self, index: usize, cycles: usize) -> Option<T> {
+        let mut probe = self.clone();
+        let limit = T::of(2.)*T::of((cycles + 1) as f64)/(self.linear_fundamental()*self.dt);
+        let mut first = None;
+        let mut crossings = 0;
+        let mut last = probe.chord[index].pos.y;
+        for _ in 0..limit.as_f64().min(u32::MAX as f64) as u32 {
+            probe.tick();
+            let y = probe.chord[index].pos.y;
+            if last < T::zero() && y >= T::zero() {
+                let t = probe.time - probe.dt*y/(y - last);
+                match first {
+                    None => first = Some(t),
+                    Some(start) => {
+                        crossings += 1;
+                        if crossings == cycles {
+                            return Some((t - start)/T::of(cycles as f64));
+                        }
+                    },
+                }
+            }
+            last = y;
+        }
+        None
+    }
+    // Sets the timestep to dt and picks k so the fundamental comes out at
+    // target_hz cycles per unit of simulated time, treating that unit as a
+    // second. Returns the new k.
+    pub fn tune_to(&mut self, target_hz: T, dt: T) -> T {
+        self.dt = dt;
+        let omega = match self.integrator {
+            Integrator::Rk4 => T::of(2.)*T::PI()*target_hz,
+            _ => T::of(2.)/dt*(T::PI()*target_hz*dt).sin(),
+        };
+        let per_k = self.transverse_stiffness()/self.k;
+        let s = self.mode_sine();
+        self.k = omega*omega*self.mean_mass()/(T::of(4.)*s*s*per_k);
+        self.target_k = self.k;
+        self.refresh();
+        self.k
+    }
+    // Restoring force per unit transverse displacement of one segment of the
+    // straight string: its tension over its length.
+    pub fn transverse_stiffness(&self) -> T {
+        let spacing = self.spacing();
+        self.k*(spacing - self.rest_length)/spacing
+    }
+    // Speed of long transverse waves along the straight string, in units of
+    // length per unit of time: the square root of its tension over its mass
+    // per unit length. Waves a few dots long go a little slower on the
+    // discrete string.
+    pub fn wave_speed(&self) -> T {
+        self.spacing()*(self.transverse_stiffness()/self.mean_mass()).sqrt()
+    }
+    pub fn spacing(&self) -> T {
+        let last = self.chord.len()-1;
+        (self.chord[last].pos.x - self.chord[0].pos.x)/T::of(last as f64)
+    }
+    // Damping per dot that a wave meets as the string's own resistance to
+    // being shaken; local damping near this soaks waves up rather than
+    // reflecting them.
+    pub fn impedance(&self) -> T {
+        (self.transverse_stiffness()*self.mean_mass()).sqrt()
+    }
+    pub fn mean_mass(&self) -> T {
+        let free: Vec<T> = self.chord.iter().filter(|d| !d.fixed).map(|d| d.mass).collect();
+        if free.is_empty() {T::one()} else {free.iter().cloned().sum::<T>()/T::of(free.len() as f64)}
+    }
+    // sin(π/2N) for a string of N segments: the lattice factor of the first
+    // mode.
+    pub fn mode_sine(&self) -> T {
+        (T::PI()/(T::of(2.)*T::of((self.chord.len()-1) as f64))).sin()
+    }
+    // Force the string pulls its last dot with, for an end owned by
+    // something else such as a Bridge.
+    pub fn end_force(&self) -> Vect<T> {
+        let dots = &self.chord;
+        let last = dots.len()-1;
+        dots[last].get_force(dots[last-1].pos, self.rest_length)*self.spring_k(self.time)*self.segment_scale(last as isize - 1)
+    }
+    // Puts the last dot at pos; with a fixed end this lets its owner move it
+    // between ticks.
+    pub fn set_end(&mut self, pos: Vect<T>) {
+        let last = self.chord.len()-1;
+        self.chord[last].pos = pos;
+    }
+    // Amplitudes of the first count sine modes in the current shape, from a
+    // discrete sine transform of the heights of the interior dots. A string
+    // built by new_harmonic(n, k, m, a) gives a for mode m and 0 for the rest.
+    pub fn mode_amplitudes(&self, count: usize) -> Vec<T> {
+        self.sine_transform(count, |d| d.pos.y)
+    }
+    // Energy in each of the first count sine modes, in both planes,
+    // kinetic from the dots' velocities and potential from their heights.
+    // Mode m of an N-segment string has Σ sin²(mπi/N) = N/2 over the dots,
+    // and its springs stiffen it by 4κ sin²(mπ/2N) for transverse stiffness
+    // κ, so a mode of amplitude a and velocity amplitude b holds
+    // N/4·(μb² + 4κ sin²(mπ/2N)·a²). Over all N-1 modes that adds up to the
+    // string's energy for small slopes and equal masses; uneven masses use
+    // the mean one and blur the modes a little.
+    pub fn mode_energies(&self, count: usize) -> Vec<T> {
+        let n = T::of((self.chord.len()-1) as f64);
+        let (mass, stiffness) = (self.mean_mass(), self.transverse_stiffness());
+        let heights = [self.sine_transform(count, |d| d.pos.y), self.sine_transform(count, |d| d.pos.z)];
+        let speeds = [self.sine_transform(count, |d| d.vel.y), self.sine_transform(count, |d| d.vel.z)];
+        (0..count).map(|j| {
+            let lattice = (T::of((j+1) as f64)*T::PI()/(T::of(2.)*n)).sin();
+            let spring = T::of(4.)*stiffness*lattice*lattice;
+            let a2 = heights[0][j]*heights[0][j] + heights[1][j]*heights[1][j];
+            let b2 = speeds[0][j]*speeds[0][j] + speeds[1][j]*speeds[1][j];
+            n/T::of(4.)*(mass*b2 + spring*a2)
+        }).collect()
+    }
+    // Amplitudes of the first count sine modes in value over the interior
+    // dots.
+    fn sine_transform(&self, count: usize, value: impl Fn(&DotOf<T>) -> T) -> Vec<T> {
+        let n = T::of((self.chord.len()-1) as f64);
+        (1..count+1).map(|m| {
+            let sum: T = self.chord.iter().enumerate().skip(1).take(self.chord.len()-2)
+                .map(|(i, d)| value(d)*(T::of(m as f64)*T::PI()*T::of(i as f64)/n).sin())
+                .sum();
+            T::of(2.)*sum/n
+        }).collect()
+    }
+    // Index of the dot nearest the fraction u along the string.
+    pub fn index_at(&self, u: T) -> usize {
+        let last = self.chord.len()-1;
+        (u.clamp(T::zero(), T::one())*T::of(last as f64)).round().as_f64() as usize
+    }
+    // Recomputes the stored accelerations after positions were changed
+    // from outside tick, since Verlet carries them over between steps.
+    fn refresh(&mut self) {
+        if self.integrator == Integrator::Verlet {
+            let t = self.time;
+            self.apply_forces(t);
+        }
+    }
+    pub fn kinetic_energy(&self) -> T {
+        self.chord.iter()
+            .filter(|d| !d.fixed)
+            .map(|d| T::of(0.5)*d.mass*d.vel.length_squared())
+            .sum()
+    }
+    pub fn potential_energy(&self) -> T {
+        let dots = &self.chord;
+        let k = self.spring_k(self.time);
+        let mut energy = T::zero();
+        let uniform = self.k_profile.is_empty();
+        for i in 1..dots.len() {
+            let stretch = (dots[i].pos - dots[i-1].pos).size() - self.rest_length;
+            let k = if uniform {k} else {k*self.k_profile[i-1]};
+            energy += T::of(0.5)*k*stretch*stretch;
+        }
+        let periodic = self.boundary == Boundary::Periodic;
+        if periodic {
+            let wrap = dots[0].pos + self.period - dots[dots.len()-1].pos;
+            let stretch = wrap.size() - self.rest_length;
+            let k = k*self.segment_scale(dots.len() as isize - 1);
+            energy += T::of(0.5)*k*stretch*stretch;
+        }
+        if self.stiffness != T::zero() {
+            let mut curve = Vec::new();
+            self.curvature(dots, &mut curve);
+            for c in curve {
+                energy += T::of(0.5)*self.stiffness*c.length_squared();
+            }
+        }
+        if let Some(ref t) = self.termination {
+            energy += T::of(0.5)*t.stiffness*(dots[dots.len()-1].pos - t.anchor).length_squared();
+        }
+        energy
+    }
+    pub fn total_energy(&self) -> T {
+        self.kinetic_energy() + self.potential_energy()
+    }
+    // Mass-weighted mean position of the dots, the ends included.
+    pub fn center_of_mass(&self) -> Vect<T> {
+        let mass: T = self.chord.iter().map(|d| d.mass).sum();
+        let moment = self.chord.iter().fold(Vect::zero(), |sum, d| sum + d.pos*d.mass);
+        moment*(T::one()/mass)
+    }
+    // Sum of every dot's mass times its velocity. The springs pull each pair
+    // of dots together equally and oppositely, so a free or periodic string
+    // with no drag, gravity, noise or anything else pushing from outside
+    // keeps it; a fixed end takes it up and gives it back.
+    pub fn total_momentum(&self) -> Vect<T> {
+        self.chord.iter().fold(Vect::zero(), |sum, d| sum + d.vel*d.mass)
+    }
+    // Writes the complete state of the string as JSON, so load can pick the
+    // motion up exactly where it was left. JSON has no NaN or infinity, so a
+    // string that check turns down isn't written at all.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.check().map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))?;
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, self)?;
+        out.flush()
+    }
+    // Writes the dots to a CSV file, one row each under a header:
+    // index, x, y, z, vx, vy, vz and fixed as 1 or 0. The numbers have every
+    // digit it takes to read them back exactly.
+    pub fn export_csv(&self, path: &Path) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "index,x,y,z,vx,vy,vz,fixed")?;
+        for (i, d) in self.chord.iter().enumerate() {
+            writeln!(out, "{},{:?},{:?},{:?},{:?},{:?},{:?},{}", i, d.pos.x, d.pos.y, d.pos.z,
+                     d.vel.x, d.vel.y, d.vel.z, d.fixed as u8)?;
+        }
+        out.flush()
+    }
+    pub fn load(path: &Path) -> io::Result<ChordOf<T>> {
+        let mut chord: ChordOf<T> = serde_json::from_reader(BufReader::new(File::open(path)?))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("not a saved string: {}", err)))?;
+        if chord.lengths.len() != chord.chord.len() {
+            // Saved before the lengths were.
+            chord.lengths = segment_lengths(&chord.initial, chord.period);
+        }
+        chord.check().map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))?;
+        Ok(chord)
+    }
+    // Why a deserialized string couldn't be simulated, if it couldn't.
+    pub fn check(&self) -> Result<(), String> {
+        let len = self.chord.len();
+        if len < 3 {
+            return Err(format!("{} dots, a string needs at least 3", len));
+        }
+        if self.initial.len() != len {
+            return Err(format!("{} initial dots for {} dots", self.initial.len(), len));
+        }
+        let mut values = vec![self.k, self.dt, self.time, self.damping, self.damping2, self.stiffness,
+            self.gravity, self.sag, self.rest_length, self.restitution, self.period.x, self.period.y];
+        values.push(self.target_k);
+        if let Some(ref g) = self.damping_glide {
+            values.extend(&[g.target, g.step]);
+        }
+        if let Some(ref v) = self.vibrato {
+            values.extend(&[v.depth, v.rate, v.phase]);
+        }
+        values.extend(self.barrier_y);
+        values.extend(self.mute.as_ref().map(|m| m.damping));
+        values.extend(self.touch.as_ref().map(|t| t.damping));
+        if let Some(ref m) = self.magnet {
+            values.extend(&[m.position.x, m.position.y, m.position.z, m.strength]);
+        }
+        values.extend(self.noise.as_ref().map(|n| n.amplitude));
+        values.extend(&self.k_profile);
+        if let Some(ref t) = self.termination {
+            values.extend(&[t.anchor.x, t.anchor.y, t.anchor.z, t.stiffness, t.resistance]);
+        }
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err("non-finite setting".to_string());
+        }
+        let finite = |d: &DotOf<T>| [d.pos.x, d.pos.y, d.pos.z, d.vel.x, d.vel.y, d.vel.z, d.acc.x, d.acc.y,
+            d.acc.z, d.prev_acc.x, d.prev_acc.y, d.prev_acc.z, d.mass].iter().all(|v| v.is_finite());
+        if let Some(i) = self.chord.iter().position(|d| !finite(d)) {
+            return Err(format!("non-finite value in dot {}", i));
+        }
+        if let Some(i) = self.initial.iter().position(|d| !finite(d)) {
+            return Err(format!("non-finite value in initial dot {}", i));
+        }
+        if self.chord.iter().any(|d| d.mass <= T::zero()) {
+            return Err("dot without positive mass".to_string());
+        }
+        if !self.k_profile.is_empty() && self.k_profile.len() != len {
+            return Err(format!("k profile of {} segments for {} dots", self.k_profile.len(), len));
+        }
+        if self.k_profile.iter().any(|&s| s <= T::zero()) {
+            return Err("segment without a positive k".to_string());
+        }
+        let driven = self.driver.as_ref().map(|d| d.index);
+        let bowed = self.bow.as_ref().map(|b| b.index);
+        if driven.into_iter().chain(bowed).any(|i| i >= len) {
+            return Err(format!("driver or bow beyond the last of {} dots", len));
+        }
+        Ok(())
+    }
+    pub fn tick(&mut self) {
+        match self.integrator {
+            Integrator::Euler => self.tick_euler(),
+            Integrator::Verlet => self.tick_verlet(),
+            Integrator::Rk4 => self.tick_rk4(),
+            Integrator::Constrained => self.tick_constrained(CONSTRAINT_PASSES),
+        }
+        if let Some(barrier) = self.barrier_y {
+            self.collide(barrier);
+        }
+        self.advance_clock();
+        if self.ticks.is_multiple_of(CHECK_EVERY) {
+            self.check_divergence();
+        }
+    }
+    // Runs the given number of ticks. With the split layout, on a string
+    // the split loop covers, the dots are copied into lanes once for the
+    // whole run and back at the end; the trajectory is the same to the bit
+    // either way.
+    pub fn run(&mut self, ticks: u64) {
+        if self.layout == Layout::Split && self.splits() {
+            let mut lanes = Lanes::gather(&self.chord);
+            for _ in 0..ticks {
+                self.tick_lanes(&mut lanes);
+            }
+            lanes.scatter(&mut self.chord);
+            self.check_divergence();
+        } else {
+            for _ in 0..ticks {
+                self.tick();
+            }
+        }
+    }
+    // The split loop leaves out RK4 and the constrained steps, bending stiffness, damping2, bows,
+    // noise, k profiles, lossy ends, periodic strings and strings moving out of the plane,
+    // which run as Dots whatever the layout. Nothing pushes a flat string out of its plane, so
+    // the lanes only need x and y.
+    fn splits(&self) -> bool {
+        (self.integrator == Integrator::Euler || self.integrator == Integrator::Verlet) && self.stiffness == T::zero() && self.damping2 == T::zero()
+            && self.bow.is_none() && self.noise.is_none() && self.k_profile.is_empty()
+            && self.boundary != Boundary::Periodic && self.termination.is_none() && self.magnet.is_none()
+            && self.is_flat()
+    }
+    // Whether every dot lies and moves in the plane the window shows.
+    pub fn is_flat(&self) -> bool {
+        self.chord.iter().all(|d| d.pos.z == T::zero() && d.vel.z == T::zero())
+    }
+    fn tick_lanes(&mut self, lanes: &mut Lanes<T>) {
+        let dt = self.dt;
+        if self.integrator == Integrator::Verlet {
+            lanes.drift(dt);
+            self.lane_forces(lanes, self.time + dt);
+            lanes.kick(dt);
+        } else {
+            self.lane_forces(lanes, self.time);
+            lanes.euler(dt);
+        }
+        if let Some(barrier) = self.barrier_y {
+            lanes.collide(barrier, self.restitution);
+        }
+        self.advance_clock();
+    }
+    // Sets each lane dot's acceleration, exactly as compute_forces and
+    // set_force would for the strings splits allows.
+    fn lane_forces(&self, lanes: &mut Lanes<T>, t: T) {
+        let k = self.spring_k(t);
+        let rest = self.rest_length;
+        let drive = self.driver.as_ref()
+            .map(|d| (d.index, d.amplitude*(T::of(2.)*T::PI()*d.frequency*t).sin()));
+        let n = lanes.pos_x.len();
+        let (px, py) = (&lanes.pos_x, &lanes.pos_y);
+        let pull = |i: usize, j: usize| {
+            let (dx, dy) = (px[j] - px[i], py[j] - py[i]);
+            let len = (dx*dx + dy* dy).sqrt();
+            if len == T::zero() {
+                return (T::zero(), T::zero());
+            }
+            let s = (len - rest)/len;
+            (dx*s, dy*s)
+        };
+        for i in 0..n {
+            let (mut fx, mut fy) = (T::zero(), T::zero());
+            if i > 0 {
+                let (x, y) = pull(i, i-1);
+                fx += x;
+                fy += y;
+            }
+            if i+1 < n {
+                let (x, y) = pull(i, i+1);
+                fx += x;
+                fy += y;
+            }
+            fx *= k;
+            fy *= k;
+            let damping = self.damping_at(i);
+            if damping != T::zero() {
+                fx -= lanes.vel_x[i]*damping;
+                fy -= lanes.vel_y[i]*damping;
+            }
+            if let Some((index, push)) = drive {
+                if i == index {
+                    fy += push;
+                }
+            }
+            if self.gravity != T::zero() {
+                fy += lanes.mass[i]*self.gravity;
+            }
+            lanes.acc_x[i] = fx/lanes.mass[i];
+            lanes.acc_y[i] = fy/lanes.mass[i];
+        }
+    }
+    // The part of a tick after the dots have moved: k's glide, vibrato's
+    // phase and the clock.
+    fn advance_clock(&mut self) {
+        if self.k != self.target_k {
+            self.slew_k();
+        }
+        if let Some(ref g) = self.damping_glide {
+            let (target, step) = (g.target, g.step);
+            self.damping += step;
+            if (step > T::zero()) == (self.damping >= target) {
+                self.damping = target;
+                self.damping_glide = None;
+            }
+        }
+        if let Some(ref mut v) = self.vibrato {
+            v.phase = (v.phase + T::of(2.)*T::PI()*v.rate*self.dt).rem_euclid(T::of(2.)*T::PI());
+        }
+        self.time += self.dt;
+        self.ticks += 1;
+    }
+    // Marks the string diverged once any dot has run far off; it stays that
+    // way until reset, since the motion can't be trusted after.
+    fn check_divergence(&mut self) {
+        if self.status != ChordStatus::Stable {
+            return;
+        }
+        let displacement = self.chord.iter()
+            .map(|d| if d.pos.x.is_finite() && d.pos.y.is_finite() && d.pos.z.is_finite() {
+                d.pos.y.abs().max(d.pos.z.abs())
+            } else {
+                T::infinity()
+            })
+            .fold(T::zero(), T::max);
+        let span = self.period.x;
+        if displacement > span*T::of(DIVERGED_SPANS) {
+            self.status = ChordStatus::Diverged {
+                ticks: self.ticks,
+                time: self.time.as_f64(),
+                displacement: displacement.as_f64(),
+            };
+        }
+    }
+    // Spring constant in effect at time t within the current tick, with any
+    // vibrato on top of k.
+    fn spring_k(&self, t: T) -> T {
+        match self.vibrato {
+            Some(ref v) => {
+                let phase = v.phase + T::of(2.)*T::PI()*v.rate*(t - self.time);
+                self.k*(T::one() + v.depth*phase.sin())
+            },
+            None => self.k,
+        }
+    }
+    // Moves k a step toward target_k, landing on it once close enough.
+    fn slew_k(&mut self) {
+        let gap = self.target_k - self.k;
+        if gap.abs() <= self.target_k.abs()*T::of(K_SNAP) {
+            self.k = self.target_k;
+        } else {
+            self.k += gap/T::of(K_SLEW_TICKS);
+        }
+    }
+    // Bounces dots that went past the barrier back off it, losing the
+    // fraction 1 - restitution of their speed into it.
+    fn collide(&mut self, barrier: T) {
+        for dot in self.chord.iter_mut() {
+            if dot.fixed || dot.pos.y <= barrier {continue;}
+            dot.pos.y = barrier;
+            if dot.vel.y > T::zero() {
+                dot.vel.y *= -self.restitution;
+            }
+        }
+    }
+    fn tick_euler(&mut self) {
+        let t = self.time;
+        let dt = self.dt;
+        self.apply_forces(t);
+        in_chunks(self.parallel_threshold, &mut self.chord, |_, dots| {
+            for dot in dots.iter_mut() {
+                if dot.fixed {continue;}
+                dot.accelerate(dt);
+                dot.move_it(dt);
+            }
+        });
+    }
+    fn tick_verlet(&mut self) {
+        let dt = self.dt;
+        in_chunks(self.parallel_threshold, &mut self.chord, |_, dots| {
+            for dot in dots.iter_mut() {
+                if dot.fixed {continue;}
+                dot.prev_acc = dot.acc;
+                dot.pos = dot.pos + dot.vel*dt + dot.acc*(T::of(0.5)*dt*dt);
+            }
+        });
+        let t = self.time + self.dt;
+        self.apply_forces(t);
+        in_chunks(self.parallel_threshold, &mut self.chord, |_, dots| {
+            for dot in dots.iter_mut() {
+                if dot.fixed {continue;}
+                dot.vel += (dot.prev_acc + dot.acc)*(T::of(0.5)*dt);
+            }
+        });
+    }
+    // Moves the dots as tick_euler does, then projects each segment back to
+    // the length it was built with, passes times along the string, the one
+    // closing the ring included when the string is periodic. A fixed dot
+    // doesn't move; between two free ones the correction is shared in
+    // inverse proportion to their mass. Each dot's velocity is then how far
+    // it got over the tick, constraints and all.
+    fn tick_constrained(&mut self, passes: u32) {
+        let dt = self.dt;
+        let mut before = mem::take(&mut self.scratch.dpos);
+        before.clear();
+        before.extend(self.chord.iter().map(|d| d.pos));
+        self.tick_euler();
+        let n = self.chord.len();
+        let segments = if self.boundary == Boundary::Periodic {n} else {n-1};
+        for _ in 0..passes {
+            for s in 0..segments {
+                let (i, j) = (s, (s + 1)%n);
+                // The dot after the last is the first one's image on the ring.
+                let image = if j == 0 {self.period} else {Vect::zero()};
+                let (a, b) = (&self.chord[i], &self.chord[j]);
+                let wa = if a.fixed {T::zero()} else {T::one()/a.mass};
+                let wb = if b.fixed {T::zero()} else {T::one()/b.mass};
+                let d = b.pos + image - a.pos;
+                let len = d.size();
+                if wa + wb == T::zero() || len == T::zero() {
+                    continue;
+                }
+                let shift = d*((len - self.lengths[s])/(len*(wa + wb)));
+                self.chord[i].pos += shift*wa;
+                self.chord[j].pos -= shift*wb;
+            }
+        }
+        for (dot, &p) in self.chord.iter_mut().zip(&before) {
+            if !dot.fixed {
+                dot.vel = (dot.pos - p)/dt;
+            }
+        }
+        self.scratch.dpos = before;
+    }
+    fn tick_rk4(&mut self) {
+        let mut scratch = mem::take(&mut self.scratch);
+        let Scratch {ref mut forces, ref mut curve, ref mut start, ref mut stage, ref mut dpos, ref mut dvel} = scratch;
+        start.clear();
+        start.extend_from_slice(&self.chord);
+        stage.clear();
+        stage.extend_from_slice(&self.chord);
+        dpos.clear();
+        dpos.resize(start.len(), Vect::zero());
+        dvel.clear();
+        dvel.resize(start.len(), Vect::zero());
+        for (s, &(w, t)) in [(T::one(), T::zero()), (T::of(2.), T::of(0.5)), (T::of(2.), T::of(0.5)), (T::one(), T::one())].iter().enumerate() {
+            self.compute_forces(stage, self.time + t*self.dt, forces, curve);
+            let h = if s < 2 {T::of(0.5)*self.dt} else {self.dt};
+            for i in 0..stage.len() {
+                if start[i].fixed {continue;}
+                let vel = stage[i].vel;
+                let acc = forces[i]/stage[i].mass;
+                dpos[i] += vel*w;
+                dvel[i] += acc*w;
+                stage[i].pos = start[i].pos + vel*h;
+                stage[i].vel = start[i].vel + acc*h;
+            }
+        }
+        for (i, dot) in self.chord.iter_mut().enumerate() {
+            if dot.fixed {continue;}
+            let sixth = T::one()/T::of(6.);
+            let (dp, dv) = (dpos[i]*sixth, dvel[i]*sixth);
+            dot.acc = dv;
+            dot.pos = start[i].pos + dp*self.dt;
+            dot.vel = start[i].vel + dv*self.dt;
+        }
+        self.scratch = scratch;
+    }
+    fn apply_forces(&mut self, t: T) {
+        let mut scratch = mem::take(&mut self.scratch);
+        self.compute_forces(&self.chord, t, &mut scratch.forces, &mut scratch.curve);
+        let forces = &scratch.forces;
+        in_chunks(self.parallel_threshold, &mut self.chord, |start, dots| {
+            for (dot, &force) in dots.iter_mut().zip(&forces[start..]) {
+                dot.set_force(force);
+            }
+        });
+        self.scratch = scratch;
+    }
+    // Force on every dot, worked out into forces so the dots are only read,
+    // and split across the cores for big strings. curve is room for the
+    // curvature when the string is stiff.
+    fn compute_forces(&self, dots: &[DotOf<T>], t: T, forces: &mut Vec<Vect<T>>, curve: &mut Vec<Vect<T>>) {
+        forces.clear();
+        forces.resize(dots.len(), Vect::zero());
+        let k = self.spring_k(t);
+        let curve = if self.stiffness != T::zero() {
+            self.curvature(dots, curve);
+            Some(&curve[..])
+        } else {
+            None
+        };
+        in_chunks(self.parallel_threshold, forces, |start, chunk| {
+            self.pull_on(dots, k, start, chunk);
+            if let Some(curve) = curve {
+                self.bend_on(curve, start, chunk);
+            }
+            if self.damping2 != T::zero() {
+                self.damp2_on(dots, start, chunk);
+            }
+        });
+        if let Some(ref d) = self.driver {
+            forces[d.index].y += d.amplitude*(T::of(2.)*T::PI()*d.frequency*t).sin();
+        }
+        if self.gravity != T::zero() {
+            for (force, dot) in forces.iter_mut().zip(dots) {
+                force.y += dot.mass*self.gravity;
+            }
+        }
+        if let Some(ref m) = self.magnet {
+            let core = T::of(MAGNET_CORE);
+            for (force, dot) in forces.iter_mut().zip(dots) {
+                let to = m.position - dot.pos;
+                let r = to.size().max(core);
+                *force += to*(m.strength/(r*r*r));
+            }
+        }
+        if let Some(ref n) = self.noise {
+            for (i, (force, dot)) in forces.iter_mut().zip(dots).enumerate() {
+                if !dot.fixed {
+                    force.y += n.amplitude*T::of(normal(n.seed, self.ticks, i as u64));
+                }
+            }
+        }
+        if let Some(ref b) = self.bow {
+            let dot = &dots[b.index];
+            let slip = dot.vel.y - b.speed;
+            // Force that would carry the dot along at exactly the bow's speed
+            // over the next step.
+            let hold = -forces[b.index].y - dot.mass*slip/self.dt;
+            forces[b.index].y += if slip.abs() < T::of(STICK_SPEED) && hold.abs() <= b.force {
+                hold
+            } else {
+                -b.force*T::of(SLIDE_FRICTION)*slip.signum()
+            };
+        }
+        if let Some(ref term) = self.termination {
+            let last = dots.len()-1;
+            let end = &dots[last];
+            forces[last] -= (end.pos - term.anchor)*term.stiffness + end.vel*term.resistance;
+            // The end only gives across the string, as a bridge does; the
+            // tension along it would otherwise drag it in.
+            forces[last].x = T::zero();
+        }
+    }
+    // Spring and drag forces on the dots from start on, one per element of
+    // chunk. Inside the string each dot's neighbours come from a window
+    // sliding along the dots; only the two ends go through neighbor.
+    fn pull_on(&self, dots: &[DotOf<T>], k: T, start: usize, chunk: &mut [Vect<T>]) {
+        let end = start + chunk.len();
+        let inner_start = start.max(1);
+        let inner_end = end.min(dots.len()-1).max(inner_start);
+        for i in (start..inner_start).chain(inner_end..end) {
+            let j = i as isize;
+            chunk[i-start] = self.pull(&dots[i], self.neighbor(dots, j-1), self.neighbor(dots, j+1), k, i);
+        }
+        let inner = &mut chunk[inner_start-start..inner_end-start];
+        let around = dots[inner_start-1..inner_end+1].windows(3);
+        for ((force, w), i) in inner.iter_mut().zip(around).zip(inner_start..) {
+            *force = self.pull(&w[1], Some(w[0].pos), Some(w[2].pos), k, i);
+        }
+    }
+    // Spring pull toward the neighbours dot i has, then drag. With a k
+    // profile each side pulls as hard as its own segment.
+    fn pull(&self, dot: &DotOf<T>, left: Option<Vect<T>>, right: Option<Vect<T>>, k: T, i: usize) -> Vect<T> {
+        let mut force = Vect::zero();
+        let uniform = self.k_profile.is_empty();
+        if let Some(p) = left {
+            let pull = dot.get_force(p, self.rest_length);
+            force += if uniform {pull} else {pull*self.segment_scale(i as isize - 1)};
+        }
+        if let Some(p) = right {
+            let pull = dot.get_force(p, self.rest_length);
+            force += if uniform {pull} else {pull*self.segment_scale(i as isize)};
+        }
+        force = force*k;
+        let damping = self.damping_at(i);
+        if damping != T::zero() {
+            force -= dot.vel*damping;
+        }
+        force
+    }
+    // Adds the damping2 drag to the dots from start on. An end without a
+    // neighbour leaves that side out; a fixed neighbour counts with its zero
+    // velocity.
+    fn damp2_on(&self, dots: &[DotOf<T>], start: usize, chunk: &mut [Vect<T>]) {
+        for (i, force) in (start..).zip(chunk.iter_mut()) {
+            let here = dots[i].vel;
+            let j = i as isize;
+            let mut diff = Vect::zero();
+            for v in [self.neighbor_vel(dots, j-1), self.neighbor_vel(dots, j+1)].iter().flatten() {
+                diff += *v - here;
+            }
+            *force += diff*self.damping2;
+        }
+    }
+    // Takes the bending force off the dots from start on, the same way
+    // round as pull_on.
+    fn bend_on(&self, curve: &[Vect<T>], start: usize, chunk: &mut [Vect<T>]) {
+        let end = start + chunk.len();
+        let inner_start = start.max(1);
+        let inner_end = end.min(curve.len()-1).max(inner_start);
+        for i in (start..inner_start).chain(inner_end..end) {
+            let j = i as isize;
+            chunk[i-start] -= self.bend(self.curve_at(curve, j-1), curve[i], self.curve_at(curve, j+1));
+        }
+        let inner = &mut chunk[inner_start-start..inner_end-start];
+        for (force, w) in inner.iter_mut().zip(curve[inner_start-1..inner_end+1].windows(3)) {
+            *force -= self.bend(w[0], w[1], w[2]);
+        }
+    }
+    fn bend(&self, left: Vect<T>, here: Vect<T>, right: Vect<T>) -> Vect<T> {
+        (left + right - here - here)*self.stiffness
+    }
+    // Position of dot j, wrapping around the ring when the string is
+    // periodic and None past the ends otherwise.
+    fn neighbor(&self, dots: &[DotOf<T>], j: isize) -> Option<Vect<T>> {
+        let len = dots.len() as isize;
+        if j >= 0 && j < len {
+            Some(dots[j as usize].pos)
+        } else if self.boundary != Boundary::Periodic {
+            None
+        } else if j < 0 {
+            Some(dots[(j+len) as usize].pos - self.period)
+        } else {
+            Some(dots[(j-len) as usize].pos + self.period)
+        }
+    }
+    // Velocity of dot j, wrapping round the ring the way neighbor does.
+    fn neighbor_vel(&self, dots: &[DotOf<T>], j: isize) -> Option<Vect<T>> {
+        let len = dots.len() as isize;
+        if j >= 0 && j < len {
+            Some(dots[j as usize].vel)
+        } else if self.boundary != Boundary::Periodic {
+            None
+        } else {
+            Some(dots[j.rem_euclid(len) as usize].vel)
+        }
+    }
+    // Discrete second difference of position at each dot, zero at ends that
+    // have no neighbour, into curve. Bending energy is ½·stiffness·Σ|curve|²,
+    // and the bending force is its exact gradient, which away from the ends
+    // is the usual fourth-difference stencil.
+    fn curvature(&self, dots: &[DotOf<T>], curve: &mut Vec<Vect<T>>) {
+        curve.clear();
+        curve.resize(dots.len(), Vect::zero());
+        in_chunks(self.parallel_threshold, curve, |start, chunk| {
+            for (j, c) in (start as isize..).zip(chunk.iter_mut()) {
+                if let (Some(l), Some(r)) = (self.neighbor(dots, j-1), self.neighbor(dots, j+1)) {
+                    let p = dots[j as usize].pos;
+                    *c = l + r - p - p;
+                }
+            }
+        });
+    }
+    fn curve_at(&self, curve: &[Vect<T>], j: isize) -> Vect<T> {
+        let len = curve.len() as isize;
+        if j >= 0 && j < len {
+            curve[j as usize]
+        } else if self.boundary == Boundary::Periodic {
+            curve[((j+len) % len) as usize]
+        } else {
+            Vect::zero()
+        }
+    }
+}
+
+// A draw from the standard normal distribution for dot i at tick, by
+// Box-Muller from two uniforms hashed out of the three with splitmix64.
+fn normal(seed: u64, tick: u64, i: u64) -> f64 {
+    let mix = |mut z: u64| {
+        z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+    let bits = mix(mix(mix(seed) ^ tick) ^ i);
+    let uniform = |b: u64| ((b >> 11) as f64 + 0.5)/(1u64 << 53) as f64;
+    let (u, v) = (uniform(bits), uniform(mix(bits)));
+    (-2.*u.ln()).sqrt()*(2.*std::f64::consts::PI*v).cos()
+}
+
+fn parallel_threshold() -> usize {
+    PARALLEL_THRESHOLD
+}
+
+// Runs f over items, split into one run of consecutive items per thread of
+// rayon's pool once there are at least threshold of them, and otherwise all
+// at once here. f gets the index its run starts at along with the run.
+fn in_chunks<T: Send>(threshold: usize, items: &mut [T], f: impl Fn(usize, &mut [T]) + Sync) {
+    let threads = rayon::current_num_threads();
+    if items.len() < threshold.max(1) || threads == 1 {
+        f(0, items);
+        return;
+    }
+    let size = items.len().div_ceil(threads);
+    items.par_chunks_mut(size).enumerate().for_each(|(n, run)| f(n*size, run));
+}
+
+// Room for what a tick works out along the string: the forces and curvature,
+// and RK4's copies of the dots and its weighted sums.
+#[derive(Clone, Default)]
+struct Scratch<T> {
+    forces: Vec<Vect<T>>,
+    curve: Vec<Vect<T>>,
+    start: Vec<DotOf<T>>,
+    stage: Vec<DotOf<T>>,
+    dpos: Vec<Vect<T>>,
+    dvel: Vec<Vect<T>>,
+}
+
+// Length of each segment of dots, the one to the right of each dot, the
+// last dot's reaching round a ring of the given period to the first.
+fn segment_lengths<T: Scalar>(dots: &[DotOf<T>], period: Vect<T>) -> Vec<T> {
+    let last = dots.len()-1;
+    let mut lengths: Vec<T> = dots.windows(2).map(|w| (w[1].pos - w[0].pos).size()).collect();
+    lengths.push((dots[0].pos + period - dots[last].pos).size());
+    lengths
+}
+
+fn stable() -> ChordStatus {
+    ChordStatus::Stable
+}
+
+fn interleaved() -> Layout {
+    Layout::Interleaved
+}
+
+// The dots as one array per coordinate, for Layout::Split.
+struct Lanes<T> {
+    pos_x: Vec<T>,
+    pos_y: Vec<T>,
+    vel_x: Vec<T>,
+    vel_y: Vec<T>,
+    acc_x: Vec<T>,
+    acc_y: Vec<T>,
+    prev_x: Vec<T>,
+    prev_y: Vec<T>,
+    mass: Vec<T>,
+    // Bit i%64 of word i/64 is set when dot i is fixed.
+    fixed: Vec<u64>,
+}
+
+impl<T: Scalar> Lanes<T> {
+    fn gather(dots: &[DotOf<T>]) -> Lanes<T> {
+        let lane = |f: &dyn Fn(&DotOf<T>) -> T| dots.iter().map(f).collect::<Vec<T>>();
+        let mut fixed = vec![0; dots.len().div_ceil(64)];
+        for (i, d) in dots.iter().enumerate() {
+            if d.fixed {
+                fixed[i/64] |= 1<<(i%64);
+            }
+        }
+        Lanes {
+            pos_x: lane(&|d| d.pos.x),
+            pos_y: lane(&|d| d.pos.y),
+            vel_x: lane(&|d| d.vel.x),
+            vel_y: lane(&|d| d.vel.y),
+            acc_x: lane(&|d| d.acc.x),
+            acc_y: lane(&|d| d.acc.y),
+            prev_x: lane(&|d| d.prev_acc.x),
+            prev_y: lane(&|d| d.prev_acc.y),
+            mass: lane(&|d| d.mass),
+            fixed,
+        }
+    }
+    fn scatter(&self, dots: &mut [DotOf<T>]) {
+        for (i, d) in dots.iter_mut().enumerate() {
+            d.pos = Vect::flat(self.pos_x[i], self.pos_y[i]);
+            d.vel = Vect::flat(self.vel_x[i], self.vel_y[i]);
+            d.acc = Vect::flat(self.acc_x[i], self.acc_y[i]);
+            d.prev_acc = Vect::flat(self.prev_x[i], self.prev_y[i]);
+        }
+    }
+    fn is_fixed(&self, i: usize) -> bool {
+        self.fixed[i/64]>>(i%64) & 1 == 1
+    }
+    fn euler(&mut self, dt: T) {
+        for i in 0..self.pos_x.len() {
+            if self.is_fixed(i) {continue;}
+            self.vel_x[i] += self.acc_x[i]*dt;
+            self.vel_y[i] += self.acc_y[i]*dt;
+            self.pos_x[i] += self.vel_x[i]*dt;
+            self.pos_y[i] += self.vel_y[i]*dt;
+        }
+    }
+    // First half of a Verlet tick: move on the old acceleration.
+    fn drift(&mut self, dt: T) {
+        for i in 0..self.pos_x.len() {
+            if self.is_fixed(i) {continue;}
+            self.prev_x[i] = self.acc_x[i];
+            self.prev_y[i] = self.acc_y[i];
+            self.pos_x[i] = self.pos_x[i] + self.vel_x[i]*dt + self.acc_x[i]*(T::of(0.5)*dt*dt);
+            self.pos_y[i] = self.pos_y[i] + self.vel_y[i]*dt + self.acc_y[i]*(T::of(0.5)*dt*dt);
+        }
+    }
+    // Second half: speed up by the mean of the old and new accelerations.
+    fn kick(&mut self, dt: T) {
+        for i in 0..self.pos_x.len() {
+            if self.is_fixed(i) {continue;}
+            self.vel_x[i] += (self.prev_x[i] + self.acc_x[i])*(T::of(0.5)*dt);
+            self.vel_y[i] += (self.prev_y[i] + self.acc_y[i])*(T::of(0.5)*dt);
+        }
+    }
+    fn collide(&mut self, barrier: T, restitution: T) {
+        for i in 0..self.pos_y.len() {
+            if self.is_fixed(i) || self.pos_y[i] <= barrier {continue;}
+            self.pos_y[i] = barrier;
+            if self.vel_y[i] > T::zero() {
+                self.vel_y[i] *= -restitution;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Forces on the dots worked out one dot at a time by index, the plain
+    // way compute_forces did before it walked the dots in windows.
+    fn indexed_forces(chord: &Chord) -> Vec<Vect> {
+        let dots = &chord.chord;
+        let k = chord.spring_k(chord.time);
+        let mut curve = Vec::new();
+        if chord.stiffness != 0. {
+            chord.curvature(dots, &mut curve);
+        }
+        (0..dots.len()).map(|i| {
+            let j = i as isize;
+            let mut force = Vect::zero();
+            if let Some(p) = chord.neighbor(dots, j-1) {
+                force += dots[i].get_force(p, chord.rest_length)*chord.segment_scale(j-1);
+            }
+            if let Some(p) = chord.neighbor(dots, j+1) {
+                force += dots[i].get_force(p, chord.rest_length)*chord.segment_scale(j);
+            }
+            force = force*k;
+            let damping = chord.damping_at(i);
+            if damping != 0. {
+                force -= dots[i].vel*damping;
+            }
+            if chord.stiffness != 0. {
+                force -= chord.bend(chord.curve_at(&curve, j-1), curve[i], chord.curve_at(&curve, j+1));
+            }
+            if chord.damping2 != 0. {
+                let mut diff = Vect::zero();
+                for v in [chord.neighbor_vel(dots, j-1), chord.neighbor_vel(dots, j+1)].iter().flatten() {
+                    diff += *v - dots[i].vel;
+                }
+                force += diff*chord.damping2;
+            }
+            force
+        }).collect()
+    }
+
+    fn bits(forces: &[Vect]) -> Vec<[u64; 3]> {
+        forces.iter().map(|f| [f.x.to_bits(), f.y.to_bits(), f.z.to_bits()]).collect()
+    }
+
+    // In a pool of three threads, so a string with a threshold of 0 splits
+    // even on one core.
+    fn same_forces(chord: &Chord, what: &str) {
+        let (mut forces, mut curve) = (Vec::new(), Vec::new());
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        pool.install(|| chord.compute_forces(&chord.chord, chord.time, &mut forces, &mut curve));
+        assert!(bits(&forces) == bits(&indexed_forces(chord)), "{} differs", what);
+    }
+
+    #[test]
+    fn windowed_forces_match_indexed_ones_to_the_bit() {
+        for &boundary in &[Boundary::Fixed, Boundary::Free, Boundary::Periodic] {
+            for &threshold in &[usize::MAX, 0] {
+                let mut chord = Chord::new_pluck_angled(90, 1./256., 0.3, 6., 0.4);
+                chord.set_boundary(boundary);
+                chord.set_parallel_threshold(threshold);
+                for _ in 0..50 {
+                    chord.tick();
+                }
+                same_forces(&chord, "a pluck");
+                chord.set_damping(1e-3);
+                chord.set_mute(0.7, 1e-2);
+                same_forces(&chord, "a damped, muted string");
+                chord.set_stiffness(1e-3);
+                chord.set_damping2(1e-3);
+                same_forces(&chord, "a stiff string with damping2");
+                chord.set_k_profile(|u| 1. + u);
+                same_forces(&chord, "a k profile");
+            }
+        }
+    }
+}
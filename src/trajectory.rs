@@ -0,0 +1,177 @@
+// Dot positions of a run saved every few ticks, for replaying without the
+// physics. The file is little-endian: "CHRD", a version byte, the number of
+// dots as a u32, k and dt as f64s and the ticks between frames as a u32,
+// then frame after frame of each dot's x and y as f32s.
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use {Chord, Vect};
+
+const MAGIC: &[u8; 4] = b"CHRD";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 4 + 1 + 4 + 8 + 8 + 4;
+
+pub struct TrajectoryWriter<W: Write> {
+    out: W,
+    dots: usize,
+}
+
+impl TrajectoryWriter<BufWriter<File>> {
+    pub fn create(path: &Path, chord: &Chord, every: u64) -> io::Result<TrajectoryWriter<BufWriter<File>>> {
+        TrajectoryWriter::new(BufWriter::new(File::create(path)?), chord, every)
+    }
+}
+
+impl<W: Write> TrajectoryWriter<W> {
+    // Writes the header for frames of chord every ticks apart.
+    pub fn new(mut out: W, chord: &Chord, every: u64) -> io::Result<TrajectoryWriter<W>> {
+        let dots = chord.dots().len();
+        if every > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("{} ticks between frames is too many", every)));
+        }
+        out.write_all(MAGIC)?;
+        out.write_all(&[VERSION])?;
+        out.write_all(&(dots as u32).to_le_bytes())?;
+        out.write_all(&chord.k().to_le_bytes())?;
+        out.write_all(&chord.dt().to_le_bytes())?;
+        out.write_all(&(every as u32).to_le_bytes())?;
+        Ok(TrajectoryWriter {out, dots})
+    }
+    pub fn frame(&mut self, chord: &Chord) -> io::Result<()> {
+        if chord.dots().len() != self.dots {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("{} dots in a trajectory of {}", chord.dots().len(), self.dots)));
+        }
+        for d in chord.dots() {
+            self.out.write_all(&(d.pos().x as f32).to_le_bytes())?;
+            self.out.write_all(&(d.pos().y as f32).to_le_bytes())?;
+        }
+        Ok(())
+    }
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+pub struct Trajectory {
+    pub dots: usize,
+    pub k: f64,
+    pub dt: f64,
+    // Ticks between frames.
+    pub every: u64,
+    // x and y of every dot of every frame, one frame after another.
+    values: Vec<f32>,
+}
+
+impl Trajectory {
+    pub fn load(path: &Path) -> Result<Trajectory, String> {
+        let bytes = fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+        Trajectory::parse(&bytes).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+    pub fn parse(bytes: &[u8]) -> Result<Trajectory, String> {
+        if bytes.len() < 5 || &bytes[..4] != MAGIC {
+            return Err("not a trajectory file".to_string());
+        }
+        if bytes[4] != VERSION {
+            return Err(format!("trajectory version {}, but only version {} can be read", bytes[4], VERSION));
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(format!("truncated: {} bytes, the header alone takes {}", bytes.len(), HEADER_LEN));
+        }
+        let u32_at = |at: usize| u32::from_le_bytes([bytes[at], bytes[at+1], bytes[at+2], bytes[at+3]]);
+        let f64_at = |at: usize| {
+            let mut b = [0; 8];
+            b.copy_from_slice(&bytes[at..at+8]);
+            f64::from_le_bytes(b)
+        };
+        let dots = u32_at(5) as usize;
+        let (k, dt, every) = (f64_at(9), f64_at(17), u32_at(25) as u64);
+        if dots < 3 || !(k > 0. && k.is_finite()) || !(dt > 0. && dt.is_finite()) || every == 0 {
+            return Err(format!("bad header: {} dots, k {}, dt {}, {} ticks between frames", dots, k, dt, every));
+        }
+        let data = &bytes[HEADER_LEN..];
+        let frame_len = dots*8;
+        if data.is_empty() {
+            return Err("no frames".to_string());
+        }
+        if !data.len().is_multiple_of(frame_len) {
+            return Err(format!("truncated: frame {} has {} of its {} bytes",
+                               data.len()/frame_len + 1, data.len()%frame_len, frame_len));
+        }
+        let values = data.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect();
+        Ok(Trajectory {dots, k, dt, every, values})
+    }
+    pub fn frames(&self) -> usize {
+        self.values.len()/(self.dots*2)
+    }
+    // The string as it was in frame i, at rest and at the recorded k.
+    pub fn frame(&self, i: usize) -> Chord {
+        let values = &self.values[i*self.dots*2..(i+1)*self.dots*2];
+        let positions: Vec<Vect> = values.chunks_exact(2).map(|p| Vect::flat(p[0] as f64, p[1] as f64)).collect();
+        let mut chord = Chord::from_positions(&positions, self.k);
+        chord.set_dt(self.dt);
+        chord
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The header and frames frames of chord as it runs, ten ticks apart.
+    fn written(chord: &mut Chord, frames: usize) -> Vec<u8> {
+        let mut writer = TrajectoryWriter::new(Vec::new(), chord, 10).unwrap();
+        for _ in 0..frames {
+            writer.frame(chord).unwrap();
+            chord.run(10);
+        }
+        writer.out
+    }
+
+    #[test]
+    fn a_written_run_reads_back() {
+        let mut chord = Chord::new_pluck(12, 1./64., 0.3, 2.);
+        let start = chord.clone();
+        let bytes = written(&mut chord, 5);
+        let trajectory = Trajectory::parse(&bytes).unwrap();
+        assert_eq!((trajectory.dots, trajectory.k, trajectory.dt, trajectory.every), (13, chord.k(), chord.dt(), 10));
+        assert_eq!(trajectory.frames(), 5);
+        for (a, b) in trajectory.frame(0).dots().iter().zip(start.dots()) {
+            assert_eq!(a.pos().x, b.pos().x as f32 as f64);
+            assert_eq!(a.pos().y, b.pos().y as f32 as f64);
+        }
+    }
+
+    #[test]
+    fn a_file_cut_off_in_its_header_is_refused() {
+        let bytes = written(&mut Chord::new(12, 1./64.), 2);
+        for len in 5..HEADER_LEN {
+            let err = Trajectory::parse(&bytes[..len]).err().expect("a cut-off header was read");
+            assert!(err.contains("truncated"), "{} bytes: {}", len, err);
+        }
+    }
+
+    #[test]
+    fn a_file_cut_off_in_a_frame_is_refused() {
+        let bytes = written(&mut Chord::new(12, 1./64.), 2);
+        let err = Trajectory::parse(&bytes[..bytes.len() - 3]).err().expect("a cut-off frame was read");
+        assert!(err.contains("frame 2"), "{}", err);
+        assert!(Trajectory::parse(&bytes[..HEADER_LEN]).is_err(), "a file of no frames was read");
+    }
+
+    #[test]
+    fn another_version_is_refused() {
+        let mut bytes = written(&mut Chord::new(12, 1./64.), 2);
+        bytes[4] = VERSION + 1;
+        let err = Trajectory::parse(&bytes).err().expect("version 2 was read");
+        assert!(err.contains("version 2"), "{}", err);
+    }
+
+    #[test]
+    fn nothing_at_all_is_refused() {
+        assert!(Trajectory::parse(&[]).is_err());
+        assert!(Trajectory::parse(b"CHRD").is_err());
+    }
+}
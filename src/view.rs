@@ -0,0 +1,97 @@
+// Where a string lands on screen: the rectangle a view gets and the map from
+// string coordinates to its pixels. Pure arithmetic, so it's here rather
+// than with the window, where it can be timed and used without one.
+use geometry::Vect;
+use string::Chord;
+
+// Rectangle of the window given over to one view, in pixels.
+#[derive(Clone, Copy)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+impl Viewport {
+    // Space left clear at the sides of what's drawn, shrinking for small
+    // views so there's always something between the margins.
+    pub fn margin_x(&self) -> f64 {
+        (self.w/16.).min(50.)
+    }
+    pub fn margin_y(&self) -> f64 {
+        (self.h/8.).min(50.)
+    }
+}
+
+// Maps string coordinates to window pixels and back.
+#[derive(Clone, Copy)]
+pub struct Transform {
+    pub x_scale: f64,
+    pub y_scale: f64,
+    pub x_offset: f64,
+    pub y_offset: f64,
+}
+
+impl Transform {
+    // Fits the string across the viewport, with its rest line at height
+    // centre and y_scale pixels per unit of displacement. A string hanging
+    // under gravity is raised by half its sag, so it's the settled shape
+    // that sits about the centre rather than the line between its ends.
+    pub fn fit(chord: &Chord, view: Viewport, centre: f64, y_scale: f64) -> Transform {
+        let segments = (chord.dots().len()-1) as f64;
+        Transform {
+            x_scale: (view.w - 2.*view.margin_x())/segments,
+            y_scale,
+            x_offset: view.x + view.margin_x(),
+            y_offset: centre - chord.sag()*y_scale/2.,
+        }
+    }
+    pub fn apply(&self, pos: Vect) -> Vect {
+        Vect {x: pos.x*self.x_scale + self.x_offset, y: pos.y*self.y_scale + self.y_offset, z: pos.z*self.y_scale}
+    }
+    pub fn invert(&self, pixel: Vect) -> Vect {
+        Vect {x: (pixel.x - self.x_offset)/self.x_scale, y: (pixel.y - self.y_offset)/self.y_scale, z: pixel.z/self.y_scale}
+    }
+}
+
+// Where the lone string's view looks, for zooming in on part of it: the
+// point of the string, in its own coordinates, put at the middle of the
+// viewport, and how many times bigger than the fitted view everything is.
+#[derive(Clone, Copy)]
+pub struct Camera {
+    pub centre: Vect,
+    pub zoom: f64,
+}
+
+impl Camera {
+    // The camera that shows what fit does: the point fit puts at the
+    // middle of view, unzoomed.
+    pub fn fitted(fit: &Transform, view: Viewport) -> Camera {
+        Camera {centre: fit.invert(middle(view)), zoom: 1.}
+    }
+    // fit as seen through the camera.
+    pub fn through(&self, fit: &Transform, view: Viewport) -> Transform {
+        let (x_scale, y_scale) = (fit.x_scale*self.zoom, fit.y_scale*self.zoom);
+        let m = middle(view);
+        Transform {x_scale, y_scale, x_offset: m.x - self.centre.x*x_scale, y_offset: m.y - self.centre.y*y_scale}
+    }
+    // Zoomed by factor, to within min and max times the fitted view,
+    // keeping the point of the string at pixel where it is.
+    pub fn zoomed(&self, fit: &Transform, view: Viewport, factor: f64, (min, max): (f64, f64), pixel: Vect) -> Camera {
+        let under = self.through(fit, view).invert(pixel);
+        let zoom = (self.zoom*factor).clamp(min, max);
+        let m = middle(view);
+        let centre = Vect::flat(under.x - (pixel.x - m.x)/(fit.x_scale*zoom), under.y - (pixel.y - m.y)/(fit.y_scale*zoom));
+        Camera {centre, zoom}
+    }
+    // Moved so what was drawn at one pixel is drawn shift pixels along.
+    pub fn panned(&self, fit: &Transform, shift: Vect) -> Camera {
+        let centre = Vect::flat(self.centre.x - shift.x/(fit.x_scale*self.zoom), self.centre.y - shift.y/(fit.y_scale*self.zoom));
+        Camera {centre, zoom: self.zoom}
+    }
+}
+
+fn middle(view: Viewport) -> Vect {
+    Vect::flat(view.x + view.w/2., view.y + view.h/2.)
+}
@@ -0,0 +1,66 @@
+// The string for JavaScript, through wasm-bindgen, so a web page can tick
+// one and draw it on a canvas. Built only with the wasm feature; see
+// examples/web for a page that uses it.
+use js_sys::Float64Array;
+use wasm_bindgen::prelude::*;
+
+use guitar::pluck_shape_at;
+use Chord;
+
+#[wasm_bindgen]
+pub struct WebChord {
+    chord: Chord,
+}
+
+// A string of n segments with spring constant k, starting as preset:
+// "triangle", "sine", "pluck", "strike" or "pulse", the last three at a
+// fifth of the way along as the command line has them.
+#[wasm_bindgen]
+pub fn new_chord(n: u16, k: f64, preset: &str) -> Result<WebChord, JsValue> {
+    if n < 2 {
+        return Err(JsValue::from_str(&format!("a string needs at least 2 segments, not {}", n)));
+    }
+    if !(k > 0. && k.is_finite()) {
+        return Err(JsValue::from_str(&format!("k must be positive and finite, not {}", k)));
+    }
+    let chord = match preset {
+        "triangle" => Chord::new(n, k),
+        "sine" => Chord::new_sine(n, k),
+        "pluck" => Chord::new_pluck(n, k, 0.2, n as f64*0.375/2.),
+        "strike" => Chord::new_strike(n, k, 0.2, 0.05, 0.05),
+        "pulse" => Chord::new_pulse(n, k, 0.2, 0.03, 2.),
+        _ => return Err(JsValue::from_str(&format!(
+            "unknown preset {}, expected one of triangle, sine, pluck, strike, pulse", preset))),
+    };
+    Ok(WebChord {chord})
+}
+
+#[wasm_bindgen]
+impl WebChord {
+    pub fn tick(&mut self, steps: u32) {
+        self.chord.run(steps as u64);
+    }
+    // x and y of every dot in turn, from the left end.
+    pub fn positions(&self) -> Float64Array {
+        let xy: Vec<f64> = self.chord.dots().iter().flat_map(|d| [d.pos().x, d.pos().y]).collect();
+        Float64Array::from(&xy[..])
+    }
+    pub fn dots(&self) -> usize {
+        self.chord.dots().len()
+    }
+    // Adds the bump P plucks with in the window, peaking at the fraction u
+    // along the string and scaled by amount.
+    pub fn excite(&mut self, u: f64, amount: f64) {
+        let u = u.clamp(0.01, 0.99);
+        self.chord.excite(|x| pluck_shape_at(x, u)*amount);
+    }
+    pub fn reset(&mut self) {
+        self.chord.reset();
+    }
+    pub fn time(&self) -> f64 {
+        self.chord.time()
+    }
+    pub fn total_energy(&self) -> f64 {
+        self.chord.total_energy()
+    }
+}
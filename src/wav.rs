@@ -0,0 +1,118 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Loudest sample after normalization, as a fraction of full scale.
+const HEADROOM: f64 = 0.9;
+
+// Writes samples as a 16-bit PCM mono WAV file. The signal has its mean
+// removed and is scaled so the loudest sample sits just under full scale.
+pub fn write_wav(path: &Path, samples: &[f64], sample_rate: u32) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    encode_wav(&mut out, &[samples], sample_rate)?;
+    out.flush()
+}
+
+// Writes left and right as the two channels of a 16-bit PCM stereo WAV
+// file, as long as the shorter of them. Each has its own mean removed, and
+// both are scaled by the same gain, so the louder one sits just under full
+// scale and the difference between them can still be heard.
+pub fn write_wav_stereo(path: &Path, left: &[f64], right: &[f64], sample_rate: u32) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    encode_wav(&mut out, &[left, right], sample_rate)?;
+    out.flush()
+}
+
+// Interleaves the channels, one frame a sample of each, into a WAV file.
+pub fn encode_wav<W: Write>(out: &mut W, channels: &[&[f64]], sample_rate: u32) -> io::Result<()> {
+    let frames = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+    let count = channels.len() as u32;
+    let data_len = frames as u32*2*count;
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&(count as u16).to_le_bytes())?;
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate*2*count).to_le_bytes())?;
+    out.write_all(&(2*count as u16).to_le_bytes())?;
+    out.write_all(&16u16.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())?;
+    let channels: Vec<&[f64]> = channels.iter().map(|c| &c[..frames]).collect();
+    let normalized = normalize(&channels);
+    for i in 0..frames {
+        for c in &normalized {
+            out.write_all(&((c[i]*i16::MAX as f64).round() as i16).to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+fn normalize(channels: &[&[f64]]) -> Vec<Vec<f64>> {
+    let centred: Vec<Vec<f64>> = channels.iter().map(|samples| {
+        let mean = if samples.is_empty() {0.} else {samples.iter().sum::<f64>()/samples.len() as f64};
+        samples.iter().map(|s| s - mean).collect()
+    }).collect();
+    let peak = centred.iter().flatten().fold(0., |m: f64, s| m.max(s.abs()));
+    let gain = if peak > 0. {HEADROOM/peak} else {0.};
+    centred.into_iter().map(|c| c.into_iter().map(|s| s*gain).collect()).collect()
+}
+
+// Linearly resamples a signal taken at from_rate to to_rate.
+pub fn resample(samples: &[f64], from_rate: f64, to_rate: f64) -> Vec<f64> {
+    if samples.len() < 2 {
+        return samples.to_vec();
+    }
+    let step = from_rate/to_rate;
+    let len = ((samples.len()-1) as f64/step).floor() as usize + 1;
+    (0..len).map(|j| {
+        let u = j as f64*step;
+        let i = (u.floor() as usize).min(samples.len()-2);
+        let t = u - i as f64;
+        samples[i]*(1.-t) + samples[i+1]*t
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use Chord;
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes([bytes[at], bytes[at+1]])
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes([bytes[at], bytes[at+1], bytes[at+2], bytes[at+3]])
+    }
+
+    #[test]
+    fn a_sine_s_pickup_writes_a_mono_16_bit_file() {
+        let mut chord = Chord::new_sine(40, 1./1024.);
+        let samples: Vec<f64> = (0..3000).map(|_| {chord.tick(); chord.pickup(0.5)}).collect();
+        let path = std::env::temp_dir().join(format!("chord_sim-{}-sine.wav", std::process::id()));
+        write_wav(&path, &samples, 44_100).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u32_at(&bytes, 16), 16);
+        assert_eq!(u16_at(&bytes, 20), 1, "not PCM");
+        assert_eq!(u16_at(&bytes, 22), 1, "not mono");
+        assert_eq!(u32_at(&bytes, 24), 44_100);
+        assert_eq!(u32_at(&bytes, 28), 2*44_100);
+        assert_eq!(u16_at(&bytes, 32), 2);
+        assert_eq!(u16_at(&bytes, 34), 16);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 2*3000);
+        assert_eq!(bytes.len(), 44 + 2*3000);
+        // Normalized to just under full scale.
+        let loudest = bytes[44..].chunks(2).map(|s| i16::from_le_bytes([s[0], s[1]]).unsigned_abs()).max().unwrap();
+        assert_eq!(loudest, (HEADROOM*i16::MAX as f64).round() as u16);
+    }
+}
@@ -0,0 +1,160 @@
+// The SDL 2 window, as a Renderer: the frame is drawn into a Framebuffer and
+// copied into a streaming texture to be shown.
+use std::error::Error;
+use std::fmt;
+
+use sdl2;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::{MouseButton, MouseWheelDirection};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::WindowCanvas;
+use sdl2::video::FullscreenType;
+use sdl2::EventPump;
+
+use render::{Framebuffer, InputState, Key, PixelFormat, Plot, Renderer};
+
+#[derive(Debug)]
+pub enum ScreenError {
+    InitFailed {reason: String},
+    VideoModeFailed {reason: String},
+}
+
+impl fmt::Display for ScreenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScreenError::InitFailed {ref reason} => write!(f, "couldn't start SDL video: {}", reason),
+            ScreenError::VideoModeFailed {ref reason} => write!(f, "couldn't open the window: {}", reason),
+        }
+    }
+}
+
+impl Error for ScreenError {}
+
+pub struct SdlWindow {
+    frame: Framebuffer,
+    canvas: WindowCanvas,
+    events: EventPump,
+}
+
+impl SdlWindow {
+    pub fn open(w: isize, h: isize, title: &str) -> Result<SdlWindow, ScreenError> {
+        let init = |reason| ScreenError::InitFailed {reason};
+        let sdl = sdl2::init().map_err(init)?;
+        let video = sdl.video().map_err(init)?;
+        let events = sdl.event_pump().map_err(init)?;
+        let window = video.window(title, w as u32, h as u32)
+            .resizable()
+            .build()
+            .map_err(|err| ScreenError::VideoModeFailed {reason: err.to_string()})?;
+        let canvas = window.into_canvas()
+            .build()
+            .map_err(|err| ScreenError::VideoModeFailed {reason: err.to_string()})?;
+        Ok(SdlWindow {frame: Framebuffer::new(w as usize, h as usize), canvas, events})
+    }
+}
+
+impl Renderer for SdlWindow {
+    fn size(&self) -> (isize, isize) {
+        self.frame.size()
+    }
+    fn format(&self) -> PixelFormat {
+        self.frame.format()
+    }
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: (u8, u8, u8)) {
+        self.frame.fill_rect(x, y, w, h, rgb);
+    }
+    fn draw_point(&mut self, x: usize, y: usize, rgb: (u8, u8, u8)) {
+        self.frame.draw_point(x, y, rgb);
+    }
+    fn blend(&mut self, plots: &[Plot]) {
+        self.frame.blend(plots);
+    }
+    fn with_pixels(&mut self, f: &mut dyn FnMut(&mut [u8], usize)) -> Result<(), String> {
+        self.frame.with_pixels(f)
+    }
+    // ARGB8888 is a u32 with red at bit 16, as the Framebuffer keeps them. A
+    // frame that can't be shown goes without.
+    fn present(&mut self) {
+        let (w, h) = self.frame.size();
+        let creator = self.canvas.texture_creator();
+        let mut texture = match creator.create_texture_streaming(PixelFormatEnum::ARGB8888, w as u32, h as u32) {
+            Ok(texture) => texture,
+            Err(_) => return,
+        };
+        let frame = &self.frame;
+        let _ = texture.with_lock(None, |bytes, pitch| {
+            for (row, line) in bytes.chunks_mut(pitch).zip(frame.pixels().chunks(w as usize)) {
+                for (b, p) in row.chunks_exact_mut(4).zip(line) {
+                    b.copy_from_slice(&p.to_ne_bytes());
+                }
+            }
+        });
+        let _ = self.canvas.copy(&texture, None, None);
+        self.canvas.present();
+    }
+    fn poll_input(&mut self) -> InputState {
+        let mut input = InputState::default();
+        for event in self.events.poll_iter() {
+            match event {
+                Event::Quit {..} => input.quit = true,
+                // A held key repeats; only its first press counts.
+                Event::KeyDown {keycode: Some(k), repeat: false, ..} => input.pressed.extend(key(k)),
+                Event::KeyUp {keycode: Some(k), ..} => input.released.extend(key(k)),
+                Event::Window {win_event: WindowEvent::SizeChanged(w, h), ..} => input.resized = Some((w as isize, h as isize)),
+                Event::MouseMotion {x, y, ..} => input.mouse = Some((x.max(0) as u16, y.max(0) as u16)),
+                Event::MouseButtonDown {mouse_btn, x, y, ..} | Event::MouseButtonUp {mouse_btn, x, y, ..} => {
+                    input.mouse = Some((x.max(0) as u16, y.max(0) as u16));
+                    let down = matches!(event, Event::MouseButtonDown {..});
+                    match mouse_btn {
+                        MouseButton::Left => if down {input.clicked = true;} else {input.unclicked = true;},
+                        MouseButton::Middle => if down {input.middle_clicked = true;} else {input.middle_unclicked = true;},
+                        _ => {}
+                    }
+                },
+                Event::MouseWheel {y, direction, mouse_x, mouse_y, ..} => {
+                    input.mouse = Some((mouse_x.max(0) as u16, mouse_y.max(0) as u16));
+                    input.wheel += if direction == MouseWheelDirection::Flipped {-y} else {y};
+                },
+                _ => {}
+            }
+        }
+        input
+    }
+    fn set_title(&mut self, title: &str) {
+        let _ = self.canvas.window_mut().set_title(title);
+    }
+    // Fullscreen takes the desktop's size, which SDL reports afterwards as a
+    // resize for the frame to follow.
+    fn set_mode(&mut self, w: isize, h: isize, fullscreen: bool) -> Result<(), String> {
+        let window = self.canvas.window_mut();
+        window.set_fullscreen(if fullscreen {FullscreenType::Desktop} else {FullscreenType::Off})?;
+        if !fullscreen && window.size() != (w as u32, h as u32) {
+            window.set_size(w as u32, h as u32).map_err(|err| err.to_string())?;
+        }
+        self.frame = Framebuffer::new(w as usize, h as usize);
+        Ok(())
+    }
+}
+
+// SDL names its keys as Key does.
+macro_rules! same_keys {
+    ($key:expr, $($name:ident),*) => {
+        match $key {
+            $(Keycode::$name => Some(Key::$name),)*
+            _ => None,
+        }
+    };
+}
+
+fn key(k: Keycode) -> Option<Key> {
+    same_keys!(k,
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Num0, Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9, KpPlus, KpMinus,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Up, Down, Left, Right, Home, End, PageUp, PageDown,
+        Space, Return, Tab, Escape,
+        Comma, Period, Slash, Minus, Equals, LeftBracket, RightBracket, Semicolon, Quote, Backslash,
+        LCtrl, RCtrl)
+}
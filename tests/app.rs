@@ -0,0 +1,81 @@
+// Drives the window's run with no window: a renderer that draws into memory
+// and plays back a frame's worth of input at a time, then closes.
+extern crate chord_sim;
+
+use std::collections::VecDeque;
+
+use chord_sim::app::App;
+use chord_sim::cli;
+use chord_sim::render::{Framebuffer, InputState, Key, PixelFormat, Renderer};
+use chord_sim::screen::Screen;
+
+struct Scripted {
+    frame: Framebuffer,
+    inputs: VecDeque<InputState>,
+}
+
+impl Renderer for Scripted {
+    fn size(&self) -> (isize, isize) {
+        self.frame.size()
+    }
+    fn format(&self) -> PixelFormat {
+        self.frame.format()
+    }
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, rgb: (u8, u8, u8)) {
+        self.frame.fill_rect(x, y, w, h, rgb);
+    }
+    fn with_pixels(&mut self, f: &mut dyn FnMut(&mut [u8], usize)) -> Result<(), String> {
+        self.frame.with_pixels(f)
+    }
+    fn present(&mut self) {}
+    // Once the script runs out the window is closed.
+    fn poll_input(&mut self) -> InputState {
+        self.inputs.pop_front().unwrap_or(InputState {quit: true, ..InputState::default()})
+    }
+    fn set_title(&mut self, _title: &str) {}
+    fn set_mode(&mut self, w: isize, h: isize, _fullscreen: bool) -> Result<(), String> {
+        self.frame = Framebuffer::new(w as usize, h as usize);
+        Ok(())
+    }
+}
+
+// An app over the given arguments whose frames bring the keys of each
+// entry of frames, as presses.
+fn app(args: &[&str], frames: &[&[Key]]) -> App {
+    let inputs = frames.iter().map(|keys| InputState {pressed: keys.to_vec(), ..InputState::default()}).collect();
+    let screen = Screen::with_renderer(Box::new(Scripted {frame: Framebuffer::new(160, 120), inputs}));
+    let options = cli::parse(args.iter().map(|a| a.to_string())).unwrap();
+    App::new(screen, &options).unwrap()
+}
+
+#[test]
+fn steps_per_frame_ticks_and_space_pauses() {
+    let mut app = app(&["--steps-per-frame", "4", "--fps", "0"], &[&[], &[], &[Key::Space], &[], &[]]);
+    let mut ticks = Vec::new();
+    while app.frame() {
+        ticks.push(app.chord().ticks());
+    }
+    assert_eq!(ticks, [4, 8, 12, 12, 12]);
+    app.run();
+}
+
+#[test]
+fn r_puts_the_string_back() {
+    let mut app = app(&["--steps-per-frame", "10", "--fps", "0"], &[&[Key::P], &[], &[], &[Key::R]]);
+    let initial = app.chord().dots().to_vec();
+    for _ in 0..3 {
+        assert!(app.frame());
+    }
+    assert!(app.chord().dots() != &initial[..]);
+    assert!(app.frame());
+    assert!(app.chord().dots() == &initial[..]);
+    app.run();
+}
+
+#[test]
+fn escape_closes_the_window() {
+    let mut app = app(&["--fps", "0"], &[&[], &[Key::Escape]]);
+    assert!(app.frame());
+    assert!(!app.frame());
+    app.run();
+}
@@ -0,0 +1,78 @@
+// The ends as Boundary sets them: free ends let the whole string go, and a
+// periodic string is a ring a wave can go round.
+extern crate chord_sim;
+
+use std::f64::consts::PI;
+
+use chord_sim::{Boundary, Chord, Integrator};
+
+#[test]
+fn a_free_string_drifts_with_its_initial_velocity() {
+    let mut chord = Chord::from_shape(40, 1./256., |_| 0.);
+    chord.set_boundary(Boundary::Free);
+    chord.excite_velocity(|_| 0.01);
+    let start = chord.center_of_mass();
+    chord.run(1000);
+    let moved = chord.center_of_mass() - start;
+    assert!((moved.y - 0.01*chord.time()).abs() < 1e-9, "the centre of mass rose {} in {}", moved.y, chord.time());
+    assert!(moved.x.abs() < 1e-9, "the centre of mass went {} along", moved.x);
+}
+
+#[test]
+fn a_free_string_stays_free_when_reset() {
+    for &boundary in &[Boundary::Free, Boundary::Periodic] {
+        let mut chord = Chord::new(40, 1./256.);
+        chord.set_boundary(boundary);
+        chord.run(100);
+        chord.reset();
+        let dots = chord.dots();
+        assert!(!dots[0].is_fixed() && !dots[dots.len()-1].is_fixed(), "{:?}: reset pinned the ends again", boundary);
+    }
+    // And a string fixed again is fixed after reset.
+    let mut chord = Chord::new(40, 1./256.);
+    chord.set_boundary(Boundary::Free);
+    chord.set_boundary(Boundary::Fixed);
+    chord.reset();
+    let dots = chord.dots();
+    assert!(dots[0].is_fixed() && dots[dots.len()-1].is_fixed(), "reset let the ends go");
+}
+
+#[test]
+fn a_sine_goes_round_a_periodic_string() {
+    let (n, amplitude) = (40u16, 0.5);
+    let mut chord = Chord::from_shape(n, 1./64., |_| 0.);
+    chord.set_boundary(Boundary::Periodic);
+    // One wavelength round the ring of n + 1 segments, moving as a wave
+    // going right does.
+    let wavenumber = 2.*PI/(n as f64 + 1.);
+    let omega = 2.*chord.wave_speed()/chord.spacing()*(wavenumber*chord.spacing()/2.).sin();
+    chord.excite(|u| amplitude*(wavenumber*u*n as f64).sin());
+    chord.excite_velocity(|u| -amplitude*omega*(wavenumber*u*n as f64).cos());
+    // A quarter of a period on, a standing wave would be flat; this one
+    // has gone a quarter of the way round.
+    chord.run((PI/2./omega/chord.dt()).round() as u64);
+    let phase = omega*chord.time();
+    for (i, d) in chord.dots().iter().enumerate() {
+        let expected = amplitude*(wavenumber*i as f64 - phase).sin();
+        assert!((d.pos().y - expected).abs() < 0.02*amplitude, "dot {} at {} rather than {}", i, d.pos().y, expected);
+    }
+}
+
+// Free at both ends nothing outside pushes on the string, and the
+// symplectic steps add each spring's pull to both its dots, so the
+// momentum it set off with stays to the last rounding however it wobbles.
+#[test]
+fn a_free_string_keeps_its_momentum() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet] {
+        let mut chord = Chord::from_shape(40, 1./256., |u| 0.2*(PI*u).sin());
+        chord.set_boundary(Boundary::Free);
+        chord.set_integrator(integrator);
+        chord.excite_velocity(|_| 0.01);
+        let start = chord.total_momentum();
+        for _ in 0..100 {
+            chord.run(100);
+            let now = chord.total_momentum();
+            assert!((now - start).size() < 1e-15, "{:?}: momentum {} from {} after {}", integrator, now, start, chord.time());
+        }
+    }
+}
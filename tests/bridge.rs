@@ -0,0 +1,34 @@
+// The lossy bridge: how fast the string dies depends on the resistance at
+// its end, fastest near the string's own impedance.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+// Ticks the pickup's RMS, over a period at a time, takes to fall 20 dB
+// from the first period's, or None if it doesn't within limit ticks.
+fn decay_ticks(resistance: f64, limit: u64) -> Option<u64> {
+    let mut chord = Chord::new_pluck(40, 1./1024., 0.2, 5.);
+    chord.set_termination(0., resistance);
+    let period = (1./chord.linear_fundamental()).round() as u64;
+    let mut first = None;
+    while chord.ticks() < limit {
+        let rms = ((0..period).map(|_| {chord.tick(); chord.pickup(0.1).powi(2)}).sum::<f64>()/period as f64).sqrt();
+        match first {
+            None => first = Some(rms),
+            Some(start) if rms < start/10. => return Some(chord.ticks()),
+            Some(_) => {},
+        }
+    }
+    None
+}
+
+#[test]
+fn more_resistance_up_to_the_impedance_dies_faster() {
+    let impedance = Chord::new(40, 1./1024.).impedance();
+    assert!(0.02 < impedance);
+    let near = decay_ticks(0.02, 500_000).expect("the string should have died at a resistance of 0.02");
+    let low = decay_ticks(0.004, 500_000).expect("the string should have died at a resistance of 0.004");
+    assert!(near < low, "20 dB down in {} ticks at 0.02 and {} at 0.004", near, low);
+    // Far past the impedance the end barely moves, and the string rings on.
+    assert!(decay_ticks(1., 4*low).is_none(), "a resistance of 1 let the string die within {} ticks", 4*low);
+}
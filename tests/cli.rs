@@ -0,0 +1,98 @@
+// Where each setting comes from: the command line over the config file over
+// the defaults, through --dump-config and a reload of the file too.
+extern crate chord_sim;
+
+use std::fs;
+use std::path::PathBuf;
+
+use chord_sim::cli::{self, Options};
+
+// A config file of this name and text in the temporary directory, gone
+// once the test is.
+struct Temp(PathBuf);
+
+impl Temp {
+    fn new(name: &str, text: &str) -> Temp {
+        let path = std::env::temp_dir().join(format!("chord_sim-{}-{}.toml", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+        Temp(path)
+    }
+    fn arg(&self) -> String {
+        self.0.display().to_string()
+    }
+}
+
+impl Drop for Temp {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn parse(args: &[&str]) -> Result<Options, String> {
+    cli::parse(args.iter().map(|a| a.to_string()))
+}
+
+const FILE: &str = "[simulation]\ndots = 50\nk = 0.002\ndamping = 0.0001\n\n[display]\nwidth = 640\n";
+
+#[test]
+fn the_file_fills_in_what_the_flags_leave_and_the_defaults_the_rest() {
+    let empty = Temp::new("empty", "");
+    let file = Temp::new("fills", FILE);
+    let defaults = parse(&["--config", &empty.arg()]).unwrap();
+    let options = parse(&["--config", &file.arg(), "--dots", "70", "--width", "800"]).unwrap();
+    // The flags win...
+    assert_eq!(options.segments, 69);
+    assert_eq!(options.width, 800);
+    // ...the file has the rest it sets...
+    assert_eq!(options.k, 0.002);
+    assert_eq!(options.damping, 0.0001);
+    // ...and what neither sets is as it always is.
+    assert_eq!(options.dt, defaults.dt);
+    assert_eq!(options.height, defaults.height);
+    assert_eq!(options.position, defaults.position);
+}
+
+#[test]
+fn dump_config_shows_the_merged_settings() {
+    let file = Temp::new("dump", FILE);
+    let options = parse(&["--config", &file.arg(), "--dots", "70", "--dump-config"]).unwrap();
+    assert!(options.dump_config);
+    let dumped = options.to_config().to_toml();
+    assert!(dumped.contains("dots = 70"), "{}", dumped);
+    assert!(dumped.contains("k = 0.002"), "{}", dumped);
+    assert!(dumped.contains("width = 640"), "{}", dumped);
+}
+
+#[test]
+fn the_flags_still_win_when_the_file_is_reloaded() {
+    let file = Temp::new("reload", FILE);
+    let options = parse(&["--config", &file.arg(), "--dots", "70"]).unwrap();
+    fs::write(&file.0, "[simulation]\ndots = 30\nk = 0.004\n").unwrap();
+    let reloaded = cli::reload(&options, &file.0).unwrap();
+    assert_eq!(reloaded.segments, 69);
+    assert_eq!(reloaded.k, 0.004);
+    // What the new file leaves out goes back to the default.
+    assert_eq!(reloaded.damping, 0.);
+}
+
+#[test]
+fn a_bad_value_in_the_file_is_an_error_unless_a_flag_covers_it() {
+    let file = Temp::new("bad", "[simulation]\nk = -1.0\n");
+    let err = parse(&["--config", &file.arg()]).err().expect("a negative k should be refused");
+    assert!(err.contains("k must be positive"), "{}", err);
+    assert_eq!(parse(&["--config", &file.arg(), "--k", "0.001"]).unwrap().k, 0.001);
+}
+
+#[test]
+fn unknown_keys_are_only_warned_about() {
+    let file = Temp::new("unknown", "[simulation]\ndots = 50\nspeed_of_light = 1.0\n");
+    assert_eq!(parse(&["--config", &file.arg()]).unwrap().segments, 49);
+}
+
+#[test]
+fn a_missing_named_file_is_an_error_but_not_for_help() {
+    let missing = std::env::temp_dir().join(format!("chord_sim-{}-missing.toml", std::process::id()));
+    let missing = missing.display().to_string();
+    assert!(parse(&["--config", &missing]).is_err());
+    assert!(parse(&["--config", &missing, "--help"]).unwrap().help);
+}
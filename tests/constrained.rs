@@ -0,0 +1,34 @@
+// The constrained step puts every segment back to length each tick, so
+// however hard the string is plucked it doesn't stretch, where the springs
+// let it.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+fn length(chord: &Chord) -> f64 {
+    chord.dots().windows(2).map(|w| (w[1].pos() - w[0].pos()).size()).sum()
+}
+
+// Furthest the string's length gets from where it started over ticks, as a
+// fraction of it.
+fn stretch(height: f64, integrator: Integrator, ticks: u64) -> f64 {
+    let mut chord = Chord::new_pluck(80, 1./4096., 0.5, height);
+    chord.set_integrator(integrator);
+    let start = length(&chord);
+    let mut furthest: f64 = 0.;
+    for _ in 0..ticks {
+        chord.tick();
+        furthest = furthest.max((length(&chord)/start - 1.).abs());
+    }
+    furthest
+}
+
+#[test]
+fn a_huge_pluck_keeps_its_length_within_a_thousandth() {
+    for &height in &[15., 30.] {
+        let constrained = stretch(height, Integrator::Constrained, 20_000);
+        assert!(constrained < 1e-3, "{} high: the constrained string stretched by {:.4}%", height, 100.*constrained);
+    }
+    let springs = stretch(15., Integrator::Verlet, 20_000);
+    assert!(springs > 0.02, "the springs stretched the string by only {:.4}%", 100.*springs);
+}
@@ -0,0 +1,71 @@
+// Damping: none at all leaves the tick as it was, and any takes the
+// swing out of the string.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+const INTEGRATORS: [Integrator; 4] = [Integrator::Euler, Integrator::Verlet, Integrator::Rk4, Integrator::Constrained];
+
+// Largest displacement of any dot over the last ticks of a run of ticks.
+fn late_swing(chord: &mut Chord, ticks: u64, last: u64) -> f64 {
+    chord.run(ticks - last);
+    let mut swing: f64 = 0.;
+    for _ in 0..last {
+        chord.tick();
+        swing = chord.dots().iter().map(|d| d.pos().y.abs()).fold(swing, f64::max);
+    }
+    swing
+}
+
+#[test]
+fn no_damping_ticks_to_the_bit_as_undamped() {
+    for &integrator in &INTEGRATORS {
+        let mut undamped = Chord::new(80, 1./4096.);
+        let mut damped = Chord::new(80, 1./4096.);
+        undamped.set_integrator(integrator);
+        damped.set_integrator(integrator);
+        damped.set_damping(0.01);
+        damped.set_damping(0.);
+        for _ in 0..1000 {
+            undamped.tick();
+            damped.tick();
+        }
+        for (u, d) in undamped.dots().iter().zip(damped.dots()) {
+            assert_eq!((u.pos(), u.vel()), (d.pos(), d.vel()), "{:?}", integrator);
+        }
+    }
+}
+
+#[test]
+fn damping_lowers_the_largest_displacement() {
+    let mut undamped = Chord::new(80, 1./4096.);
+    let mut damped = Chord::new(80, 1./4096.);
+    damped.set_damping(1e-3);
+    let free = late_swing(&mut undamped, 20_000, 2_000);
+    let held = late_swing(&mut damped, 20_000, 2_000);
+    assert!(held < 0.9*free, "damped swing {} against {} undamped", held, free);
+}
+
+// Energy in the next nine sine modes over the first's.
+fn overtones_over_fundamental(chord: &Chord) -> f64 {
+    let energies = chord.mode_energies(10);
+    energies[1..].iter().sum::<f64>()/energies[0]
+}
+
+#[test]
+fn damping2_takes_the_overtones_out_first() {
+    let mut curved = Chord::new_pluck(40, 1./1024., 0.3, 0.5);
+    let mut uniform = curved.clone();
+    let start = overtones_over_fundamental(&curved);
+    curved.set_damping2(0.01);
+    // The uniform damping damping2 gives the first mode, which then dies at
+    // much the same rate on both strings.
+    uniform.set_damping(4.*0.01*curved.mode_sine().powi(2));
+    curved.run(10_000);
+    uniform.run(10_000);
+    let (first_curved, first_uniform) = (curved.mode_energies(1)[0], uniform.mode_energies(1)[0]);
+    assert!((first_curved/first_uniform - 1.).abs() < 0.05, "the first mode has {} with damping2 and {} without", first_curved, first_uniform);
+    let (with, without) = (overtones_over_fundamental(&curved)/start, overtones_over_fundamental(&uniform)/start);
+    assert!(without > 0.5, "uniform damping took the overtones down to {} of the fundamental", without);
+    assert!(with < 0.2*without, "damping2 left the overtones at {} of where they were against {}", with, without);
+}
@@ -0,0 +1,75 @@
+// What the string is heard at against what the theory of the discrete string
+// says it should be.
+extern crate chord_sim;
+
+use std::f64::consts::PI;
+
+use chord_sim::Chord;
+
+#[test]
+fn crossing_period_agrees_with_linear_fundamental() {
+    for &(n, k) in &[(24, 1./1024.), (60, 1./256.), (120, 1./16.)] {
+        let chord = Chord::new_harmonic(n, k, 1, 0.5);
+        let period = chord.crossing_period(n as usize/2, 20).expect("the middle dot should cross zero");
+        let expected = 1./chord.linear_fundamental();
+        let error = (period - expected).abs()/expected;
+        assert!(error < 0.005, "n = {}, k = {}: period {} against {}, {:.3}% off", n, k, period, expected, 100.*error);
+    }
+}
+
+#[test]
+fn a_heavier_string_sounds_lower() {
+    let light = Chord::new_harmonic(40, 1./1024., 1, 0.5);
+    let mut heavy = light.clone();
+    heavy.set_mass_profile(|_| 4.);
+    let light_period = light.crossing_period(20, 10).expect("the light string should cross zero");
+    let heavy_period = heavy.crossing_period(20, 10).expect("the heavy string should cross zero");
+    // Four times the mass is half the frequency.
+    let ratio = heavy_period/light_period;
+    assert!((ratio - 2.).abs() < 0.01, "heavy period {} against light {}, ratio {}", heavy_period, light_period, ratio);
+}
+
+// How many times the first mode's frequency the second mode rings at.
+fn second_over_first(stiffness: f64) -> f64 {
+    let mut first = Chord::new_harmonic(40, 1./1024., 1, 0.5);
+    let mut second = Chord::new_harmonic(40, 1./1024., 2, 0.5);
+    first.set_stiffness(stiffness);
+    second.set_stiffness(stiffness);
+    let first_period = first.crossing_period(20, 10).expect("the first mode should cross zero");
+    // The middle is the second mode's node; a quarter along is its peak.
+    let second_period = second.crossing_period(10, 10).expect("the second mode should cross zero");
+    first_period/second_period
+}
+
+#[test]
+fn stiffness_sharpens_the_second_mode() {
+    let flexible = second_over_first(0.);
+    let stiff = second_over_first(4./1024.);
+    assert!((flexible - 2.).abs() < 0.002, "without stiffness the second mode is {} times the first", flexible);
+    assert!(stiff > 2.1, "with stiffness the second mode is only {} times the first", stiff);
+}
+
+#[test]
+fn the_detected_fundamental_is_the_discrete_string_s() {
+    for &(n, k, dt) in &[(40, 1./1024., 1.), (60, 1./256., 0.5)] {
+        let mut chord = Chord::new_sine(n, k);
+        chord.set_dt(dt);
+        // Unit masses a unit apart, held by springs of rest length l under
+        // a tension of k(1 - l): ω = 2√(k(1 - l))·sin(π/2N).
+        let tension = k*(1. - chord.rest_length());
+        let expected = 2.*tension.sqrt()*(PI/(2.*n as f64)).sin()/(2.*PI);
+        let found = chord.fundamental_frequency(1./dt).expect("a sine should have a peak");
+        let error = (found - expected).abs()/expected;
+        assert!(error < 0.03, "n = {}, k = {}, dt = {}: found {} against {}, {:.2}% off", n, k, dt, found, expected, 100.*error);
+    }
+}
+
+#[test]
+fn a_string_tuned_to_110_hz_is_heard_at_110_hz() {
+    let dt = 1./44_100.;
+    let mut chord = Chord::new_harmonic(40, 1./1024., 1, 0.5);
+    let k = chord.tune_to(110., dt);
+    assert_eq!(k, chord.k());
+    let heard = chord.fundamental_frequency(1./dt).expect("a tuned string should have a peak");
+    assert!((heard - 110.).abs() < 1.1, "tuned to k = {} and heard at {} Hz", k, heard);
+}
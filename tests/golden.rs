@@ -0,0 +1,25 @@
+// Checks each golden scene against its image in goldens/, or writes the
+// images afresh with UPDATE_GOLDENS=1.
+extern crate chord_sim;
+
+use chord_sim::golden::{self, Outcome, HEIGHT, SCENES, WIDTH};
+
+#[test]
+fn scenes_match_their_goldens() {
+    let mut failed = Vec::new();
+    for name in &SCENES {
+        match golden::scene(name).and_then(|rgb| golden::check(name, WIDTH, HEIGHT, &rgb)) {
+            Ok(Outcome::Matched) => {},
+            Ok(Outcome::Written) => println!("{}: wrote {}/{}.bmp", name, golden::DIR, name),
+            Ok(Outcome::Differs {pixels, actual}) => failed.push(format!("{}: {} pixels differ, drawn as {}", name, pixels, actual.display())),
+            Err(err) => failed.push(format!("{}: {}", name, err)),
+        }
+    }
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+#[test]
+fn scenes_fill_the_frame() {
+    let rgb = golden::scene(SCENES[0]).unwrap();
+    assert_eq!(rgb.len(), WIDTH*HEIGHT*3);
+}
@@ -0,0 +1,63 @@
+// How well each integrator keeps what an undamped string should keep: its
+// energy, and a single mode's amplitude.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+// Furthest the total energy gets from where it started over ticks, as a
+// fraction of it.
+fn energy_drift(mut chord: Chord, integrator: Integrator, ticks: u64) -> f64 {
+    chord.set_integrator(integrator);
+    let start = chord.total_energy();
+    let mut drift: f64 = 0.;
+    for _ in 0..ticks {
+        chord.tick();
+        drift = drift.max((chord.total_energy() - start).abs());
+    }
+    drift/start
+}
+
+#[test]
+fn verlet_drifts_far_less_energy_than_euler() {
+    let euler = energy_drift(Chord::new(80, 1./4096.), Integrator::Euler, 10_000);
+    let verlet = energy_drift(Chord::new(80, 1./4096.), Integrator::Verlet, 10_000);
+    assert!(verlet < 1e-6, "Verlet drifted by {:e}", verlet);
+    assert!(euler > 50.*verlet, "Euler drifted by {:e} against Verlet's {:e}", euler, verlet);
+}
+
+// Largest displacement of the middle dot over the coming ticks.
+fn middle_peak(chord: &mut Chord, ticks: u64) -> f64 {
+    let middle = chord.dots().len()/2;
+    let mut peak: f64 = 0.;
+    for _ in 0..ticks {
+        chord.tick();
+        peak = peak.max(chord.dots()[middle].pos().y.abs());
+    }
+    peak
+}
+
+#[test]
+fn rk4_keeps_a_sine_within_a_percent_over_50k_steps() {
+    let mut chord = Chord::new_sine(40, 1./1024.);
+    chord.set_integrator(Integrator::Rk4);
+    let period = (1./chord.linear_fundamental()).ceil() as u64;
+    let first = middle_peak(&mut chord, period);
+    chord.run(50_000 - 2*period);
+    let last = middle_peak(&mut chord, period);
+    assert_eq!(chord.ticks(), 50_000);
+    assert!((last - first).abs() < 0.01*first, "the sine swung {} at first and {} at the end", first, last);
+}
+
+#[test]
+fn the_symplectic_steps_keep_a_sine_s_energy() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet] {
+        let chord = Chord::new_sine(40, 1./1024.);
+        let periods = (2./chord.linear_fundamental()).ceil() as u64;
+        let early = energy_drift(chord.clone(), integrator, periods);
+        let late = energy_drift(chord, integrator, 25*periods);
+        // The energy wobbles about where it started as the string swings,
+        // but the wobble doesn't grow.
+        assert!(late < 1e-3, "{:?} drifted by {:e}", integrator, late);
+        assert!(late < 1.5*early, "{:?} drifted by {:e} over two periods and {:e} over 50", integrator, early, late);
+    }
+}
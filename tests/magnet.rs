@@ -0,0 +1,34 @@
+// The pickup magnet: the string settles a little toward it, and rings a
+// little flat for the pull.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+const STRENGTH: f64 = 1e-5;
+const OFFSET: f64 = 5.;
+
+#[test]
+fn the_string_sags_toward_the_magnet() {
+    let mut chord = Chord::from_shape(40, 1./1024., |_| 0.);
+    chord.set_magnet(0.5, OFFSET, STRENGTH);
+    chord.relax(20_000, 0.05);
+    let heights: Vec<f64> = chord.dots().iter().map(|d| d.pos().y).collect();
+    assert!(heights[1..40].iter().all(|&y| y > 0.), "some dot didn't come toward the magnet: {:?}", heights);
+    let deepest = (0..heights.len()).max_by(|&a, &b| heights[a].total_cmp(&heights[b])).unwrap();
+    assert_eq!(deepest, 20, "the string sagged furthest at dot {}", deepest);
+    assert!(chord.sag() > 0.01 && chord.sag() < 0.1*OFFSET, "the string sagged {}", chord.sag());
+    assert_eq!(chord.sag(), heights[20]);
+}
+
+#[test]
+fn the_magnet_pulls_the_pitch_down_a_little() {
+    let mut free = Chord::new_harmonic(40, 1./1024., 1, 0.5);
+    let mut pulled = free.clone();
+    pulled.set_magnet(0.5, OFFSET, STRENGTH);
+    free.relax(20_000, 0.05);
+    pulled.relax(20_000, 0.05);
+    let free_pitch = free.fundamental_frequency(1.).unwrap();
+    let pulled_pitch = pulled.fundamental_frequency(1.).unwrap();
+    let shift = pulled_pitch/free_pitch - 1.;
+    assert!(shift < -0.001 && shift > -0.05, "the magnet moved the pitch from {} to {}, {:+.2}%", free_pitch, pulled_pitch, 100.*shift);
+}
@@ -0,0 +1,46 @@
+// The drum head: its rim stays where it was pinned, and a hit in the middle
+// spreads out the same every way the grid looks alike.
+extern crate chord_sim;
+
+use chord_sim::Membrane;
+
+#[test]
+fn the_rim_never_moves() {
+    for &diagonals in &[false, true] {
+        let mut drum = Membrane::new_strike(21, 15, 1./16., 3., 0.5);
+        drum.set_diagonals(diagonals);
+        let rim: Vec<_> = drum.dots().iter().filter(|d| d.is_fixed()).map(|d| (d.pos(), d.vel())).collect();
+        assert_eq!(rim.len(), 2*21 + 2*13);
+        for _ in 0..2000 {
+            drum.tick();
+        }
+        let now: Vec<_> = drum.dots().iter().filter(|d| d.is_fixed()).map(|d| (d.pos(), d.vel())).collect();
+        assert_eq!(now, rim, "diagonals {}", diagonals);
+        assert!(drum.dots().iter().any(|d| d.pos().z.abs() > 1e-3), "the hit went nowhere");
+    }
+}
+
+#[test]
+fn a_hit_in_the_middle_stays_symmetric() {
+    let n = 21;
+    for &diagonals in &[false, true] {
+        let mut drum = Membrane::new_strike(n, n, 1./16., 3., 0.5);
+        drum.set_diagonals(diagonals);
+        for _ in 0..10 {
+            for _ in 0..100 {
+                drum.tick();
+            }
+            for j in 0..n {
+                for i in 0..n {
+                    let z = drum.dot(i, j).pos().z;
+                    // Mirrored across, down, and along the diagonal.
+                    for &(a, b) in &[(n-1-i, j), (i, n-1-j), (j, i)] {
+                        let mirror = drum.dot(a, b).pos().z;
+                        assert!((z - mirror).abs() < 1e-12, "diagonals {}, at {}: dot ({}, {}) is at {} and ({}, {}) at {}",
+                            diagonals, drum.time(), i, j, z, a, b, mirror);
+                    }
+                }
+            }
+        }
+    }
+}
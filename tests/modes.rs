@@ -0,0 +1,51 @@
+// How the string's shape and energy split between its sine modes.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+#[test]
+fn a_harmonic_has_all_its_amplitude_in_its_mode() {
+    for &(n, mode) in &[(40, 1), (40, 3), (64, 7), (100, 12)] {
+        let amplitudes = Chord::new_harmonic(n, 1./1024., mode, 2.5).mode_amplitudes(16);
+        for (m, &a) in amplitudes.iter().enumerate() {
+            let expected = if m + 1 == mode as usize {2.5} else {0.};
+            assert!((a - expected).abs() < 1e-12, "n = {}, mode {}: mode {} has {}", n, mode, m + 1, a);
+        }
+    }
+}
+
+#[test]
+fn a_harmonic_s_nodes_stay_put() {
+    let n = 60;
+    for &mode in &[2, 3, 5] {
+        let mut chord = Chord::new_harmonic(n, 1./1024., mode, 2.5);
+        let nodes: Vec<usize> = (1..mode).map(|j| (n*j/mode) as usize).collect();
+        for tick in 0..1000 {
+            chord.tick();
+            for &i in &nodes {
+                let y = chord.dots()[i].pos().y;
+                assert!(y.abs() < 1e-9, "mode {}: the node at dot {} is at {} after {} ticks", mode, i, y, tick + 1);
+            }
+        }
+    }
+}
+
+#[test]
+fn a_third_harmonic_keeps_its_energy_in_the_third_mode() {
+    let (n, k) = (60, 1./1024.);
+    let mut chord = Chord::new_harmonic(n, k, 3, 0.1);
+    // What the string holds just for being stretched between its ends.
+    let flat = Chord::from_shape(n, k, |_| 0.).total_energy();
+    let plucked = chord.total_energy() - flat;
+    for _ in 0..5 {
+        let energies = chord.mode_energies(10);
+        for (m, &e) in energies.iter().enumerate() {
+            if m == 2 {
+                assert!((e - plucked).abs() < 0.01*plucked, "after {} ticks mode 3 has {} of {}", chord.ticks(), e, plucked);
+            } else {
+                assert!(e < 1e-6*plucked, "after {} ticks mode {} has {} of {}", chord.ticks(), m + 1, e, plucked);
+            }
+        }
+        chord.run(777);
+    }
+}
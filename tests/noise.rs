@@ -0,0 +1,31 @@
+// The random push on the dots: the same seed pushes the same way every
+// time, and no amplitude doesn't push at all.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+fn bits(chord: &Chord) -> Vec<[u64; 4]> {
+    chord.dots().iter().map(|d| [d.pos().x.to_bits(), d.pos().y.to_bits(), d.vel().x.to_bits(), d.vel().y.to_bits()]).collect()
+}
+
+fn noisy(amplitude: f64, seed: u64) -> Chord {
+    let mut chord = Chord::new(60, 1./1024.);
+    chord.set_noise(amplitude, seed);
+    chord.run(3000);
+    chord
+}
+
+#[test]
+fn a_fixed_seed_gives_the_same_trajectory_every_time() {
+    let (first, second) = (noisy(1e-3, 42), noisy(1e-3, 42));
+    assert_eq!(bits(&first), bits(&second));
+    assert_ne!(bits(&first), bits(&noisy(1e-3, 43)), "another seed pushed the same way");
+    assert_ne!(bits(&first), bits(&noisy(0., 42)), "the noise didn't push at all");
+}
+
+#[test]
+fn no_amplitude_is_no_noise() {
+    let mut quiet = Chord::new(60, 1./1024.);
+    quiet.run(3000);
+    assert_eq!(bits(&noisy(0., 42)), bits(&quiet));
+}
@@ -0,0 +1,36 @@
+// Splitting a tick across threads mustn't change it: each dot's sums are
+// done in the same order whichever thread does them.
+extern crate chord_sim;
+extern crate rayon;
+
+use chord_sim::{Chord, Integrator};
+
+// The string after ticks with the given threshold, as (x, y) pairs. The
+// ticks run in a pool of four threads, so they split even on one core.
+fn ticked(mut chord: Chord, threshold: usize, ticks: usize) -> Vec<(u64, u64)> {
+    chord.set_parallel_threshold(threshold);
+    let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    pool.install(|| {
+        for _ in 0..ticks {
+            chord.tick();
+        }
+    });
+    chord.dots().iter().map(|d| (d.pos().x.to_bits(), d.pos().y.to_bits())).collect()
+}
+
+#[test]
+fn split_ticks_match_unsplit_ones_to_the_bit() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet, Integrator::Rk4, Integrator::Constrained] {
+        let mut chord = Chord::new_pluck(3000, 1./4096., 0.3, 40.);
+        chord.set_integrator(integrator);
+        chord.set_stiffness(1e-4);
+        chord.set_damping(1e-4);
+        assert!(ticked(chord.clone(), 0, 100) == ticked(chord, usize::MAX, 100), "{:?} differs", integrator);
+    }
+}
+
+#[test]
+fn a_big_string_splits_the_same_as_it_ticks_alone() {
+    let chord = Chord::new_strike(30_000, 1./4096., 0.2, 0.01, 5.);
+    assert!(ticked(chord.clone(), 0, 10) == ticked(chord, usize::MAX, 10));
+}
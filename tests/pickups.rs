@@ -0,0 +1,37 @@
+// Two pickups along the string hear its modes differently: one sitting on
+// a mode's node hears none of it.
+extern crate chord_sim;
+
+use std::f64::consts::PI;
+
+use chord_sim::{spectrum, Chord};
+
+// Loudest the spectrum of signal gets within a few bins of frequency.
+fn loudness_near(signal: &[f64], frequency: f64) -> f64 {
+    let bins = spectrum::spectrum(signal);
+    let width = bins[1].0 - bins[0].0;
+    bins.iter().filter(|&&(f, _)| (f - frequency).abs() < 3.*width).map(|&(_, m)| m).fold(0., f64::max)
+}
+
+#[test]
+fn a_pickup_on_a_node_hears_none_of_that_mode() {
+    let n = 60;
+    // The first and third modes together, small enough to stay apart.
+    let mut chord = Chord::from_shape(n, 1./256., |x| {
+        let u = x/n as f64;
+        0.2*(PI*u).sin() + 0.2*(3.*PI*u).sin()
+    });
+    let third = chord.mode_frequencies(3)[2]*chord.dt();
+    let (mut on_node, mut off_node) = (Vec::new(), Vec::new());
+    for _ in 0..1<<15 {
+        chord.tick();
+        on_node.push(chord.pickup(1./3.));
+        off_node.push(chord.pickup(0.1));
+    }
+    let (node, elsewhere) = (loudness_near(&on_node, third), loudness_near(&off_node, third));
+    assert!(elsewhere > 0.05, "the pickup off the node hears the third mode at only {}", elsewhere);
+    assert!(node < 1e-3*elsewhere, "the pickup on the node hears the third mode at {} against {}", node, elsewhere);
+    // Both hear the first mode.
+    let first = chord.mode_frequencies(1)[0]*chord.dt();
+    assert!(loudness_near(&on_node, first) > 0.05 && loudness_near(&off_node, first) > 0.02);
+}
@@ -0,0 +1,34 @@
+// The same string simulated in f32 and in f64: over a short run they agree
+// as far as f32 can say.
+extern crate chord_sim;
+
+use chord_sim::{Chord, ChordOf, Integrator};
+
+// Furthest apart any dot gets in the two over ticks, and the largest
+// displacement the f64 one reaches.
+fn apart(integrator: Integrator, ticks: u64) -> (f64, f64) {
+    let mut single = ChordOf::<f32>::new(40, 1./1024.);
+    let mut double = Chord::new(40, 1./1024.);
+    single.set_integrator(integrator);
+    double.set_integrator(integrator);
+    let (mut gap, mut peak): (f64, f64) = (0., 0.);
+    for _ in 0..ticks {
+        single.tick();
+        double.tick();
+        for (s, d) in single.dots().iter().zip(double.dots()) {
+            gap = gap.max((s.pos().y as f64 - d.pos().y).abs()).max((s.pos().x as f64 - d.pos().x).abs());
+            peak = peak.max(d.pos().y.abs());
+        }
+    }
+    (gap, peak)
+}
+
+#[test]
+fn f32_and_f64_agree_to_f32_precision() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet, Integrator::Rk4] {
+        let (gap, peak) = apart(integrator, 1000);
+        // f32 carries about seven digits, and a thousand ticks of rounding
+        // cost a few of them.
+        assert!(gap < 1e-4*peak, "{:?}: {} apart on a string {} high", integrator, gap, peak);
+    }
+}
@@ -0,0 +1,58 @@
+// Pulses sent down the string: one comes back off the fixed end upside
+// down, and a join onto a stiffer part splits one in two.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+// Height of the dot furthest from the axis, and where it is along the
+// string.
+fn extreme(chord: &Chord) -> (f64, f64) {
+    let d = chord.dots().iter().max_by(|a, b| a.pos().y.abs().total_cmp(&b.pos().y.abs())).unwrap();
+    (d.pos().y, d.pos().x)
+}
+
+#[test]
+fn a_pulse_flips_over_when_it_reflects() {
+    let n = 200;
+    let mut chord = Chord::new_pulse(n, 1./64., 0.3, 0.03, 1.);
+    let speed = chord.wave_speed();
+    let (height, at) = extreme(&chord);
+    assert_eq!(height, 1.);
+    // Halfway to the far end it's still itself...
+    chord.run((0.35*n as f64/speed/chord.dt()) as u64);
+    let (height, there) = extreme(&chord);
+    assert!(height > 0.9, "on the way out the pulse is {} high", height);
+    assert!((there - at - 0.35*n as f64).abs() < 3., "the pulse got from {} to {}", at, there);
+    // ...and on the way back from it, as far again, it's upside down.
+    chord.run((0.7*n as f64/speed/chord.dt()) as u64);
+    let (height, back) = extreme(&chord);
+    assert!(height < -0.9, "on the way back the pulse is {} high", height);
+    assert!((back - there).abs() < 3., "the pulse came back to {} rather than {}", back, there);
+}
+
+// Lowest height left of the join and highest right of it, once a pulse
+// from a quarter of the way along has crossed onto the second half of the
+// string, stiffer than the first by ratio.
+fn split(ratio: f64) -> (f64, f64) {
+    let n = 400;
+    let mut chord = Chord::new_pulse(n, 1./64., 0.25, 0.02, 0.2);
+    chord.set_k_profile(|u| if u < 0.5 {1.} else {ratio});
+    let join = chord.dots()[n as usize/2].pos().x;
+    chord.run((0.4*n as f64/chord.wave_speed()/chord.dt()) as u64);
+    let dots = chord.dots();
+    let reflected = dots.iter().filter(|d| d.pos().x < join).map(|d| d.pos().y).fold(f64::INFINITY, f64::min);
+    let transmitted = dots.iter().filter(|d| d.pos().x > join).map(|d| d.pos().y).fold(f64::NEG_INFINITY, f64::max);
+    (reflected, transmitted)
+}
+
+#[test]
+fn a_stiffer_half_reflects_part_of_a_pulse_and_passes_the_rest() {
+    let (reflected, transmitted) = split(4.);
+    // The README's −0.029 and 0.161.
+    assert!((reflected + 0.029).abs() < 0.002, "the reflected pulse is {} high", reflected);
+    assert!((transmitted - 0.161).abs() < 0.003, "the transmitted pulse is {} high", transmitted);
+    // With no join the pulse goes straight on.
+    let (reflected, transmitted) = split(1.);
+    assert!(reflected > -1e-3, "a uniform string reflected {}", reflected);
+    assert!(transmitted > 0.19, "a uniform string passed on only {}", transmitted);
+}
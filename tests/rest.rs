@@ -0,0 +1,44 @@
+// Strings at rest: with nothing to move them they stay as they are, and
+// under gravity relax finds the sag they hang in.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+#[test]
+fn a_flat_string_stays_flat() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet, Integrator::Rk4, Integrator::Constrained] {
+        for &rest_length in &[0., 0.5] {
+            let mut chord = Chord::from_shape(40, 1./256., |_| 0.);
+            chord.set_integrator(integrator);
+            chord.set_rest_length(rest_length);
+            chord.run(1000);
+            for (i, (d, start)) in chord.dots().iter().zip(chord.initial_dots()).enumerate() {
+                assert_eq!(d.pos().y, 0., "{:?}, rest length {}: dot {} rose", integrator, rest_length, i);
+                assert!((d.pos().x - start.pos().x).abs() < 1e-12, "{:?}, rest length {}: dot {} moved along to {}",
+                    integrator, rest_length, i, d.pos().x);
+            }
+        }
+    }
+}
+
+#[test]
+fn relaxing_without_gravity_leaves_a_flat_string_flat() {
+    let mut chord = Chord::from_shape(40, 1./1024., |_| 0.);
+    let before: Vec<_> = chord.dots().iter().map(|d| (d.pos(), d.vel())).collect();
+    chord.relax(5000, 0.05);
+    let after: Vec<_> = chord.dots().iter().map(|d| (d.pos(), d.vel())).collect();
+    assert_eq!(after, before);
+    assert_eq!(chord.sag(), 0.);
+}
+
+#[test]
+fn a_slacker_string_sags_further() {
+    let sags: Vec<f64> = [1./256., 1./1024., 1./4096.].iter().map(|&k| {
+        let mut chord = Chord::from_shape(20, k, |_| 0.);
+        chord.set_gravity(1e-5);
+        chord.relax(20_000, 0.01);
+        chord.sag()
+    }).collect();
+    assert!(sags[0] > 0., "the tightest string didn't sag");
+    assert!(sags.windows(2).all(|w| w[1] > 2.*w[0]), "sags {:?} as k falls fourfold each time", sags);
+}
@@ -0,0 +1,30 @@
+// Running a string forward, reversing its velocities and running it as long
+// again: Verlet comes back to where it started, Euler doesn't.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+// Furthest any dot ends up from where it started after ticks each way.
+fn round_trip_error(integrator: Integrator, ticks: u64) -> f64 {
+    let mut chord = Chord::new(40, 1./4096.);
+    chord.set_integrator(integrator);
+    let start: Vec<_> = chord.dots().iter().map(|d| d.pos()).collect();
+    chord.run(ticks);
+    chord.reverse_velocities();
+    chord.run(ticks);
+    chord.dots().iter().zip(&start).map(|(d, &p)| (d.pos() - p).size()).fold(0., f64::max)
+}
+
+#[test]
+fn verlet_runs_back_to_within_rounding() {
+    let error = round_trip_error(Integrator::Verlet, 10_000);
+    assert!(error < 1e-10, "Verlet came back {:e} out", error);
+}
+
+#[test]
+fn euler_comes_back_much_further_out() {
+    let verlet = round_trip_error(Integrator::Verlet, 1_000);
+    let euler = round_trip_error(Integrator::Euler, 1_000);
+    assert!(euler > 1e-4, "Euler came back only {:e} out", euler);
+    assert!(euler > 1e4*verlet, "Euler {:e} against Verlet {:e}", euler, verlet);
+}
@@ -0,0 +1,38 @@
+// Strings built from samples of their displacement and velocity, as from a
+// profile made elsewhere or another string's state.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+#[test]
+fn from_samples_gives_back_exactly_what_it_was_given() {
+    let samples: Vec<f64> = (0..33).map(|i| (i as f64*0.37).sin()*3.1 + 0.01*i as f64).collect();
+    let velocities: Vec<f64> = (0..33).map(|i| (i as f64*1.3).cos()/7.).collect();
+    let chord = Chord::from_samples(&samples, Some(&velocities), 1./1024.);
+    let dots = chord.dots();
+    assert_eq!(dots.len(), samples.len());
+    for (i, d) in dots.iter().enumerate() {
+        let end = i == 0 || i == dots.len()-1;
+        assert_eq!(d.is_fixed(), end, "dot {}", i);
+        assert_eq!(d.pos().y, if end {0.} else {samples[i]}, "dot {}", i);
+        assert_eq!(d.vel().y, if end {0.} else {velocities[i]}, "dot {}", i);
+    }
+}
+
+#[test]
+fn another_string_s_state_round_trips() {
+    let mut played = Chord::new(60, 1./1024.);
+    played.run(1234);
+    let heights: Vec<f64> = played.dots().iter().map(|d| d.pos().y).collect();
+    let speeds: Vec<f64> = played.dots().iter().map(|d| d.vel().y).collect();
+    let copy = Chord::from_samples(&heights, Some(&speeds), 1./1024.);
+    let read: Vec<(f64, f64)> = copy.dots().iter().map(|d| (d.pos().y, d.vel().y)).collect();
+    let wanted: Vec<(f64, f64)> = heights.into_iter().zip(speeds).collect();
+    assert_eq!(read, wanted);
+}
+
+#[test]
+#[should_panic(expected = "at least 3 samples")]
+fn two_samples_are_too_few() {
+    Chord::from_samples(&[0., 0.], None, 1./1024.);
+}
@@ -0,0 +1,74 @@
+// Saving a string and loading it back, and what load makes of files that
+// aren't a string it can run.
+extern crate chord_sim;
+extern crate serde_json;
+
+use std::fs;
+use std::path::PathBuf;
+
+use chord_sim::{Chord, Integrator};
+
+// A file of this name in the temporary directory, gone once the test is.
+struct Temp(PathBuf);
+
+impl Temp {
+    fn new(name: &str) -> Temp {
+        Temp(std::env::temp_dir().join(format!("chord_sim-{}-{}.json", std::process::id(), name)))
+    }
+}
+
+impl Drop for Temp {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn positions(chord: &Chord) -> Vec<[u64; 4]> {
+    chord.dots().iter().map(|d| [d.pos().x.to_bits(), d.pos().y.to_bits(), d.vel().x.to_bits(), d.vel().y.to_bits()]).collect()
+}
+
+#[test]
+fn a_loaded_string_carries_on_exactly_as_the_saved_one() {
+    let file = Temp::new("round-trip");
+    let mut chord = Chord::new_pluck_angled(60, 1./1024., 0.3, 8., 0.3);
+    chord.set_integrator(Integrator::Verlet);
+    chord.set_damping(1e-4);
+    chord.set_stiffness(1e-4);
+    chord.run(777);
+    chord.save(&file.0).unwrap();
+    let mut loaded = Chord::load(&file.0).unwrap();
+    assert_eq!(serde_json::to_string(&loaded).unwrap(), serde_json::to_string(&chord).unwrap());
+    chord.run(500);
+    loaded.run(500);
+    assert!(positions(&loaded) == positions(&chord));
+}
+
+// The saved JSON of the default string, edited by f, then loaded.
+fn load_edited(name: &str, f: impl FnOnce(&mut serde_json::Value)) -> String {
+    let file = Temp::new(name);
+    Chord::new(20, 1./1024.).save(&file.0).unwrap();
+    let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&file.0).unwrap()).unwrap();
+    f(&mut json);
+    fs::write(&file.0, json.to_string()).unwrap();
+    Chord::load(&file.0).err().expect("the edited string should be turned down").to_string()
+}
+
+#[test]
+fn a_wrong_dot_count_fails_cleanly() {
+    let err = load_edited("few-dots", |json| json["chord"].as_array_mut().unwrap().truncate(2));
+    assert!(err.contains("2 dots, a string needs at least 3"), "{}", err);
+    let err = load_edited("initial-dots", |json| {json["initial"].as_array_mut().unwrap().pop();});
+    assert!(err.contains("20 initial dots for 21 dots"), "{}", err);
+}
+
+#[test]
+fn a_nan_fails_cleanly() {
+    // JSON has no NaN, and serde writes one as null.
+    let err = load_edited("nan", |json| json["chord"][5]["pos"]["y"] = serde_json::Value::Null);
+    assert!(err.starts_with("not a saved string"), "{}", err);
+    let file = Temp::new("diverged");
+    let chord = Chord::from_samples(&[0., 1., f64::NAN, 1., 0.], None, 1./1024.);
+    let err = chord.save(&file.0).expect_err("a NaN string shouldn't be saved").to_string();
+    assert_eq!(err, "non-finite value in dot 2");
+    assert!(!file.0.exists());
+}
@@ -0,0 +1,25 @@
+// A hammer blow: the string starts flat with a kick of velocity, and the
+// two pulses it sends out mirror each other.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+#[test]
+fn a_strike_in_the_middle_stays_symmetric() {
+    let n = 80;
+    let mut chord = Chord::new_strike(n, 1./16., 0.5, 0.1, 0.05);
+    for _ in 0..10 {
+        chord.run(100);
+        let dots = chord.dots();
+        for i in 0..=n as usize/2 {
+            let (left, right) = (dots[i], dots[n as usize - i]);
+            assert!((left.pos().y - right.pos().y).abs() < 1e-12, "after {} ticks dot {} is at {} and its mirror at {}",
+                chord.ticks(), i, left.pos().y, right.pos().y);
+            assert!((left.pos().x + right.pos().x - n as f64).abs() < 1e-12, "after {} ticks dot {} is at x = {} and its mirror at {}",
+                chord.ticks(), i, left.pos().x, right.pos().x);
+        }
+    }
+    // And the blow did move it.
+    let peak = chord.dots().iter().map(|d| d.pos().y.abs()).fold(0., f64::max);
+    assert!(peak > 0.01, "the strike went nowhere");
+}
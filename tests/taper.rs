@@ -0,0 +1,32 @@
+// A tapered string with nothing to taper is the uniform string, to the bit.
+extern crate chord_sim;
+
+use chord_sim::{Chord, Integrator};
+
+fn bits(chord: &Chord) -> Vec<[u64; 5]> {
+    chord.dots().iter()
+        .map(|d| [d.pos().x.to_bits(), d.pos().y.to_bits(), d.vel().x.to_bits(), d.vel().y.to_bits(), d.mass().to_bits()])
+        .collect()
+}
+
+#[test]
+fn a_mass_ratio_of_one_is_the_uniform_string() {
+    for &integrator in &[Integrator::Euler, Integrator::Verlet, Integrator::Rk4, Integrator::Constrained] {
+        let mut uniform = Chord::new(60, 1./1024.);
+        let mut tapered = Chord::new_tapered(60, 1./1024., 1.);
+        assert_eq!(tapered.taper(), 1.);
+        assert_eq!(bits(&tapered), bits(&uniform));
+        assert_eq!(tapered.mode_frequencies(5), uniform.mode_frequencies(5));
+        uniform.set_integrator(integrator);
+        tapered.set_integrator(integrator);
+        uniform.run(2000);
+        tapered.run(2000);
+        assert_eq!(bits(&tapered), bits(&uniform), "{:?}", integrator);
+    }
+    // Whereas a real taper changes how it moves.
+    let mut uniform = Chord::new(60, 1./1024.);
+    let mut tapered = Chord::new_tapered(60, 1./1024., 4.);
+    uniform.run(2000);
+    tapered.run(2000);
+    assert_ne!(bits(&tapered), bits(&uniform));
+}
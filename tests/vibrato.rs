@@ -0,0 +1,55 @@
+// Vibrato wobbles k: at no depth it's not there at all, and slow enough it
+// leaves the string's energy where it was on average.
+extern crate chord_sim;
+
+use chord_sim::Chord;
+
+// Mean total energy over each of cycles stretches of length ticks.
+fn mean_energies(chord: &mut Chord, length: u64, cycles: usize) -> Vec<f64> {
+    (0..cycles).map(|_| {
+        (0..length).map(|_| {chord.tick(); chord.total_energy()}).sum::<f64>()/length as f64
+    }).collect()
+}
+
+fn bits(chord: &Chord) -> Vec<[u64; 2]> {
+    chord.dots().iter().map(|d| [d.pos().y.to_bits(), d.vel().y.to_bits()]).collect()
+}
+
+#[test]
+fn no_depth_is_no_vibrato() {
+    let mut plain = Chord::new(60, 1./1024.);
+    let mut wobbled = Chord::new(60, 1./1024.);
+    wobbled.set_vibrato(0., 0.01);
+    plain.run(5000);
+    wobbled.run(5000);
+    assert_eq!(bits(&plain), bits(&wobbled));
+    // Turning it down to nothing part way through stops it there.
+    wobbled.set_vibrato(0.3, 0.01);
+    wobbled.set_vibrato(0., 0.01);
+    plain.run(5000);
+    wobbled.run(5000);
+    assert_eq!(bits(&plain), bits(&wobbled));
+}
+
+#[test]
+fn slow_vibrato_leaves_the_mean_energy_steady() {
+    let mut chord = Chord::new_harmonic(20, 1./16., 1, 0.5);
+    let rate = chord.linear_fundamental()/10.;
+    chord.set_vibrato(0.5, rate);
+    let energies = mean_energies(&mut chord, (1./rate).round() as u64, 20);
+    for (cycle, e) in energies.iter().enumerate() {
+        assert!((e/energies[0] - 1.).abs() < 1e-5, "cycle {} averaged {} against {} in the first", cycle, e, energies[0]);
+    }
+}
+
+// Wobbling at twice the string's frequency is what's unsafe, at any depth:
+// even 2% puts half a percent more energy in over 20 cycles, and 10% twelve.
+#[test]
+fn vibrato_at_twice_the_frequency_pumps_energy_in() {
+    let mut chord = Chord::new_harmonic(20, 1./16., 1, 0.5);
+    let rate = 2.*chord.linear_fundamental();
+    chord.set_vibrato(0.02, rate);
+    let energies = mean_energies(&mut chord, (1./rate).round() as u64, 20);
+    assert!(energies.windows(2).all(|w| w[1] > w[0]), "the energy didn't keep growing: {:?}", energies);
+    assert!(energies[19] > 1.004*energies[0], "the energy only went from {} to {}", energies[0], energies[19]);
+}